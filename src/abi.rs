@@ -0,0 +1,231 @@
+//! Encoding and decoding of values with the [Solidity contract ABI](https://docs.soliditylang.org/en/latest/abi-spec.html).
+//!
+//! Only the types needed to read the NFT contracts are supported:
+//! the static types `uint256`, `address`, `bool` and `bytes1` to `bytes32`,
+//! and the dynamic types `bytes`, `string` and `T[]`.
+
+#[cfg(test)]
+mod tests;
+
+use crate::address::Address;
+use ethnum::u256;
+
+const WORD_SIZE: usize = 32;
+
+/// The type of an ABI-encoded value.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AbiType {
+    Uint256,
+    Address,
+    Bool,
+    /// `bytes<M>` with `0 < M <= 32`.
+    FixedBytes(usize),
+    Bytes,
+    String,
+    /// A dynamic array `T[]`.
+    Array(Box<AbiType>),
+}
+
+/// A value that can be ABI-encoded.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AbiValue {
+    Uint256(u256),
+    Address(Address),
+    Bool(bool),
+    FixedBytes(Vec<u8>),
+    Bytes(Vec<u8>),
+    String(String),
+    Array(Vec<AbiValue>),
+}
+
+impl AbiValue {
+    fn is_dynamic(&self) -> bool {
+        matches!(self, Self::Bytes(_) | Self::String(_) | Self::Array(_))
+    }
+}
+
+/// Returns the selector of the function with the given canonical signature, e.g. `ownerOf(uint256)`,
+/// i.e., the first 4 bytes of the Keccak-256 hash of the signature.
+pub fn selector(signature: &str) -> [u8; 4] {
+    let hash = ic_crypto_sha3::Keccak256::hash(signature.as_bytes());
+    [hash[0], hash[1], hash[2], hash[3]]
+}
+
+/// Encodes a call to the function with the given canonical signature.
+pub fn encode_call(signature: &str, args: &[AbiValue]) -> Vec<u8> {
+    let mut data = selector(signature).to_vec();
+    data.extend(encode(args));
+    data
+}
+
+/// Encodes the given values as a tuple:
+/// static values are encoded in place, whereas dynamic values are appended after all values,
+/// and replaced by their offset.
+pub fn encode(values: &[AbiValue]) -> Vec<u8> {
+    let head_size = WORD_SIZE * values.len();
+    let mut head = Vec::with_capacity(head_size);
+    let mut tail = Vec::new();
+    for value in values {
+        if value.is_dynamic() {
+            head.extend_from_slice(&uint_word(head_size + tail.len()));
+            tail.extend(encode_dynamic(value));
+        } else {
+            head.extend_from_slice(&encode_static(value));
+        }
+    }
+    head.extend(tail);
+    head
+}
+
+fn encode_static(value: &AbiValue) -> [u8; WORD_SIZE] {
+    match value {
+        AbiValue::Uint256(value) => value.to_be_bytes(),
+        AbiValue::Address(address) => address.to_fixed_size_data().0,
+        AbiValue::Bool(value) => uint_word(*value as usize),
+        AbiValue::FixedBytes(bytes) => {
+            assert!(
+                !bytes.is_empty() && bytes.len() <= WORD_SIZE,
+                "BUG: fixed-size bytes must have between 1 and 32 bytes"
+            );
+            let mut word = [0; WORD_SIZE];
+            word[..bytes.len()].copy_from_slice(bytes);
+            word
+        }
+        AbiValue::Bytes(_) | AbiValue::String(_) | AbiValue::Array(_) => {
+            panic!("BUG: {value:?} is not a static value")
+        }
+    }
+}
+
+fn encode_dynamic(value: &AbiValue) -> Vec<u8> {
+    match value {
+        AbiValue::Bytes(bytes) => encode_bytes(bytes),
+        AbiValue::String(string) => encode_bytes(string.as_bytes()),
+        AbiValue::Array(values) => {
+            let mut data = uint_word(values.len()).to_vec();
+            data.extend(encode(values));
+            data
+        }
+        _ => panic!("BUG: {value:?} is not a dynamic value"),
+    }
+}
+
+/// Encodes the length of the bytes, followed by the bytes right-padded to a multiple of 32 bytes.
+fn encode_bytes(bytes: &[u8]) -> Vec<u8> {
+    let mut data = uint_word(bytes.len()).to_vec();
+    data.extend_from_slice(bytes);
+    let padding = (WORD_SIZE - bytes.len() % WORD_SIZE) % WORD_SIZE;
+    data.resize(data.len() + padding, 0);
+    data
+}
+
+fn uint_word(value: usize) -> [u8; WORD_SIZE] {
+    u256::from(value as u64).to_be_bytes()
+}
+
+/// Decodes a tuple of values of the given types, e.g. the values returned by a function.
+pub fn decode(types: &[AbiType], data: &[u8]) -> Result<Vec<AbiValue>, String> {
+    decode_tuple(types, data, 0)
+}
+
+/// Decodes a tuple of values starting at the given position,
+/// from which the offsets of its dynamic values are counted.
+fn decode_tuple(types: &[AbiType], data: &[u8], start: usize) -> Result<Vec<AbiValue>, String> {
+    types
+        .iter()
+        .enumerate()
+        .map(|(i, abi_type)| decode_value(abi_type, data, start, start + WORD_SIZE * i))
+        .collect()
+}
+
+fn decode_value(
+    abi_type: &AbiType,
+    data: &[u8],
+    start: usize,
+    position: usize,
+) -> Result<AbiValue, String> {
+    match abi_type {
+        AbiType::Uint256 => read_word(data, position).map(AbiValue::Uint256),
+        AbiType::Address => {
+            let word = read_word(data, position)?.to_be_bytes();
+            Address::try_from(&word).map(AbiValue::Address)
+        }
+        AbiType::Bool => match read_word(data, position)? {
+            u256::ZERO => Ok(AbiValue::Bool(false)),
+            u256::ONE => Ok(AbiValue::Bool(true)),
+            value => Err(format!("invalid ABI-encoded bool {value}")),
+        },
+        AbiType::FixedBytes(size) => {
+            let word = read_word(data, position)?.to_be_bytes();
+            let (bytes, padding) = word.split_at((*size).min(WORD_SIZE));
+            if padding.iter().any(|byte| *byte != 0) {
+                return Err(format!("bytes{size} has non-zero padding"));
+            }
+            Ok(AbiValue::FixedBytes(bytes.to_vec()))
+        }
+        AbiType::Bytes => read_bytes(data, start, position).map(AbiValue::Bytes),
+        AbiType::String => {
+            let bytes = read_bytes(data, start, position)?;
+            String::from_utf8(bytes)
+                .map(AbiValue::String)
+                .map_err(|e| format!("ABI-encoded string is not UTF-8: {e}"))
+        }
+        AbiType::Array(item_type) => {
+            let offset = start + read_size(data, position)?;
+            let length = read_size(data, offset)?;
+            let item_types = vec![item_type.as_ref().clone(); length];
+            decode_tuple(&item_types, data, offset + WORD_SIZE).map(AbiValue::Array)
+        }
+    }
+}
+
+/// Reads the `bytes` whose offset from the given start is stored at the given position.
+fn read_bytes(data: &[u8], start: usize, position: usize) -> Result<Vec<u8>, String> {
+    let offset = start + read_size(data, position)?;
+    let length = read_size(data, offset)?;
+    let content = offset + WORD_SIZE;
+    data.get(content..content + length)
+        .map(<[u8]>::to_vec)
+        .ok_or_else(|| {
+            format!(
+                "ABI-encoded data of {} bytes has no {length} bytes at position {content}",
+                data.len()
+            )
+        })
+}
+
+/// Reads the 32-byte word at the given position of ABI-encoded data.
+pub(crate) fn read_word(data: &[u8], position: usize) -> Result<u256, String> {
+    position
+        .checked_add(WORD_SIZE)
+        .and_then(|end| data.get(position..end))
+        .map(|word| u256::from_be_bytes(word.try_into().expect("BUG: word is 32 bytes")))
+        .ok_or_else(|| {
+            format!(
+                "ABI-encoded data of {} bytes has no word at position {position}",
+                data.len()
+            )
+        })
+}
+
+/// Reads the offset or length at the given position of ABI-encoded data,
+/// which cannot exceed the size of the data.
+pub(crate) fn read_size(data: &[u8], position: usize) -> Result<usize, String> {
+    let size = read_word(data, position)?;
+    if size > u256::from(data.len() as u64) {
+        return Err(format!(
+            "ABI-encoded size {size} exceeds the {} bytes of data",
+            data.len()
+        ));
+    }
+    Ok(size.as_usize())
+}
+
+/// Reads a dynamic `uint256[]` whose offset is stored at the given position of ABI-encoded data.
+pub(crate) fn read_uint256_array(data: &[u8], position: usize) -> Result<Vec<u256>, String> {
+    let offset = read_size(data, position)?;
+    let length = read_size(data, offset)?;
+    (1..=length)
+        .map(|i| read_word(data, offset + WORD_SIZE * i))
+        .collect()
+}
@@ -0,0 +1,164 @@
+use crate::abi::{decode, encode, encode_call, selector, AbiType, AbiValue};
+use crate::address::Address;
+use ethnum::u256;
+
+mod selector {
+    use super::*;
+
+    #[test]
+    fn should_compute_selectors_of_nft_functions() {
+        for (signature, expected) in [
+            ("ownerOf(uint256)", "6352211e"),
+            ("tokenURI(uint256)", "c87b56dd"),
+            ("totalSupply()", "18160ddd"),
+            ("name()", "06fdde03"),
+            ("symbol()", "95d89b41"),
+        ] {
+            assert_eq!(hex::encode(selector(signature)), expected, "{signature}");
+        }
+    }
+}
+
+mod encode {
+    use super::*;
+
+    #[test]
+    fn should_encode_static_arguments_in_place() {
+        assert_eq!(
+            hex::encode(encode_call(
+                "ownerOf(uint256)",
+                &[AbiValue::Uint256(u256::from(42_u8))]
+            )),
+            "6352211e000000000000000000000000000000000000000000000000000000000000002a"
+        );
+    }
+
+    #[test]
+    fn should_encode_dynamic_arguments_after_static_ones() {
+        // Example of the Solidity documentation.
+        let data = encode_call(
+            "sam(bytes,bool,uint256[])",
+            &[
+                AbiValue::Bytes(b"dave".to_vec()),
+                AbiValue::Bool(true),
+                AbiValue::Array(vec![
+                    AbiValue::Uint256(u256::from(1_u8)),
+                    AbiValue::Uint256(u256::from(2_u8)),
+                    AbiValue::Uint256(u256::from(3_u8)),
+                ]),
+            ],
+        );
+
+        assert_eq!(
+            hex::encode(data),
+            [
+                "a5643bf2",
+                "0000000000000000000000000000000000000000000000000000000000000060",
+                "0000000000000000000000000000000000000000000000000000000000000001",
+                "00000000000000000000000000000000000000000000000000000000000000a0",
+                "0000000000000000000000000000000000000000000000000000000000000004",
+                "6461766500000000000000000000000000000000000000000000000000000000",
+                "0000000000000000000000000000000000000000000000000000000000000003",
+                "0000000000000000000000000000000000000000000000000000000000000001",
+                "0000000000000000000000000000000000000000000000000000000000000002",
+                "0000000000000000000000000000000000000000000000000000000000000003",
+            ]
+            .concat()
+        );
+    }
+}
+
+mod decode {
+    use super::*;
+
+    #[test]
+    fn should_decode_string_output() {
+        let output = hex::decode(
+            [
+                "0000000000000000000000000000000000000000000000000000000000000020",
+                "0000000000000000000000000000000000000000000000000000000000000005",
+                "50756e6b73000000000000000000000000000000000000000000000000000000",
+            ]
+            .concat(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            decode(&[AbiType::String], &output),
+            Ok(vec![AbiValue::String("Punks".to_string())])
+        );
+    }
+
+    #[test]
+    fn should_decode_encoded_values() {
+        let types = [
+            AbiType::Address,
+            AbiType::String,
+            AbiType::FixedBytes(4),
+            AbiType::Array(Box::new(AbiType::String)),
+            AbiType::Bytes,
+            AbiType::Uint256,
+        ];
+        let values = vec![
+            AbiValue::Address(
+                "0xb44B5e756A894775FC32EDdf3314Bb1B1944dC34"
+                    .parse()
+                    .unwrap(),
+            ),
+            AbiValue::String(
+                "ipfs://bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi/1".to_string(),
+            ),
+            AbiValue::FixedBytes(vec![0xde, 0xad, 0xbe, 0xef]),
+            AbiValue::Array(vec![
+                AbiValue::String("one".to_string()),
+                AbiValue::String("".to_string()),
+                AbiValue::String("a".repeat(40)),
+            ]),
+            AbiValue::Bytes(vec![0xff; 32]),
+            AbiValue::Uint256(u256::MAX),
+        ];
+
+        assert_eq!(decode(&types, &encode(&values)), Ok(values));
+    }
+
+    #[test]
+    fn should_reject_invalid_output() {
+        let word = |value: u8| u256::from(value).to_be_bytes().to_vec();
+
+        for (abi_type, output) in [
+            (AbiType::Uint256, vec![0; 31]),
+            (AbiType::Bool, word(2)),
+            (AbiType::Address, u256::MAX.to_be_bytes().to_vec()),
+            (AbiType::FixedBytes(4), u256::MAX.to_be_bytes().to_vec()),
+            // The string is longer than the output.
+            (
+                AbiType::String,
+                [word(32), word(33), vec![0x61; 32]].concat(),
+            ),
+            // The offset points past the output.
+            (AbiType::Bytes, [word(64), word(0)].concat()),
+            (
+                AbiType::String,
+                [word(32), word(2), vec![0xc3, 0x28], vec![0; 30]].concat(),
+            ),
+        ] {
+            assert!(
+                decode(std::slice::from_ref(&abi_type), &output).is_err(),
+                "{abi_type:?} should not decode from {}",
+                hex::encode(&output)
+            );
+        }
+    }
+
+    #[test]
+    fn should_decode_owner() {
+        let owner: Address = "0xdd2851cdd40ae6536831558dd46db62fac7a844d"
+            .parse()
+            .unwrap();
+
+        assert_eq!(
+            decode(&[AbiType::Address], &owner.to_fixed_size_data().0),
+            Ok(vec![AbiValue::Address(owner)])
+        );
+    }
+}
@@ -0,0 +1,229 @@
+//! Assets uploaded by the controllers of the minter, such as the layers of the token images.
+//!
+//! Assets are larger than a single ingress message, so they are uploaded in batches:
+//! a controller creates a batch, uploads the content in chunks, and commits the batch
+//! with the SHA-256 hash of the whole content.
+//! Committed contents are appended to a stable log (see [`crate::storage`]) and each commit is
+//! recorded as an [`EventType::CommittedAsset`] event, so that the inputs of the generator are auditable.
+//! Pending batches only live in the heap and are discarded on upgrade.
+
+#[cfg(test)]
+mod tests;
+
+use crate::endpoints::{AssetError, CommitAssetBatchArg, CreateAssetBatchArg, UploadAssetChunkArg};
+use crate::logs::INFO;
+use crate::state::{audit::process_event, event::EventType, mutate_state, read_state};
+use crate::storage;
+use ic_canister_log::log;
+use minicbor::{Decode, Encode};
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// The maximum size in bytes of an asset.
+pub const MAX_ASSET_SIZE: usize = 8 * 1024 * 1024;
+
+/// The maximum number of batches that can be pending at the same time.
+pub const MAX_PENDING_BATCHES: usize = 8;
+
+/// Pending batches that were not committed within this delay are discarded.
+pub const BATCH_EXPIRATION: Duration = Duration::from_secs(60 * 60);
+
+const MAX_KEY_LENGTH: usize = 256;
+const MAX_CONTENT_TYPE_LENGTH: usize = 128;
+
+/// An asset committed by a controller.
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+pub struct Asset {
+    /// The key under which the asset is referenced, e.g. "background/blue.png".
+    #[n(0)]
+    pub key: String,
+    /// The media type of the asset, e.g. "image/png".
+    #[n(1)]
+    pub content_type: String,
+    /// The SHA-256 hash of the content.
+    #[cbor(n(2), with = "minicbor::bytes")]
+    pub sha256: [u8; 32],
+    /// The size of the content in bytes.
+    #[n(3)]
+    pub size: u64,
+    /// The index of the content in the stable asset log.
+    #[n(4)]
+    pub content_index: u64,
+}
+
+impl Asset {
+    /// Reads the content of the asset from stable memory.
+    pub fn content(&self) -> Vec<u8> {
+        storage::asset_content(self.content_index).unwrap_or_else(|| {
+            panic!(
+                "BUG: missing content of asset '{}' at index {}",
+                self.key, self.content_index
+            )
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct PendingBatch {
+    key: String,
+    content_type: String,
+    content: Vec<u8>,
+    created_at: u64,
+}
+
+#[derive(Default)]
+struct PendingBatches {
+    batches: BTreeMap<u64, PendingBatch>,
+    next_batch_id: u64,
+}
+
+thread_local! {
+    static PENDING_BATCHES: RefCell<PendingBatches> = RefCell::default();
+}
+
+/// Creates a new batch for the given asset and returns its ID.
+/// `now` is the current time in nanoseconds since the epoch.
+pub fn create_batch(arg: CreateAssetBatchArg, now: u64) -> Result<u64, AssetError> {
+    validate_key(&arg.key)?;
+    validate_content_type(&arg.content_type)?;
+    PENDING_BATCHES.with(|pending| {
+        let mut pending = pending.borrow_mut();
+        pending.batches.retain(|_, batch| {
+            now.saturating_sub(batch.created_at) < BATCH_EXPIRATION.as_nanos() as u64
+        });
+        if pending.batches.len() >= MAX_PENDING_BATCHES {
+            return Err(AssetError::TooManyPendingBatches {
+                max_pending_batches: MAX_PENDING_BATCHES as u64,
+            });
+        }
+        let batch_id = pending.next_batch_id;
+        pending.next_batch_id += 1;
+        pending.batches.insert(
+            batch_id,
+            PendingBatch {
+                key: arg.key,
+                content_type: arg.content_type,
+                content: vec![],
+                created_at: now,
+            },
+        );
+        Ok(batch_id)
+    })
+}
+
+/// Appends a chunk to the content of a pending batch.
+pub fn upload_chunk(arg: UploadAssetChunkArg) -> Result<(), AssetError> {
+    PENDING_BATCHES.with(|pending| {
+        let mut pending = pending.borrow_mut();
+        let batch = pending
+            .batches
+            .get_mut(&arg.batch_id)
+            .ok_or(AssetError::BatchNotFound {
+                batch_id: arg.batch_id,
+            })?;
+        if batch.content.len() + arg.content.len() > MAX_ASSET_SIZE {
+            return Err(AssetError::AssetTooLarge {
+                max_size: MAX_ASSET_SIZE as u64,
+            });
+        }
+        batch.content.extend_from_slice(&arg.content);
+        Ok(())
+    })
+}
+
+/// Commits a pending batch, replacing any asset previously committed under the same key.
+///
+/// The batch is discarded if its content does not match the expected hash,
+/// or if it would change the image of a minted token.
+pub fn commit_batch(arg: CommitAssetBatchArg) -> Result<(), AssetError> {
+    let (batch, sha256) = take_verified_batch(arg)?;
+    let asset = Asset {
+        sha256,
+        size: batch.content.len() as u64,
+        content_index: storage::total_asset_content_count(),
+        key: batch.key,
+        content_type: batch.content_type,
+    };
+    read_state(|s| {
+        let is_modified = s
+            .assets
+            .get(&asset.key)
+            .is_some_and(|committed| committed.sha256 != asset.sha256);
+        if is_modified && s.is_asset_drawn(&asset.key) {
+            return Err(AssetError::AssetInUse {
+                key: asset.key.clone(),
+            });
+        }
+        let mut assets = s.assets.clone();
+        assets.insert(asset.key.clone(), asset.clone());
+        s.generator_configs()
+            .try_for_each(|config| crate::svg::validate_layers(config, &assets))
+            .map_err(AssetError::InvalidLayers)
+    })?;
+    assert_eq!(
+        storage::record_asset_content(batch.content),
+        asset.content_index
+    );
+    log!(
+        INFO,
+        "[commit_asset_batch]: committed asset '{}' ({} bytes, sha256 {})",
+        asset.key,
+        asset.size,
+        hex::encode(asset.sha256)
+    );
+    mutate_state(|s| process_event(s, EventType::CommittedAsset(asset)));
+    Ok(())
+}
+
+fn take_verified_batch(
+    CommitAssetBatchArg { batch_id, sha256 }: CommitAssetBatchArg,
+) -> Result<(PendingBatch, [u8; 32]), AssetError> {
+    let batch = PENDING_BATCHES
+        .with(|pending| pending.borrow_mut().batches.remove(&batch_id))
+        .ok_or(AssetError::BatchNotFound { batch_id })?;
+    let actual = ic_crypto_sha2::Sha256::hash(&batch.content);
+    if sha256.as_slice() != actual {
+        return Err(AssetError::HashMismatch {
+            expected: hex::encode(sha256),
+            actual: hex::encode(actual),
+        });
+    }
+    Ok((batch, actual))
+}
+
+fn validate_key(key: &str) -> Result<(), AssetError> {
+    if key.is_empty() || key.len() > MAX_KEY_LENGTH {
+        return Err(AssetError::InvalidKey(format!(
+            "key must be between 1 and {MAX_KEY_LENGTH} bytes"
+        )));
+    }
+    if key.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        return Err(AssetError::InvalidKey(
+            "key cannot contain whitespace or control characters".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn validate_content_type(content_type: &str) -> Result<(), AssetError> {
+    if content_type.is_empty() || content_type.len() > MAX_CONTENT_TYPE_LENGTH {
+        return Err(AssetError::InvalidContentType(format!(
+            "content type must be between 1 and {MAX_CONTENT_TYPE_LENGTH} bytes"
+        )));
+    }
+    // Content types are embedded as is in the data URIs of the rendered images,
+    // hence only a bare `type/subtype` without parameters is accepted.
+    let is_name = |name: &str| {
+        name.starts_with(|c: char| c.is_ascii_alphanumeric())
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c))
+    };
+    match content_type.split_once('/') {
+        Some((type_, subtype)) if is_name(type_) && is_name(subtype) => Ok(()),
+        _ => Err(AssetError::InvalidContentType(format!(
+            "content type '{content_type}' must be of the form 'type/subtype'"
+        ))),
+    }
+}
@@ -0,0 +1,129 @@
+use crate::assets::{
+    create_batch, take_verified_batch, upload_chunk, BATCH_EXPIRATION, MAX_ASSET_SIZE,
+    MAX_PENDING_BATCHES,
+};
+use crate::endpoints::{AssetError, CommitAssetBatchArg, CreateAssetBatchArg, UploadAssetChunkArg};
+use serde_bytes::ByteBuf;
+
+const NOW: u64 = 1_700_000_000_000_000_000;
+
+#[test]
+fn should_concatenate_chunks() {
+    let batch_id = create_batch(create_arg("background/blue.png"), NOW).unwrap();
+    upload_chunk(chunk(batch_id, b"blue ")).unwrap();
+    upload_chunk(chunk(batch_id, b"background")).unwrap();
+
+    let (batch, sha256) = take_verified_batch(CommitAssetBatchArg {
+        batch_id,
+        sha256: ByteBuf::from(ic_crypto_sha2::Sha256::hash(b"blue background").to_vec()),
+    })
+    .unwrap();
+
+    assert_eq!(batch.key, "background/blue.png");
+    assert_eq!(batch.content, b"blue background");
+    assert_eq!(sha256, ic_crypto_sha2::Sha256::hash(b"blue background"));
+}
+
+#[test]
+fn should_discard_batch_with_hash_mismatch() {
+    let batch_id = create_batch(create_arg("background/blue.png"), NOW).unwrap();
+    upload_chunk(chunk(batch_id, b"blue background")).unwrap();
+
+    let commit_arg = CommitAssetBatchArg {
+        batch_id,
+        sha256: ByteBuf::from(vec![0; 32]),
+    };
+    assert!(matches!(
+        take_verified_batch(commit_arg.clone()),
+        Err(AssetError::HashMismatch { .. })
+    ));
+    assert_eq!(
+        take_verified_batch(commit_arg),
+        Err(AssetError::BatchNotFound { batch_id })
+    );
+}
+
+#[test]
+fn should_reject_chunks_exceeding_max_size() {
+    let batch_id = create_batch(create_arg("background/blue.png"), NOW).unwrap();
+    upload_chunk(chunk(batch_id, &vec![0; MAX_ASSET_SIZE])).unwrap();
+
+    assert_eq!(
+        upload_chunk(chunk(batch_id, b"x")),
+        Err(AssetError::AssetTooLarge {
+            max_size: MAX_ASSET_SIZE as u64
+        })
+    );
+}
+
+#[test]
+fn should_limit_pending_batches_until_they_expire() {
+    for i in 0..MAX_PENDING_BATCHES {
+        create_batch(create_arg(&format!("layer/{i}.png")), NOW).unwrap();
+    }
+    assert_eq!(
+        create_batch(create_arg("layer/extra.png"), NOW),
+        Err(AssetError::TooManyPendingBatches {
+            max_pending_batches: MAX_PENDING_BATCHES as u64
+        })
+    );
+
+    let later = NOW + BATCH_EXPIRATION.as_nanos() as u64;
+    assert!(create_batch(create_arg("layer/extra.png"), later).is_ok());
+}
+
+#[test]
+fn should_reject_invalid_keys_and_content_types() {
+    for key in ["", "with space.png", &"x".repeat(257)] {
+        assert!(matches!(
+            create_batch(create_arg(key), NOW),
+            Err(AssetError::InvalidKey(_))
+        ));
+    }
+    for content_type in [
+        "",
+        "image/png\"",
+        "text/html<script>",
+        "image",
+        "image/",
+        "/png",
+        "image/svg+xml/extra",
+        "image/png;base64,PHN2Zz4=",
+        "image/png charset=utf-8",
+    ] {
+        assert!(matches!(
+            create_batch(
+                CreateAssetBatchArg {
+                    key: "layer.png".to_string(),
+                    content_type: content_type.to_string(),
+                },
+                NOW
+            ),
+            Err(AssetError::InvalidContentType(_))
+        ));
+    }
+    for content_type in ["image/png", "image/svg+xml", "image/vnd.microsoft.icon"] {
+        assert!(create_batch(
+            CreateAssetBatchArg {
+                key: content_type.to_string(),
+                content_type: content_type.to_string(),
+            },
+            NOW
+        )
+        .is_ok());
+    }
+}
+
+fn create_arg(key: &str) -> CreateAssetBatchArg {
+    CreateAssetBatchArg {
+        key: key.to_string(),
+        content_type: "image/png".to_string(),
+    }
+}
+
+fn chunk(batch_id: u64, content: &[u8]) -> UploadAssetChunkArg {
+    UploadAssetChunkArg {
+        batch_id,
+        content: ByteBuf::from(content.to_vec()),
+    }
+}
@@ -1,6 +1,7 @@
 pub mod id;
 pub mod nat;
 pub mod principal;
+pub mod rpc_providers;
 pub mod u256;
 
 #[cfg(test)]
@@ -0,0 +1,34 @@
+use crate::eth_rpc_client::providers::RpcProvider;
+use minicbor::data::Type;
+use minicbor::decode::{Decoder, Error};
+use minicbor::encode::{Encoder, Write};
+use minicbor::{Decode, Encode};
+
+/// Decodes the providers of a chain spec, which were encoded as plain URLs
+/// before providers could be configured with headers and limits.
+pub fn decode<Ctx>(d: &mut Decoder<'_>, ctx: &mut Ctx) -> Result<Vec<RpcProvider>, Error> {
+    let len = d
+        .array()?
+        .ok_or_else(|| Error::message("expected a definite-length array of providers"))?;
+    let mut providers = Vec::with_capacity(len as usize);
+    for _ in 0..len {
+        let provider = match d.datatype()? {
+            Type::String => RpcProvider::from(d.str()?.to_string()),
+            _ => RpcProvider::decode(d, ctx)?,
+        };
+        providers.push(provider);
+    }
+    Ok(providers)
+}
+
+pub fn encode<Ctx, W: Write>(
+    v: &[RpcProvider],
+    e: &mut Encoder<W>,
+    ctx: &mut Ctx,
+) -> Result<(), minicbor::encode::Error<W::Error>> {
+    e.array(v.len() as u64)?;
+    for provider in v {
+        provider.encode(e, ctx)?;
+    }
+    Ok(())
+}
@@ -27,6 +27,12 @@ struct U256Container {
     pub value: u256,
 }
 
+#[derive(Debug, PartialEq, Eq, Encode, Decode)]
+struct OptU256Container {
+    #[cbor(n(0), with = "crate::cbor::u256::option")]
+    pub value: Option<u256>,
+}
+
 #[derive(Debug, PartialEq, Eq, Encode, Decode)]
 struct NatContainer {
     #[cbor(n(0), with = "crate::cbor::nat")]
@@ -72,6 +78,13 @@ proptest! {
         })?;
     }
 
+    #[test]
+    fn opt_u256_encoding_roundtrip(n in proptest::option::of((any::<u128>(), any::<u128>()))) {
+        check_roundtrip(&OptU256Container {
+            value: n.map(|(hi, lo)| U256([hi, lo])),
+        })?;
+    }
+
     #[test]
     fn checked_amount_of_encoding_roundtrip((hi, lo) in (any::<u128>(), any::<u128>())) {
         check_roundtrip(&U256NewtypeContainer {
@@ -0,0 +1,190 @@
+//! Certification of the HTTP responses served by the minter.
+//!
+//! The minter maintains a hash tree mapping each served path to the SHA-256 hash of its body,
+//! and sets the root hash as the certified data of the canister,
+//! see <https://internetcomputer.org/docs/current/references/ic-interface-spec#http-gateway-certification>.
+//! The tree is not persisted: it is rebuilt from the state on init and upgrade,
+//! in chunks of [`REBUILD_CHUNK_SIZE`] tokens certified by successive timers,
+//! so that the rebuild stays within the instruction limit of a message however many tokens were minted.
+//! Responses of tokens that were not certified yet are served without certificate.
+
+#[cfg(test)]
+mod tests;
+
+use crate::collections::TokenKey;
+use crate::metadata::{base_url, collection_asset, token_asset, TokenPath, COLLECTION_PATH};
+use crate::state::{read_state, State};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use ic_certified_map::{labeled, labeled_hash, AsHashTree, Hash, RbTree};
+use serde::Serialize;
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+use std::ops::Bound;
+use std::time::Duration;
+
+const HTTP_ASSETS_LABEL: &[u8] = b"http_assets";
+
+/// The maximum number of tokens certified by a single step of the rebuild of the tree.
+pub const REBUILD_CHUNK_SIZE: usize = 500;
+
+thread_local! {
+    static ASSET_HASHES: RefCell<RbTree<String, Hash>> = RefCell::new(RbTree::new());
+    /// The progress of the rebuild of the tree, if one is in progress.
+    static REBUILD: RefCell<Option<RebuildProgress>> = RefCell::default();
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum RebuildProgress {
+    /// No token was certified yet.
+    Started,
+    /// The tokens up to the given one were certified.
+    CertifiedUpTo(TokenKey),
+}
+
+/// Certifies the responses served for the given minted or burned token.
+pub fn certify_token(state: &State, token: &TokenKey) {
+    certify_tokens(state, std::slice::from_ref(token));
+}
+
+/// Certifies the responses served for the given tokens,
+/// and stops certifying the responses that are no longer served, such as those of rolled back tokens.
+pub fn certify_tokens(state: &State, tokens: &[TokenKey]) {
+    if tokens.is_empty() {
+        return;
+    }
+    let base_url = base_url();
+    ASSET_HASHES.with(|tree| {
+        let mut tree = tree.borrow_mut();
+        for token in tokens {
+            for (path, _) in TokenPath::all(token) {
+                tree.delete(path.as_bytes());
+            }
+            for (path, hash) in token_asset_hashes(state, token, &base_url) {
+                tree.insert(path, hash);
+            }
+        }
+    });
+    update_certified_data();
+}
+
+/// Certifies the collection metadata and starts certifying the responses served for all the minted
+/// and burned tokens in the background, discarding any previously certified response.
+///
+/// Must be called whenever the certified tree may be out of date, such as on init and upgrade.
+pub fn start_rebuild(state: &State) {
+    ASSET_HASHES.with(|tree| {
+        let mut tree = tree.borrow_mut();
+        *tree = RbTree::new();
+        tree.insert(
+            COLLECTION_PATH.to_string(),
+            ic_crypto_sha2::Sha256::hash(&collection_asset(state).body),
+        );
+    });
+    update_certified_data();
+    let is_in_progress = REBUILD.with(|rebuild| {
+        rebuild
+            .borrow_mut()
+            .replace(RebuildProgress::Started)
+            .is_some()
+    });
+    if !is_in_progress {
+        schedule_rebuild_step();
+    }
+}
+
+fn schedule_rebuild_step() {
+    ic_cdk_timers::set_timer(Duration::ZERO, || {
+        if read_state(certify_next_chunk) {
+            schedule_rebuild_step();
+        }
+    });
+}
+
+/// Certifies the next chunk of tokens of the rebuild in progress,
+/// and returns whether tokens remain to be certified.
+fn certify_next_chunk(state: &State) -> bool {
+    let after = match REBUILD.with(|rebuild| rebuild.borrow().clone()) {
+        Some(RebuildProgress::Started) => None,
+        Some(RebuildProgress::CertifiedUpTo(token)) => Some(token),
+        None => return false,
+    };
+    let tokens = next_tokens_to_certify(state, after.as_ref(), REBUILD_CHUNK_SIZE);
+    certify_tokens(state, &tokens);
+    let progress = match tokens.last() {
+        Some(last) if tokens.len() == REBUILD_CHUNK_SIZE => {
+            Some(RebuildProgress::CertifiedUpTo(last.clone()))
+        }
+        _ => None,
+    };
+    let is_in_progress = progress.is_some();
+    REBUILD.with(|rebuild| *rebuild.borrow_mut() = progress);
+    is_in_progress
+}
+
+/// Returns up to `limit` minted or burned tokens following the given one, in ascending order.
+fn next_tokens_to_certify(state: &State, after: Option<&TokenKey>, limit: usize) -> Vec<TokenKey> {
+    let range = match after {
+        Some(token) => (Bound::Excluded(token), Bound::Unbounded),
+        None => (Bound::Unbounded, Bound::Unbounded),
+    };
+    let tokens: BTreeSet<&TokenKey> = state
+        .minted_events
+        .range_keys(range)
+        .take(limit)
+        .chain(
+            state
+                .burned_tokens
+                .range(range)
+                .map(|(token, _)| token)
+                .take(limit),
+        )
+        .collect();
+    tokens.into_iter().take(limit).cloned().collect()
+}
+
+/// Returns the `IC-Certificate` header certifying the response served at the given path,
+/// or `None` if the response is not certified.
+///
+/// The certificate is only available in query calls.
+pub fn certificate_header(path: &str) -> Option<(String, String)> {
+    let certificate = ic_cdk::api::data_certificate()?;
+    let witness = ASSET_HASHES.with(|tree| {
+        let tree = tree.borrow();
+        tree.get(path.as_bytes())?;
+        let witness = labeled(HTTP_ASSETS_LABEL, tree.witness(path.as_bytes()));
+        let mut serializer = serde_cbor::ser::Serializer::new(vec![]);
+        serializer.self_describe().unwrap();
+        witness
+            .serialize(&mut serializer)
+            .expect("BUG: failed to serialize the hash tree");
+        Some(serializer.into_inner())
+    })?;
+    Some((
+        "IC-Certificate".to_string(),
+        format!(
+            "certificate=:{}:, tree=:{}:",
+            BASE64.encode(certificate),
+            BASE64.encode(witness)
+        ),
+    ))
+}
+
+fn update_certified_data() {
+    ASSET_HASHES.with(|tree| {
+        ic_cdk::api::set_certified_data(&labeled_hash(
+            HTTP_ASSETS_LABEL,
+            &tree.borrow().root_hash(),
+        ))
+    });
+}
+
+fn token_asset_hashes(state: &State, token: &TokenKey, base_url: &str) -> Vec<(String, Hash)> {
+    TokenPath::all(token)
+        .into_iter()
+        .filter_map(|(path, token_path)| {
+            token_asset(state, token_path, base_url)
+                .map(|asset| (path, ic_crypto_sha2::Sha256::hash(&asset.body)))
+        })
+        .collect()
+}
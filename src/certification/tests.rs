@@ -0,0 +1,85 @@
+use crate::address::Address;
+use crate::certification::{next_tokens_to_certify, token_asset_hashes};
+use crate::collections::TokenKey;
+use crate::eth_logs::TransferEvent;
+use crate::metadata::{token_asset, TokenPath};
+use crate::numeric::BlockNumber;
+use crate::state::audit::{apply_state_transition, EventType};
+use crate::test_fixtures::{holder, mint_event, state, transfer_event};
+use ethnum::u256;
+
+const BASE_URL: &str = "https://2ibo7-dia.icp0.io";
+
+#[test]
+fn should_not_certify_unminted_token() {
+    let mut state = state();
+    apply_state_transition(&mut state, &EventType::AcceptedMint(mint_event(1, 1)));
+
+    assert!(token_asset_hashes(&state, &TokenKey::from(u256::from(1_u8)), BASE_URL).is_empty());
+}
+
+#[test]
+fn should_certify_all_paths_of_minted_token() {
+    let mut state = state();
+    let event = mint_event(1, 1);
+    apply_state_transition(&mut state, &EventType::AcceptedMint(event.clone()));
+    apply_state_transition(
+        &mut state,
+        &EventType::MintedNft {
+            event_source: event.source(),
+        },
+    );
+
+    let token = TokenKey::from(u256::from(1_u8));
+    let hashes = token_asset_hashes(&state, &token, BASE_URL);
+
+    let paths: Vec<_> = hashes.iter().map(|(path, _)| path.as_str()).collect();
+    assert_eq!(
+        paths,
+        vec!["/token/1", "/token/1.json", "/token/1/image.svg"]
+    );
+    for ((_, hash), (_, token_path)) in hashes.iter().zip(TokenPath::all(&token)) {
+        let asset = token_asset(&state, token_path, BASE_URL).unwrap();
+        assert_eq!(hash, &ic_crypto_sha2::Sha256::hash(&asset.body));
+    }
+}
+
+#[test]
+fn should_list_minted_and_burned_tokens_in_chunks() {
+    let mut state = state();
+    for token_id in 1..=3 {
+        let event = mint_event(token_id, token_id);
+        apply_state_transition(&mut state, &EventType::AcceptedMint(event.clone()));
+        apply_state_transition(
+            &mut state,
+            &EventType::MintedNft {
+                event_source: event.source(),
+            },
+        );
+    }
+    // Token 2 is minted and burned, token 5 was minted before the first scraped block.
+    for token_id in [2, 5] {
+        apply_state_transition(&mut state, &EventType::BurnedNft(burn_event(token_id)));
+    }
+    let token = |token_id: u64| TokenKey::from(u256::from(token_id));
+
+    assert_eq!(
+        next_tokens_to_certify(&state, None, 2),
+        vec![token(1), token(2)]
+    );
+    assert_eq!(
+        next_tokens_to_certify(&state, Some(&token(2)), 2),
+        vec![token(3), token(5)]
+    );
+    assert_eq!(next_tokens_to_certify(&state, Some(&token(5)), 2), vec![]);
+}
+
+fn burn_event(token_id: u64) -> TransferEvent {
+    TransferEvent {
+        transaction_hash: "0x5e5a5954e0a6fe5e61067330ea6f1398425a5e01a1dc1ef895b5dde00994e796"
+            .parse()
+            .unwrap(),
+        block_number: BlockNumber::new(3960700u128),
+        ..transfer_event(token_id, holder(), Address::ZERO, token_id)
+    }
+}
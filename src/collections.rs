@@ -0,0 +1,168 @@
+//! Additional NFT collections watched by the minter.
+//!
+//! Besides the collection configured at init, the minter can watch the contracts of other collections,
+//! which are added and removed by upgrades. Each additional collection is scraped from its own starting block,
+//! generates the traits of its tokens with its own configuration,
+//! and serves its tokens under its own path prefix, i.e., at `/{collection}/token/{id}`,
+//! while the tokens of the collection configured at init are served at `/token/{id}`.
+//! Each additional collection also has its own metadata, from which the names of its tokens are derived.
+//! The scraping mode, the token standard and the assets are shared by all collections,
+//! whereas the delayed reveal only applies to the collection configured at init.
+
+#[cfg(test)]
+mod tests;
+
+use crate::address::Address;
+use crate::generator::GeneratorConfig;
+use crate::metadata::CollectionMetadata;
+use crate::numeric::BlockNumber;
+use candid::{CandidType, Deserialize, Nat};
+use ethnum::u256;
+use minicbor::{Decode, Encode};
+use std::collections::BTreeSet;
+use std::fmt;
+use std::str::FromStr;
+
+/// The maximum length of a collection ID.
+pub const MAX_COLLECTION_ID_LENGTH: usize = 64;
+
+/// The path segment preceding token IDs, which cannot be used as a collection ID.
+const TOKEN_PATH_SEGMENT: &str = "token";
+
+/// The ID of an additional collection, which is also the prefix of the paths of its tokens.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Encode, Decode)]
+#[cbor(transparent)]
+pub struct CollectionId(#[n(0)] String);
+
+impl CollectionId {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl FromStr for CollectionId {
+    type Err = String;
+
+    fn from_str(id: &str) -> Result<Self, Self::Err> {
+        if id.is_empty() || id.len() > MAX_COLLECTION_ID_LENGTH {
+            return Err(format!(
+                "collection ID must have between 1 and {MAX_COLLECTION_ID_LENGTH} characters"
+            ));
+        }
+        if !id
+            .bytes()
+            .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-' || b == b'_')
+        {
+            return Err(format!(
+                "collection ID '{id}' can only contain lowercase letters, digits, '-' and '_'"
+            ));
+        }
+        if id == TOKEN_PATH_SEGMENT {
+            return Err(format!("collection ID '{id}' is reserved"));
+        }
+        Ok(Self(id.to_string()))
+    }
+}
+
+impl fmt::Display for CollectionId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// A token of one of the collections watched by the minter.
+/// Tokens of the collection configured at init have no collection ID.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TokenKey {
+    pub collection: Option<CollectionId>,
+    pub token_id: u256,
+}
+
+impl TokenKey {
+    /// The path at which the metadata of the token is served,
+    /// and under which its other resources are served.
+    pub fn path(&self) -> String {
+        match &self.collection {
+            Some(collection) => format!("/{collection}/token/{}", self.token_id),
+            None => format!("/token/{}", self.token_id),
+        }
+    }
+}
+
+impl From<u256> for TokenKey {
+    fn from(token_id: u256) -> Self {
+        Self {
+            collection: None,
+            token_id,
+        }
+    }
+}
+
+impl fmt::Display for TokenKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.collection {
+            Some(collection) => write!(f, "{collection}/{}", self.token_id),
+            None => write!(f, "{}", self.token_id),
+        }
+    }
+}
+
+/// The configuration of an additional collection.
+#[derive(CandidType, Deserialize, Clone, Debug, Encode, Decode, PartialEq, Eq)]
+pub struct CollectionArg {
+    /// The prefix of the paths at which the tokens of the collection are served.
+    #[n(0)]
+    pub id: String,
+    #[n(1)]
+    pub ethereum_contract_address: String,
+    /// The contract is scraped from the next block on.
+    #[cbor(n(2), with = "crate::cbor::nat")]
+    pub last_scraped_block_number: Nat,
+    #[n(3)]
+    pub generator_config: Option<GeneratorConfig>,
+    /// The metadata of the tokens of the collection. Tokens are named after their ID if not set.
+    #[n(4)]
+    pub collection_metadata: Option<CollectionMetadata>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Collection {
+    pub ethereum_contract_address: Address,
+    pub first_scraped_block_number: BlockNumber,
+    pub last_scraped_block_number: BlockNumber,
+    pub skipped_blocks: BTreeSet<BlockNumber>,
+    pub generator_config: GeneratorConfig,
+    pub metadata: CollectionMetadata,
+}
+
+impl TryFrom<CollectionArg> for (CollectionId, Collection) {
+    type Error = String;
+
+    fn try_from(arg: CollectionArg) -> Result<Self, Self::Error> {
+        let id = CollectionId::from_str(&arg.id)?;
+        let ethereum_contract_address = Address::from_str(&arg.ethereum_contract_address)
+            .map_err(|e| format!("invalid contract address of collection '{id}': {e}"))?;
+        if ethereum_contract_address == Address::ZERO {
+            return Err(format!(
+                "contract address of collection '{id}' cannot be the zero address"
+            ));
+        }
+        let last_scraped_block_number = BlockNumber::try_from(arg.last_scraped_block_number)
+            .map_err(|e| format!("invalid last scraped block of collection '{id}': {e}"))?;
+        let first_scraped_block_number =
+            last_scraped_block_number
+                .checked_increment()
+                .ok_or_else(|| {
+                    format!("last scraped block of collection '{id}' is at maximum value")
+                })?;
+        let collection = Collection {
+            ethereum_contract_address,
+            first_scraped_block_number,
+            last_scraped_block_number,
+            skipped_blocks: Default::default(),
+            generator_config: arg.generator_config.unwrap_or_default(),
+            metadata: arg.collection_metadata.unwrap_or_default(),
+        };
+        Ok((id, collection))
+    }
+}
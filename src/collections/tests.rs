@@ -0,0 +1,85 @@
+use crate::collections::{Collection, CollectionArg, CollectionId, TokenKey};
+use crate::numeric::BlockNumber;
+use candid::Nat;
+use ethnum::u256;
+use std::str::FromStr;
+
+mod collection_id {
+    use super::*;
+
+    #[test]
+    fn should_parse_valid_ids() {
+        for id in ["punks", "genesis-2", "my_collection", "0"] {
+            assert_eq!(CollectionId::from_str(id).unwrap().as_str(), id);
+        }
+    }
+
+    #[test]
+    fn should_reject_invalid_ids() {
+        for id in ["", "Punks", "a/b", "a b", "token", &"a".repeat(65)] {
+            assert!(
+                CollectionId::from_str(id).is_err(),
+                "{id} should be invalid"
+            );
+        }
+    }
+}
+
+mod token_key {
+    use super::*;
+
+    #[test]
+    fn should_prefix_paths_of_additional_collections() {
+        let token = TokenKey::from(u256::from(7_u8));
+        assert_eq!(token.path(), "/token/7");
+        assert_eq!(token.to_string(), "7");
+
+        let token = TokenKey {
+            collection: Some("punks".parse().unwrap()),
+            ..token
+        };
+        assert_eq!(token.path(), "/punks/token/7");
+        assert_eq!(token.to_string(), "punks/7");
+    }
+}
+
+mod collection_arg {
+    use super::*;
+
+    #[test]
+    fn should_scrape_from_next_block() {
+        let (id, collection) = <(CollectionId, Collection)>::try_from(arg()).unwrap();
+
+        assert_eq!(id.as_str(), "punks");
+        assert_eq!(
+            collection.first_scraped_block_number,
+            BlockNumber::from(101_u8)
+        );
+        assert_eq!(
+            collection.last_scraped_block_number,
+            BlockNumber::from(100_u8)
+        );
+    }
+
+    #[test]
+    fn should_reject_zero_address() {
+        let arg = CollectionArg {
+            ethereum_contract_address: "0x0000000000000000000000000000000000000000".to_string(),
+            ..arg()
+        };
+
+        assert!(<(CollectionId, Collection)>::try_from(arg)
+            .unwrap_err()
+            .contains("zero address"));
+    }
+
+    fn arg() -> CollectionArg {
+        CollectionArg {
+            id: "punks".to_string(),
+            ethereum_contract_address: "0x7a250d5630B4cF539739dF2C5dAcb4c659F2488D".to_string(),
+            last_scraped_block_number: Nat::from(100_u8),
+            generator_config: None,
+            collection_metadata: None,
+        }
+    }
+}
@@ -0,0 +1,402 @@
+//! Audit of the minter state against the NFT contracts.
+//!
+//! Mints can be missed, e.g. because of skipped blocks, faulty providers or a start block set too late.
+//! The audit periodically reads the `totalSupply()` of each ERC-721 contract and the `ownerOf(uint256)`
+//! of a sample of its tokens with `eth_call`, at the last block scraped for the contract,
+//! and compares them with the tokens minted or to mint by the minter.
+//! Owners are only compared when the minter scrapes all transfers, otherwise only the existence of the sampled tokens is checked.
+//! The sample rotates from one audit to the next, so that all tokens are eventually checked.
+//! The result of the last audit is reset by upgrades.
+
+#[cfg(test)]
+mod tests;
+
+use crate::address::Address;
+use crate::collections::{CollectionId, TokenKey};
+use crate::endpoints;
+use crate::eth_logs::{ScrapingMode, TokenStandard};
+use crate::eth_rpc::{into_nat, BlockSpec};
+use crate::eth_rpc_client::contract::ContractCallError;
+use crate::eth_rpc_client::EthRpcClient;
+use crate::guard::TimerGuard;
+use crate::logs::INFO;
+use crate::numeric::BlockNumber;
+use crate::state::{read_state, State, TaskType};
+use candid::Nat;
+use ethnum::u256;
+use ic_canister_log::log;
+use ic_metrics_encoder::MetricsEncoder;
+use std::cell::{Cell, RefCell};
+use std::collections::BTreeSet;
+use std::fmt;
+
+/// The number of tokens of each collection whose owner is checked by an audit.
+pub const SAMPLE_SIZE: usize = 10;
+
+/// What to read from the contract of a collection, and what the minter expects.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AuditPlan {
+    pub collection: Option<CollectionId>,
+    pub contract_address: Address,
+    pub block_number: BlockNumber,
+    pub expected_total_supply: u256,
+    /// The sampled tokens, with their owner if the minter follows transfers.
+    pub samples: Vec<(u256, Option<Address>)>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Discrepancy {
+    TotalSupply {
+        onchain: u256,
+        expected: u256,
+    },
+    Owner {
+        token_id: u256,
+        onchain: Address,
+        expected: Address,
+    },
+    MissingToken {
+        token_id: u256,
+        error: String,
+    },
+}
+
+impl fmt::Display for Discrepancy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Discrepancy::TotalSupply { onchain, expected } => write!(
+                f,
+                "total supply is {onchain} on chain but {expected} in the minter"
+            ),
+            Discrepancy::Owner {
+                token_id,
+                onchain,
+                expected,
+            } => write!(
+                f,
+                "token {token_id} is owned by {onchain} on chain but by {expected} in the minter"
+            ),
+            Discrepancy::MissingToken { token_id, error } => {
+                write!(f, "token {token_id} does not exist on chain: {error}")
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CollectionAudit {
+    pub collection: Option<CollectionId>,
+    pub contract_address: Address,
+    pub block_number: BlockNumber,
+    pub onchain_total_supply: Option<u256>,
+    pub expected_total_supply: u256,
+    pub sampled_token_ids: Vec<u256>,
+    pub discrepancies: Vec<Discrepancy>,
+    /// The calls that failed, leaving part of the collection unchecked.
+    pub errors: Vec<String>,
+}
+
+impl CollectionAudit {
+    fn label(&self) -> String {
+        match &self.collection {
+            Some(collection) => format!("collection {collection}"),
+            None => "main collection".to_string(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AuditReport {
+    /// The time in nanoseconds since the epoch at which the audit completed.
+    pub timestamp: u64,
+    pub collections: Vec<CollectionAudit>,
+}
+
+impl AuditReport {
+    pub fn discrepancy_count(&self) -> usize {
+        self.collections
+            .iter()
+            .map(|audit| audit.discrepancies.len())
+            .sum()
+    }
+
+    pub fn error_count(&self) -> usize {
+        self.collections
+            .iter()
+            .map(|audit| audit.errors.len())
+            .sum()
+    }
+
+    /// Returns the discrepancies found in each collection, formatted for display.
+    pub fn warnings(&self) -> Vec<String> {
+        self.collections
+            .iter()
+            .flat_map(|audit| {
+                audit.discrepancies.iter().map(move |discrepancy| {
+                    format!(
+                        "{} at block {}: {discrepancy}",
+                        audit.label(),
+                        audit.block_number
+                    )
+                })
+            })
+            .collect()
+    }
+}
+
+impl From<AuditReport> for endpoints::ConsistencyAudit {
+    fn from(report: AuditReport) -> Self {
+        Self {
+            timestamp: report.timestamp,
+            collections: report
+                .collections
+                .into_iter()
+                .map(|audit| endpoints::CollectionAudit {
+                    collection: audit.collection.map(|id| id.to_string()),
+                    ethereum_contract_address: audit.contract_address.to_string(),
+                    block_number: Nat::from(audit.block_number),
+                    onchain_total_supply: audit.onchain_total_supply.map(into_nat),
+                    expected_total_supply: into_nat(audit.expected_total_supply),
+                    sampled_token_ids: audit.sampled_token_ids.into_iter().map(into_nat).collect(),
+                    discrepancies: audit
+                        .discrepancies
+                        .into_iter()
+                        .map(endpoints::Discrepancy::from)
+                        .collect(),
+                    errors: audit.errors,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<Discrepancy> for endpoints::Discrepancy {
+    fn from(discrepancy: Discrepancy) -> Self {
+        match discrepancy {
+            Discrepancy::TotalSupply { onchain, expected } => Self::TotalSupply {
+                onchain: into_nat(onchain),
+                expected: into_nat(expected),
+            },
+            Discrepancy::Owner {
+                token_id,
+                onchain,
+                expected,
+            } => Self::Owner {
+                token_id: into_nat(token_id),
+                onchain: onchain.to_string(),
+                expected: expected.to_string(),
+            },
+            Discrepancy::MissingToken { token_id, error } => Self::MissingToken {
+                token_id: into_nat(token_id),
+                error,
+            },
+        }
+    }
+}
+
+thread_local! {
+    static LAST_AUDIT: RefCell<Option<AuditReport>> = RefCell::default();
+    /// The position of the first token sampled by the next audit.
+    static NEXT_SAMPLE: Cell<usize> = Cell::default();
+}
+
+/// Returns the result of the last audit since the last upgrade.
+pub fn last_audit() -> Option<AuditReport> {
+    LAST_AUDIT.with(|audit| audit.borrow().clone())
+}
+
+/// Returns what to audit for the contract of each watched collection,
+/// sampling the tokens, in ascending order, from the given position onward.
+/// Only ERC-721 contracts are audited, since ERC-1155 contracts have neither `totalSupply()` nor `ownerOf`.
+pub fn plan_audit(state: &State, first_sample: usize) -> Vec<AuditPlan> {
+    if state.token_standard != TokenStandard::Erc721 {
+        return vec![];
+    }
+    state
+        .scraped_contracts()
+        .into_iter()
+        .map(|(collection, contract_address, block_number)| {
+            let tokens: BTreeSet<&TokenKey> = state
+                .minted_events
+                .keys()
+                .chain(state.events_to_mint.iter().map(|(_, token, _)| token))
+                .filter(|token| token.collection == collection && !state.is_burned(token))
+                .collect();
+            let samples = tokens
+                .iter()
+                .cycle()
+                .skip(first_sample % tokens.len().max(1))
+                .take(SAMPLE_SIZE.min(tokens.len()))
+                .map(|token| {
+                    let owner = match state.scraping_mode {
+                        ScrapingMode::AllTransfers => state.owner_of(token),
+                        ScrapingMode::Mints => None,
+                    };
+                    (token.token_id, owner)
+                })
+                .collect();
+            AuditPlan {
+                collection,
+                contract_address,
+                block_number,
+                expected_total_supply: u256::from(tokens.len() as u64),
+                samples,
+            }
+        })
+        .collect()
+}
+
+pub fn check_total_supply(onchain: u256, expected: u256) -> Option<Discrepancy> {
+    (onchain != expected).then_some(Discrepancy::TotalSupply { onchain, expected })
+}
+
+/// The JSON-RPC error code of calls that reverted.
+const EXECUTION_REVERTED: i64 = 3;
+
+/// Whether the JSON-RPC error is a revert, which some providers report with another code.
+fn is_revert(code: i64, message: &str) -> bool {
+    code == EXECUTION_REVERTED || message.contains("execution reverted")
+}
+
+/// Compares the result of `ownerOf` with the owner expected by the minter, if any.
+/// Returns an error if the call failed for another reason than a revert,
+/// or if some providers returned an owner while others reverted.
+/// Providers may revert with different messages, which is still a revert.
+pub fn check_owner(
+    token_id: u256,
+    expected: Option<Address>,
+    onchain: Result<Address, ContractCallError>,
+) -> Result<Option<Discrepancy>, String> {
+    match onchain {
+        Ok(onchain) => Ok(expected
+            .filter(|expected| *expected != onchain)
+            .map(|expected| Discrepancy::Owner {
+                token_id,
+                onchain,
+                expected,
+            })),
+        Err(e) => {
+            let revert = match &e {
+                ContractCallError::Rpc(error) => error.json_rpc_error_matching(is_revert),
+                ContractCallError::InvalidOutput(_) => None,
+            };
+            match revert {
+                Some((code, message)) => Ok(Some(Discrepancy::MissingToken {
+                    token_id,
+                    error: format!("JSON-RPC error {code}: {message}"),
+                })),
+                None => Err(format!("ownerOf({token_id}): {e:?}")),
+            }
+        }
+    }
+}
+
+async fn audit_collection(client: &EthRpcClient, plan: AuditPlan) -> CollectionAudit {
+    let block = BlockSpec::Number(plan.block_number);
+    let mut discrepancies = vec![];
+    let mut errors = vec![];
+    let onchain_total_supply = match client
+        .total_supply(plan.contract_address, block.clone())
+        .await
+    {
+        Ok(supply) => {
+            discrepancies.extend(check_total_supply(supply, plan.expected_total_supply));
+            Some(supply)
+        }
+        Err(e) => {
+            errors.push(format!("totalSupply(): {e:?}"));
+            None
+        }
+    };
+    for (token_id, expected_owner) in &plan.samples {
+        let onchain = client
+            .owner_of(plan.contract_address, *token_id, block.clone())
+            .await;
+        match check_owner(*token_id, *expected_owner, onchain) {
+            Ok(discrepancy) => discrepancies.extend(discrepancy),
+            Err(e) => errors.push(e),
+        }
+    }
+    CollectionAudit {
+        collection: plan.collection,
+        contract_address: plan.contract_address,
+        block_number: plan.block_number,
+        onchain_total_supply,
+        expected_total_supply: plan.expected_total_supply,
+        sampled_token_ids: plan.samples.iter().map(|(token_id, _)| *token_id).collect(),
+        discrepancies,
+        errors,
+    }
+}
+
+/// Audits the contract of each watched collection and keeps the result,
+/// logging every discrepancy.
+pub async fn audit_consistency() {
+    let _guard = match TimerGuard::new(TaskType::ConsistencyAudit) {
+        Ok(guard) => guard,
+        Err(_) => return,
+    };
+    let first_sample = NEXT_SAMPLE.with(Cell::get);
+    let (client, plans) =
+        read_state(|s| (EthRpcClient::from_state(s), plan_audit(s, first_sample)));
+    if plans.is_empty() {
+        return;
+    }
+
+    let mut collections = Vec::with_capacity(plans.len());
+    for plan in plans {
+        collections.push(audit_collection(&client, plan).await);
+    }
+    NEXT_SAMPLE.with(|next| next.set(first_sample.wrapping_add(SAMPLE_SIZE)));
+    let report = AuditReport {
+        timestamp: ic_cdk::api::time(),
+        collections,
+    };
+
+    for warning in report.warnings() {
+        log!(INFO, "[audit_consistency]: discrepancy in {warning}");
+    }
+    for audit in &report.collections {
+        for error in &audit.errors {
+            log!(
+                INFO,
+                "[audit_consistency]: failed to audit {} at block {}: {error}",
+                audit.label(),
+                audit.block_number
+            );
+        }
+    }
+    log!(
+        INFO,
+        "[audit_consistency]: found {} discrepancies in {} collections",
+        report.discrepancy_count(),
+        report.collections.len()
+    );
+    LAST_AUDIT.with(|audit| *audit.borrow_mut() = Some(report));
+}
+
+/// Encodes the result of the last audit, if any.
+pub fn encode_metrics<W: std::io::Write>(encoder: &mut MetricsEncoder<W>) -> std::io::Result<()> {
+    LAST_AUDIT.with(|audit| {
+        let audit = audit.borrow();
+        let audit = match audit.as_ref() {
+            Some(audit) => audit,
+            None => return Ok(()),
+        };
+        encoder.encode_gauge(
+            "cketh_minter_consistency_discrepancies",
+            audit.discrepancy_count() as f64,
+            "The number of discrepancies between the NFT contracts and the minter found by the last audit.",
+        )?;
+        encoder.encode_gauge(
+            "cketh_minter_consistency_audit_errors",
+            audit.error_count() as f64,
+            "The number of calls to the NFT contracts that failed during the last audit.",
+        )?;
+        encoder.encode_gauge(
+            "cketh_minter_last_consistency_audit_timestamp_seconds",
+            (audit.timestamp / 1_000_000_000) as f64,
+            "The time of the last audit of the NFT contracts, in seconds since the epoch.",
+        )
+    })
+}
@@ -0,0 +1,309 @@
+use crate::address::Address;
+use crate::collections::CollectionId;
+use crate::consistency::{
+    check_owner, check_total_supply, plan_audit, AuditPlan, AuditReport, CollectionAudit,
+    Discrepancy, SAMPLE_SIZE,
+};
+use crate::eth_logs::{ScrapingMode, TokenStandard};
+use crate::eth_rpc::{Data, HttpOutcallError, HttpOutcallResult, JsonRpcResult};
+use crate::eth_rpc_client::contract::ContractCallError;
+use crate::eth_rpc_client::providers::{RpcNodeProvider, RpcProvider};
+use crate::eth_rpc_client::{MultiCallError, MultiCallResults};
+use crate::numeric::BlockNumber;
+use crate::state::audit::{apply_state_transition, EventType};
+use crate::state::State;
+use crate::test_fixtures::{holder, mint_event, state, transfer_event, CONTRACT};
+use ethnum::u256;
+use ic_cdk::api::call::RejectionCode;
+
+const BUYER: &str = "0x7e41257f7b5c3dd3313ef02b1f4c864fe95bec2b";
+
+mod plan_audit {
+    use super::*;
+
+    #[test]
+    fn should_expect_minted_and_pending_tokens_that_were_not_burned() {
+        let mut state = state();
+        for (log_index, token_id) in [(1, 7), (2, 3), (3, 11)] {
+            apply_state_transition(
+                &mut state,
+                &EventType::AcceptedMint(mint_event(log_index, token_id)),
+            );
+        }
+        apply_state_transition(
+            &mut state,
+            &EventType::MintedNft {
+                event_source: mint_event(1, 7).source(),
+            },
+        );
+        apply_state_transition(
+            &mut state,
+            &EventType::BurnedNft(transfer_event(4, holder(), Address::ZERO, 11)),
+        );
+
+        assert_eq!(
+            plan_audit(&state, 0),
+            vec![AuditPlan {
+                collection: None,
+                contract_address: CONTRACT.parse().unwrap(),
+                block_number: state.last_scraped_block_number,
+                expected_total_supply: u256::from(2_u8),
+                samples: vec![(u256::from(3_u8), None), (u256::from(7_u8), None)],
+            }]
+        );
+    }
+
+    #[test]
+    fn should_rotate_sampled_tokens() {
+        let mut state = state();
+        let token_count = SAMPLE_SIZE as u64 + 5;
+        for token_id in 0..token_count {
+            apply_state_transition(
+                &mut state,
+                &EventType::AcceptedMint(mint_event(token_id, token_id)),
+            );
+        }
+
+        let sampled_ids = |first_sample| -> Vec<u64> {
+            plan_audit(&state, first_sample)[0]
+                .samples
+                .iter()
+                .map(|(token_id, _)| token_id.as_u64())
+                .collect()
+        };
+
+        assert_eq!(sampled_ids(0), (0..SAMPLE_SIZE as u64).collect::<Vec<_>>());
+        assert_eq!(
+            sampled_ids(SAMPLE_SIZE),
+            (SAMPLE_SIZE as u64..token_count)
+                .chain(0..5)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(sampled_ids(token_count as usize), sampled_ids(0));
+    }
+
+    #[test]
+    fn should_only_expect_owners_when_scraping_all_transfers() {
+        let mut state = State {
+            scraping_mode: ScrapingMode::AllTransfers,
+            ..state()
+        };
+        apply_state_transition(&mut state, &EventType::AcceptedMint(mint_event(1, 7)));
+        apply_state_transition(
+            &mut state,
+            &EventType::TransferredNft(transfer_event(2, holder(), buyer(), 7)),
+        );
+
+        assert_eq!(
+            plan_audit(&state, 0)[0].samples,
+            vec![(u256::from(7_u8), Some(buyer()))]
+        );
+    }
+
+    #[test]
+    fn should_not_audit_erc1155_contracts() {
+        let mut state = State {
+            token_standard: TokenStandard::Erc1155,
+            ..state()
+        };
+        apply_state_transition(&mut state, &EventType::AcceptedMint(mint_event(1, 7)));
+
+        assert_eq!(plan_audit(&state, 0), vec![]);
+    }
+}
+
+mod check {
+    use super::*;
+
+    #[test]
+    fn should_report_different_total_supply() {
+        assert_eq!(
+            check_total_supply(u256::from(10_u8), u256::from(10_u8)),
+            None
+        );
+        assert_eq!(
+            check_total_supply(u256::from(12_u8), u256::from(10_u8)),
+            Some(Discrepancy::TotalSupply {
+                onchain: u256::from(12_u8),
+                expected: u256::from(10_u8),
+            })
+        );
+    }
+
+    #[test]
+    fn should_report_different_owner() {
+        let token_id = u256::from(7_u8);
+
+        assert_eq!(
+            check_owner(token_id, Some(holder()), Ok(holder())),
+            Ok(None)
+        );
+        assert_eq!(
+            check_owner(token_id, Some(holder()), Ok(buyer())),
+            Ok(Some(Discrepancy::Owner {
+                token_id,
+                onchain: buyer(),
+                expected: holder(),
+            }))
+        );
+    }
+
+    #[test]
+    fn should_only_check_existence_without_expected_owner() {
+        assert_eq!(check_owner(u256::from(7_u8), None, Ok(buyer())), Ok(None));
+    }
+
+    #[test]
+    fn should_report_reverted_owner_of_as_missing_token() {
+        let reverted = Err(ContractCallError::Rpc(
+            MultiCallError::ConsistentJsonRpcError {
+                code: 3,
+                message: "execution reverted: ERC721: invalid token ID".to_string(),
+            },
+        ));
+
+        assert_eq!(
+            check_owner(u256::from(7_u8), None, reverted),
+            Ok(Some(Discrepancy::MissingToken {
+                token_id: u256::from(7_u8),
+                error: "JSON-RPC error 3: execution reverted: ERC721: invalid token ID".to_string(),
+            }))
+        );
+    }
+
+    #[test]
+    fn should_report_reverts_with_different_messages_as_missing_token() {
+        let reverted = inconsistent_results(vec![
+            Ok(JsonRpcResult::Error {
+                code: 3,
+                message: "execution reverted: ERC721: invalid token ID".to_string(),
+            }),
+            Ok(JsonRpcResult::Error {
+                code: -32000,
+                message: "execution reverted".to_string(),
+            }),
+            Err(HttpOutcallError::IcError {
+                code: RejectionCode::SysTransient,
+                message: "timeout".to_string(),
+            }),
+        ]);
+
+        assert_eq!(
+            check_owner(u256::from(7_u8), Some(holder()), reverted),
+            Ok(Some(Discrepancy::MissingToken {
+                token_id: u256::from(7_u8),
+                error: "JSON-RPC error 3: execution reverted: ERC721: invalid token ID".to_string(),
+            }))
+        );
+    }
+
+    #[test]
+    fn should_not_report_revert_when_other_provider_returned_owner() {
+        let inconsistent = inconsistent_results(vec![
+            Ok(JsonRpcResult::Error {
+                code: 3,
+                message: "execution reverted".to_string(),
+            }),
+            Ok(JsonRpcResult::Result(Data(
+                [[0; 12].as_slice(), holder().as_ref()].concat(),
+            ))),
+        ]);
+
+        assert!(check_owner(u256::from(7_u8), Some(holder()), inconsistent).is_err());
+    }
+
+    #[test]
+    fn should_not_report_other_json_rpc_errors_as_missing_token() {
+        let rate_limited = Err(ContractCallError::Rpc(
+            MultiCallError::ConsistentJsonRpcError {
+                code: -32005,
+                message: "limit exceeded".to_string(),
+            },
+        ));
+
+        assert!(check_owner(u256::from(7_u8), Some(holder()), rate_limited).is_err());
+    }
+
+    fn inconsistent_results(
+        results: Vec<HttpOutcallResult<JsonRpcResult<Data>>>,
+    ) -> Result<Address, ContractCallError> {
+        Err(ContractCallError::Rpc(MultiCallError::InconsistentResults(
+            MultiCallResults::from_non_empty_iter(results.into_iter().enumerate().map(
+                |(i, result)| {
+                    let url = format!("https://rpc{i}.example.org");
+                    (RpcNodeProvider::Custom(RpcProvider::from(url)), result)
+                },
+            )),
+        )))
+    }
+
+    #[test]
+    fn should_not_report_failed_calls_as_discrepancies() {
+        let failed = Err(ContractCallError::Rpc(
+            MultiCallError::ConsistentHttpOutcallError(HttpOutcallError::IcError {
+                code: RejectionCode::SysTransient,
+                message: "timeout".to_string(),
+            }),
+        ));
+
+        assert!(check_owner(u256::from(7_u8), Some(holder()), failed).is_err());
+    }
+}
+
+#[test]
+fn should_format_warnings_by_collection() {
+    let report = AuditReport {
+        timestamp: 0,
+        collections: vec![
+            collection_audit(None, vec![]),
+            collection_audit(
+                Some("other".parse().unwrap()),
+                vec![
+                    Discrepancy::TotalSupply {
+                        onchain: u256::from(3_u8),
+                        expected: u256::from(2_u8),
+                    },
+                    Discrepancy::Owner {
+                        token_id: u256::from(7_u8),
+                        onchain: buyer(),
+                        expected: holder(),
+                    },
+                ],
+            ),
+        ],
+    };
+
+    assert_eq!(report.discrepancy_count(), 2);
+    assert_eq!(
+        report.warnings(),
+        vec![
+            "collection other at block 100: total supply is 3 on chain but 2 in the minter"
+                .to_string(),
+            format!(
+                "collection other at block 100: token 7 is owned by {} on chain but by {} in the minter",
+                buyer(),
+                holder()
+            ),
+        ]
+    );
+}
+
+fn collection_audit(
+    collection: Option<CollectionId>,
+    discrepancies: Vec<Discrepancy>,
+) -> CollectionAudit {
+    CollectionAudit {
+        collection,
+        contract_address: CONTRACT.parse().unwrap(),
+        block_number: BlockNumber::new(100),
+        onchain_total_supply: None,
+        expected_total_supply: u256::ZERO,
+        sampled_token_ids: vec![],
+        discrepancies,
+        errors: vec![],
+    }
+}
+
+fn buyer() -> Address {
+    BUYER.parse().unwrap()
+}
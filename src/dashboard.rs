@@ -2,7 +2,12 @@
 mod tests;
 
 use askama::Template;
-use ic_cketh_minter::eth_logs::MintEvent;
+use ic_cketh_minter::collections::{Collection, CollectionId};
+use ic_cketh_minter::consistency::last_audit;
+use ic_cketh_minter::eth_logs::{MintEvent, TransferEvent};
+use ic_cketh_minter::eth_rpc::BlockHeight;
+use ic_cketh_minter::eth_rpc_client::health::provider_health;
+use ic_cketh_minter::eth_rpc_client::EthRpcClient;
 use ic_cketh_minter::lifecycle::EthereumNetwork;
 use ic_cketh_minter::numeric::BlockNumber;
 use ic_cketh_minter::state::{MintedEvent, State};
@@ -15,31 +20,81 @@ pub struct DashboardTemplate {
     pub ethereum_network: EthereumNetwork,
     pub contract_address: String,
     pub minter_address: String,
+    pub ethereum_block_height: BlockHeight,
     pub first_synced_block: BlockNumber,
     pub last_synced_block: BlockNumber,
     pub last_observed_block: Option<BlockNumber>,
     pub minted_events: Vec<MintedEvent>,
     pub events_to_mint: Vec<MintEvent>,
+    pub burned_tokens: Vec<TransferEvent>,
     pub skipped_blocks: BTreeSet<BlockNumber>,
+    pub collections: Vec<(CollectionId, Collection)>,
+    pub providers: Vec<ProviderHealthRow>,
+    /// The discrepancies between the NFT contracts and the minter found by the last audit.
+    pub consistency_warnings: Vec<String>,
+}
+
+/// The health of a JSON-RPC provider, formatted for display.
+pub struct ProviderHealthRow {
+    pub url: String,
+    pub success_rate: String,
+    pub average_latency: String,
+    pub consecutive_failures: u32,
+    pub available: bool,
 }
 
 impl DashboardTemplate {
     pub fn from_state(state: &State) -> Self {
-        let mut minted_events: Vec<_> = state.minted_events.values().cloned().collect();
-        minted_events.sort_unstable_by_key(|event| Reverse(event.mint_event.token_id));
+        Self::from_state_at(state, ic_cdk::api::time())
+    }
+
+    fn from_state_at(state: &State, now: u64) -> Self {
+        let minted_events: Vec<_> = state.minted_events.values().rev().cloned().collect();
         let mut events_to_mint: Vec<_> = state.events_to_mint.values().cloned().collect();
         events_to_mint.sort_unstable_by_key(|event| Reverse(event.block_number));
+        let mut burned_tokens: Vec<_> = state.burned_tokens.values().cloned().collect();
+        burned_tokens.sort_unstable_by_key(|event| Reverse((event.block_number, event.log_index)));
 
         DashboardTemplate {
-            ethereum_network: state.ethereum_network,
+            ethereum_network: state.ethereum_network.clone(),
             contract_address: state.ethereum_contract_address.to_string(),
             minter_address: state.minter_address.to_string(),
+            ethereum_block_height: state.ethereum_block_height,
             first_synced_block: state.first_scraped_block_number,
             last_synced_block: state.last_scraped_block_number,
             last_observed_block: state.last_observed_block_number,
             minted_events,
             events_to_mint,
+            burned_tokens,
             skipped_blocks: state.skipped_blocks.clone(),
+            collections: state
+                .collections
+                .iter()
+                .map(|(id, collection)| (id.clone(), collection.clone()))
+                .collect(),
+            providers: EthRpcClient::from_state(state)
+                .provider_urls()
+                .into_iter()
+                .map(|url| {
+                    let health = provider_health(&url);
+                    ProviderHealthRow {
+                        success_rate: health
+                            .success_rate()
+                            .map(|rate| format!("{:.0}%", rate * 100.0))
+                            .unwrap_or_else(|| "N/A".to_string()),
+                        average_latency: health
+                            .average_latency()
+                            .map(|latency| format!("{} ms", latency.as_millis()))
+                            .unwrap_or_else(|| "N/A".to_string()),
+                        consecutive_failures: health.consecutive_failures,
+                        available: health.is_available(now),
+                        url,
+                    }
+                })
+                .collect(),
+            consistency_warnings: last_audit()
+                .map(|audit| audit.warnings())
+                .unwrap_or_default(),
         }
     }
 }
@@ -1,10 +1,16 @@
 use crate::address::Address;
-use crate::eth_logs::{report_transaction_error, MintEvent, MintEventError, TransferEventError};
-use crate::eth_rpc::{BlockSpec, HttpOutcallError};
+use crate::collections::CollectionId;
+use crate::eth_logs::{
+    report_transaction_error, verify_mint_receipt, MintEvent, MintEventError,
+    ReceiptVerificationError, ScrapingMode, TokenStandard, TransferEvent, TransferEventError,
+};
+use crate::eth_rpc::{BlockHeight, BlockSpec, BlockTag, Hash, HttpOutcallError};
 use crate::eth_rpc_client::EthRpcClient;
 use crate::guard::TimerGuard;
 use crate::logs::{DEBUG, INFO};
+use crate::management::raw_rand;
 use crate::numeric::BlockNumber;
+use crate::reveal::reveal_if_block_reached;
 use crate::state::{
     audit::process_event, event::EventType, mutate_state, read_state, State, TaskType,
 };
@@ -20,11 +26,58 @@ async fn mint_cketh() {
         Err(_) => return,
     };
 
-    let events = read_state(|s| (s.events_to_mint.clone()));
+    let event_sources = read_state(State::events_without_seed);
+    if !event_sources.is_empty() {
+        match raw_rand().await {
+            Ok(seed) => mutate_state(|s| {
+                // Events may have been rolled back by a chain reorganization in the meantime.
+                let event_sources: Vec<_> = event_sources
+                    .into_iter()
+                    .filter(|source| s.events_to_mint.contains(source))
+                    .collect();
+                process_event(
+                    s,
+                    EventType::ReceivedRandomness {
+                        seed,
+                        event_sources,
+                    },
+                )
+            }),
+            Err(err) => {
+                log!(
+                    INFO,
+                    "Failed to get randomness for {} events to mint: {err}, rescheduling the minting",
+                    event_sources.len()
+                );
+                ic_cdk_timers::set_timer(crate::MINT_RETRY_DELAY, || ic_cdk::spawn(mint_cketh()));
+                return;
+            }
+        }
+    }
+
+    // Events accepted while waiting for randomness are minted in the next run.
+    let events: Vec<_> = read_state(|s| {
+        s.events_to_mint
+            .iter()
+            .filter(|(event_source, _, _)| s.is_ready_to_mint(event_source))
+            .map(|(event_source, _, event)| (*event_source, event.clone()))
+            .collect()
+    });
 
-    let error_count = 0;
+    let mut error_count = 0;
+    let verify_receipts = read_state(|s| s.verify_receipts);
 
     for (event_source, event) in events {
+        if verify_receipts {
+            match verify_receipt(&event).await {
+                Ok(true) => {}
+                Ok(false) => continue,
+                Err(()) => {
+                    error_count += 1;
+                    continue;
+                }
+            }
+        }
         // let block_index = match client
         //     .transfer(TransferArg {
         //         from_subaccount: None,
@@ -51,11 +104,16 @@ async fn mint_cketh() {
         //         continue;
         //     }
         // };
-        mutate_state(|s| process_event(s, EventType::MintedNft { event_source }));
+        let attributes = mutate_state(|s| {
+            process_event(s, EventType::MintedNft { event_source });
+            s.attributes_of(&event.token_key())
+                .map(|attributes| attributes.to_vec())
+                .unwrap_or_default()
+        });
         log!(
             INFO,
-            "generated metadata and assets for token id {}",
-            event.token_id,
+            "generated attributes {attributes:?} for token {}",
+            event.token_key(),
         );
     }
 
@@ -68,22 +126,82 @@ async fn mint_cketh() {
     }
 }
 
-/// Scraps Ethereum logs between `from` and `min(from + MAX_BLOCK_SPREAD, to)` since certain RPC providers
-/// require that the number of blocks queried is no greater than MAX_BLOCK_SPREAD.
-/// Returns the last block number that was scraped (which is `min(from + MAX_BLOCK_SPREAD, to)`) if there
+/// Checks the event against the receipt of its transaction, and rejects the event if the receipt contradicts it.
+/// Returns whether the event can be minted, or an error if the check should be retried,
+/// e.g., once the providers know the transaction or once the chain reorganization is processed.
+async fn verify_receipt(event: &MintEvent) -> Result<bool, ()> {
+    let event_source = event.source();
+    let receipt = match read_state(EthRpcClient::from_state)
+        .eth_get_transaction_receipt(event.transaction_hash)
+        .await
+    {
+        Ok(receipt) => receipt,
+        Err(e) => {
+            log!(
+                INFO,
+                "Failed to retrieve the receipt of event {event_source}: {e:?}, rescheduling the minting"
+            );
+            return Err(());
+        }
+    };
+    mutate_state(|s| {
+        // The event may have been rolled back by a chain reorganization in the meantime.
+        if !s.events_to_mint.contains(&event_source) {
+            return Ok(false);
+        }
+        let (_, contract_address, _) = s
+            .scraped_contracts()
+            .into_iter()
+            .find(|(collection, _, _)| *collection == event.collection)
+            .expect("BUG: events to mint belong to watched collections");
+        match verify_mint_receipt(event, contract_address, s.token_standard, receipt.as_ref()) {
+            Ok(()) => Ok(true),
+            Err(ReceiptVerificationError::MissingReceipt) => {
+                log!(
+                    INFO,
+                    "The transaction of event {event_source} has no receipt yet, rescheduling the minting"
+                );
+                Err(())
+            }
+            Err(ReceiptVerificationError::OtherBlock) => {
+                log!(
+                    INFO,
+                    "The transaction of event {event_source} is included in another block, waiting for the chain reorganization to be processed"
+                );
+                Err(())
+            }
+            Err(ReceiptVerificationError::Mismatch(reason)) => {
+                log!(INFO, "Rejecting event {event_source}: {reason}");
+                process_event(
+                    s,
+                    EventType::RejectedMint {
+                        event_source,
+                        reason,
+                    },
+                );
+                Ok(false)
+            }
+        }
+    })
+}
+
+/// Scraps Ethereum logs between `from` and `min(from + max_block_spread, to)` since certain RPC providers
+/// require that the number of blocks queried is no greater than the maximum block spread.
+/// Returns the last block number that was scraped (which is `min(from + max_block_spread, to)`) if there
 /// was no error when querying the providers, otherwise returns `None`.
 async fn scrape_eth_logs_range_inclusive(
+    collection: Option<&CollectionId>,
     contract_address: Address,
+    scraping_mode: ScrapingMode,
+    token_standard: TokenStandard,
     from: BlockNumber,
     to: BlockNumber,
 ) -> Option<BlockNumber> {
-    /// The maximum block spread is introduced by Cloudflare limits.
-    /// https://developers.cloudflare.com/web3/ethereum-gateway/
-    const MAX_BLOCK_SPREAD: u16 = 799;
+    let max_block_spread = read_state(|s| s.max_block_spread());
     match from.cmp(&to) {
         Ordering::Less | Ordering::Equal => {
             let max_to = from
-                .checked_add(BlockNumber::from(MAX_BLOCK_SPREAD))
+                .checked_add(BlockNumber::from(max_block_spread))
                 .unwrap_or(BlockNumber::MAX);
             let mut last_block_number = min(max_to, to);
             log!(
@@ -93,11 +211,14 @@ async fn scrape_eth_logs_range_inclusive(
                 last_block_number
             );
 
-            let (mint_events, errors) = loop {
+            let (transfer_events, errors) = loop {
                 match crate::eth_logs::last_received_eth_events(
+                    collection,
                     contract_address,
                     from,
                     last_block_number,
+                    scraping_mode,
+                    token_standard,
                 )
                 .await
                 {
@@ -112,8 +233,14 @@ async fn scrape_eth_logs_range_inclusive(
                         ) {
                             if from == last_block_number {
                                 mutate_state(|s| {
-                                    process_event(s, EventType::SkippedBlock(last_block_number));
-                                    s.last_scraped_block_number = last_block_number;
+                                    process_event(
+                                        s,
+                                        EventType::SkippedBlock {
+                                            block_number: last_block_number,
+                                            collection: collection.cloned(),
+                                        },
+                                    );
+                                    s.set_last_scraped_block_number(collection, last_block_number);
                                 });
                                 return Some(last_block_number);
                             } else {
@@ -133,32 +260,8 @@ async fn scrape_eth_logs_range_inclusive(
                 };
             };
 
-            for mint in mint_events {
-                log!(
-                    INFO,
-                    "Received event {mint:?}; will generate metadata and assets for token id {}",
-                    mint.token_id,
-                );
-                mutate_state(|s| process_event(s, EventType::AcceptedMint(mint)));
-            }
-            if read_state(State::has_events_to_mint) {
-                ic_cdk_timers::set_timer(Duration::from_secs(0), || ic_cdk::spawn(mint_cketh()));
-            }
-            for error in errors {
-                if let TransferEventError::InvalidEventSource { source, error } = &error {
-                    mutate_state(|s| {
-                        process_event(
-                            s,
-                            EventType::InvalidTransfer {
-                                event_source: *source,
-                                reason: error.to_string(),
-                            },
-                        )
-                    });
-                }
-                report_transaction_error(error);
-            }
-            mutate_state(|s| s.last_scraped_block_number = last_block_number);
+            process_transfer_events(collection, transfer_events, errors);
+            mutate_state(|s| s.set_last_scraped_block_number(collection, last_block_number));
             Some(last_block_number)
         }
         Ordering::Greater => {
@@ -170,12 +273,109 @@ async fn scrape_eth_logs_range_inclusive(
     }
 }
 
+/// Processes the transfers scraped from the logs of the contract of the given collection,
+/// together with the log entries that could not be parsed.
+fn process_transfer_events(
+    collection: Option<&CollectionId>,
+    transfer_events: Vec<TransferEvent>,
+    errors: Vec<TransferEventError>,
+) {
+    for transfer in transfer_events {
+        let mint = match MintEvent::try_from(transfer.clone()) {
+            Ok(mint) => mint,
+            Err(MintEventError::NoMintEvent) if transfer.is_burn() => {
+                log!(
+                    INFO,
+                    "Received burn {transfer:?} of token id {}",
+                    transfer.token_id
+                );
+                mutate_state(|s| process_event(s, EventType::BurnedNft(transfer)));
+                continue;
+            }
+            Err(MintEventError::NoMintEvent) => {
+                log!(
+                    DEBUG,
+                    "Received transfer {transfer:?} of token id {}",
+                    transfer.token_id
+                );
+                mutate_state(|s| process_event(s, EventType::TransferredNft(transfer)));
+                continue;
+            }
+        };
+        if read_state(|s| s.is_new_edition(&mint)) {
+            log!(
+                INFO,
+                "Received event {mint:?}; minted {} more editions of token id {}",
+                mint.amount(),
+                mint.token_id,
+            );
+            mutate_state(|s| process_event(s, EventType::AcceptedEdition(mint)));
+            continue;
+        }
+        if let Err(reason) = read_state(|s| s.validate_mint(&mint)) {
+            log!(INFO, "Rejected event {mint:?}: {reason}");
+            mutate_state(|s| {
+                process_event(
+                    s,
+                    EventType::InvalidTransfer {
+                        event_source: mint.source(),
+                        reason,
+                        token_id: Some(mint.token_id),
+                        collection: mint.collection.clone(),
+                        block_number: Some(mint.block_number),
+                    },
+                )
+            });
+            continue;
+        }
+        log!(
+            INFO,
+            "Received event {mint:?}; will generate metadata and assets for token id {}",
+            mint.token_id,
+        );
+        mutate_state(|s| process_event(s, EventType::AcceptedMint(mint)));
+    }
+    if read_state(State::has_events_to_mint) {
+        ic_cdk_timers::set_timer(Duration::from_secs(0), || ic_cdk::spawn(mint_cketh()));
+    }
+    for error in errors {
+        if let TransferEventError::InvalidEventSource {
+            source,
+            token_id,
+            block_number,
+            error,
+        } = &error
+        {
+            mutate_state(|s| {
+                process_event(
+                    s,
+                    EventType::InvalidTransfer {
+                        event_source: *source,
+                        reason: error.to_string(),
+                        token_id: *token_id,
+                        collection: collection.cloned(),
+                        block_number: Some(*block_number),
+                    },
+                )
+            });
+        }
+        report_transaction_error(error);
+    }
+}
+
 pub async fn scrape_eth_logs() {
     let _guard = match TimerGuard::new(TaskType::ScrapEthLogs) {
         Ok(guard) => guard,
         Err(_) => return,
     };
-    let contract_address = read_state(|s| s.ethereum_contract_address);
+    let (scraping_mode, token_standard) = read_state(|s| (s.scraping_mode, s.token_standard));
+    if !roll_back_reorganized_blocks().await {
+        log!(
+            DEBUG,
+            "[scrape_eth_logs]: skipping scrapping ETH logs: cannot check for chain reorganizations"
+        );
+        return;
+    }
     let last_block_number = match update_last_observed_block_number().await {
         Some(block_number) => block_number,
         None => {
@@ -186,44 +386,176 @@ pub async fn scrape_eth_logs() {
             return;
         }
     };
-    let mut last_scraped_block_number = read_state(|s| s.last_scraped_block_number);
 
-    while last_scraped_block_number < last_block_number {
-        let next_block_to_query = last_scraped_block_number
-            .checked_increment()
-            .unwrap_or(BlockNumber::MAX);
-        last_scraped_block_number = match scrape_eth_logs_range_inclusive(
+    // The contracts are scraped one after the other, each from the last block scraped for it.
+    for (collection, contract_address, mut last_scraped_block_number) in
+        read_state(State::scraped_contracts)
+    {
+        while last_scraped_block_number < last_block_number {
+            let next_block_to_query = last_scraped_block_number
+                .checked_increment()
+                .unwrap_or(BlockNumber::MAX);
+            last_scraped_block_number = match scrape_eth_logs_range_inclusive(
+                collection.as_ref(),
+                contract_address,
+                scraping_mode,
+                token_standard,
+                next_block_to_query,
+                last_block_number,
+            )
+            .await
+            {
+                Some(last_scraped_block_number) => last_scraped_block_number,
+                None => break,
+            };
+            reveal_if_block_reached();
+        }
+    }
+}
+
+/// Scrapes again the blocks that were skipped because their logs did not fit into a single response.
+///
+/// The logs of each skipped block are queried with one filter per event signature,
+/// or read from the receipts of its transactions for ERC-721 contracts,
+/// and a block whose logs still cannot be retrieved remains skipped until the next attempt.
+/// The retries hold the scraping lock, so that their events are not interleaved with a concurrent scraping.
+pub async fn retry_skipped_blocks() {
+    let _guard = match TimerGuard::new(TaskType::ScrapEthLogs) {
+        Ok(guard) => guard,
+        Err(_) => return,
+    };
+    let (scraping_mode, token_standard) = read_state(|s| (s.scraping_mode, s.token_standard));
+
+    for (collection, contract_address, block_number) in
+        read_state(State::skipped_blocks_of_contracts)
+    {
+        let (transfer_events, errors) = match crate::eth_logs::skipped_block_eth_events(
+            collection.as_ref(),
             contract_address,
-            next_block_to_query,
-            last_block_number,
+            block_number,
+            scraping_mode,
+            token_standard,
         )
         .await
         {
-            Some(last_scraped_block_number) => last_scraped_block_number,
-            None => {
-                return;
+            Ok(result) => result,
+            Err(e) => {
+                log!(
+                    INFO,
+                    "[retry_skipped_blocks]: failed to get transfers of skipped block {block_number}: {e:?}",
+                );
+                continue;
             }
         };
+        log!(
+            INFO,
+            "[retry_skipped_blocks]: scraped {} transfers in skipped block {block_number}",
+            transfer_events.len()
+        );
+        let transfer_events = read_state(|s| s.skipped_block_transfers(transfer_events));
+        process_transfer_events(collection.as_ref(), transfer_events, errors);
+        mutate_state(|s| {
+            process_event(
+                s,
+                EventType::ScrapedSkippedBlock {
+                    block_number,
+                    collection,
+                },
+            )
+        });
     }
 }
 
 pub async fn update_last_observed_block_number() -> Option<BlockNumber> {
     let block_height = read_state(State::ethereum_block_height);
     match read_state(EthRpcClient::from_state)
-        .eth_get_block_by_number(BlockSpec::Tag(block_height))
+        .eth_get_block_by_number(BlockSpec::Tag(block_height.block_tag()))
         .await
     {
-        Ok(latest_block) => {
-            let block_number = Some(latest_block.number);
-            mutate_state(|s| s.last_observed_block_number = block_number);
-            block_number
+        Ok(tagged_block) => {
+            let block_number = block_height.block_number(tagged_block.number);
+            mutate_state(|s| {
+                s.last_observed_block_number = Some(block_number);
+                // The hash of a block that is not scraped yet could trigger needless rollbacks.
+                if block_number == tagged_block.number {
+                    s.record_block_hash(tagged_block.number, tagged_block.hash);
+                }
+            });
+            Some(block_number)
         }
         Err(e) => {
-            log!(
-                INFO,
-                "Failed to get the latest {block_height} block number: {e:?}"
-            );
+            log!(INFO, "Failed to get the {block_height} block number: {e:?}");
             read_state(|s| s.last_observed_block_number)
         }
     }
 }
+
+/// Checks that the canonical chain still includes the last blocks processed by the minter,
+/// and rolls back the events of the blocks that it no longer includes.
+///
+/// Since the hash of a block commits to all its ancestors, the recorded blocks are checked
+/// from the most recent one, until one of them is still part of the canonical chain.
+/// Rolled back events that are included again keep the randomness they received,
+/// rather than getting fresh randomness from `raw_rand`.
+/// Returns `false` if the canonical chain could not be queried.
+async fn roll_back_reorganized_blocks() -> bool {
+    // Finalized blocks cannot be reorganized.
+    if read_state(|s| s.ethereum_block_height == BlockHeight::Tag(BlockTag::Finalized)) {
+        return true;
+    }
+    let block_hashes: Vec<(BlockNumber, Hash)> = read_state(|s| {
+        s.block_hashes
+            .iter()
+            .rev()
+            .map(|(block_number, block_hash)| (*block_number, *block_hash))
+            .collect()
+    });
+    let (latest_block_number, oldest_block_number) =
+        match (block_hashes.first(), block_hashes.last()) {
+            (Some((latest, _)), Some((oldest, _))) => (*latest, *oldest),
+            _ => return true,
+        };
+
+    let client = read_state(EthRpcClient::from_state);
+    let mut canonical_block_number = None;
+    for (block_number, block_hash) in block_hashes {
+        match client
+            .eth_get_block_by_number(BlockSpec::Number(block_number))
+            .await
+        {
+            Ok(block) if block.hash == block_hash => {
+                canonical_block_number = Some(block_number);
+                break;
+            }
+            Ok(block) => {
+                log!(
+                    INFO,
+                    "[roll_back_reorganized_blocks]: block {block_number} now has hash {} instead of {block_hash}",
+                    block.hash
+                );
+            }
+            Err(e) => {
+                log!(
+                    INFO,
+                    "[roll_back_reorganized_blocks]: failed to get block {block_number}: {e:?}"
+                );
+                return false;
+            }
+        }
+    }
+
+    let block_number = match canonical_block_number {
+        Some(block_number) if block_number == latest_block_number => return true,
+        Some(block_number) => block_number,
+        // The reorganization is deeper than the recorded blocks.
+        None => oldest_block_number
+            .checked_decrement()
+            .unwrap_or(BlockNumber::ZERO),
+    };
+    log!(
+        INFO,
+        "[roll_back_reorganized_blocks]: rolling back the events after block {block_number}"
+    );
+    mutate_state(|s| process_event(s, EventType::ChainReorganized { block_number }));
+    true
+}
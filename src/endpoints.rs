@@ -1,6 +1,7 @@
 use candid::{CandidType, Deserialize, Nat};
 use icrc_ledger_types::icrc2::transfer_from::TransferFromError;
 use minicbor::{Decode, Encode};
+use serde_bytes::ByteBuf;
 use std::fmt::{Display, Formatter};
 
 #[derive(CandidType, Deserialize, Clone, Debug, PartialEq, Eq, Hash)]
@@ -13,23 +14,62 @@ pub struct RetrieveEthRequest {
     pub block_index: Nat,
 }
 
-#[derive(CandidType, Debug, Default, Deserialize, Clone, Encode, Decode, PartialEq, Eq)]
-#[cbor(index_only)]
+#[derive(CandidType, Debug, Default, Deserialize, Clone, PartialEq, Eq)]
 pub enum CandidBlockTag {
     /// The latest mined block.
     #[default]
-    #[cbor(n(0))]
     Latest,
     /// The latest safe head block.
     /// See
     /// <https://www.alchemy.com/overviews/ethereum-commitment-levels#what-are-ethereum-commitment-levels>
-    #[cbor(n(1))]
     Safe,
     /// The latest finalized block.
     /// See
     /// <https://www.alchemy.com/overviews/ethereum-commitment-levels#what-are-ethereum-commitment-levels>
-    #[cbor(n(2))]
     Finalized,
+    /// The block with the given number of confirmations, i.e., the given number of blocks below the latest block.
+    LatestMinusConfirmations(u64),
+}
+
+// Tags without confirmations are encoded as their index only,
+// as they were before confirmations were supported.
+impl<C> Encode<C> for CandidBlockTag {
+    fn encode<W: minicbor::encode::Write>(
+        &self,
+        e: &mut minicbor::Encoder<W>,
+        _ctx: &mut C,
+    ) -> Result<(), minicbor::encode::Error<W::Error>> {
+        match self {
+            Self::Latest => e.u32(0)?.ok(),
+            Self::Safe => e.u32(1)?.ok(),
+            Self::Finalized => e.u32(2)?.ok(),
+            Self::LatestMinusConfirmations(confirmations) => {
+                e.array(2)?.u32(3)?.u64(*confirmations)?.ok()
+            }
+        }
+    }
+}
+
+impl<'b, C> Decode<'b, C> for CandidBlockTag {
+    fn decode(
+        d: &mut minicbor::Decoder<'b>,
+        _ctx: &mut C,
+    ) -> Result<Self, minicbor::decode::Error> {
+        if d.datatype()? == minicbor::data::Type::Array {
+            if d.array()? != Some(2) || d.u32()? != 3 {
+                return Err(minicbor::decode::Error::message(
+                    "expected the index of LatestMinusConfirmations followed by the confirmations",
+                ));
+            }
+            return Ok(Self::LatestMinusConfirmations(d.u64()?));
+        }
+        match d.u32()? {
+            0 => Ok(Self::Latest),
+            1 => Ok(Self::Safe),
+            2 => Ok(Self::Finalized),
+            n => Err(minicbor::decode::Error::unknown_variant(n)),
+        }
+    }
 }
 
 #[derive(CandidType, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
@@ -129,6 +169,138 @@ impl From<TransferFromError> for WithdrawalError {
     }
 }
 
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct CreateAssetBatchArg {
+    pub key: String,
+    pub content_type: String,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct UploadAssetChunkArg {
+    pub batch_id: u64,
+    pub content: ByteBuf,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct CommitAssetBatchArg {
+    pub batch_id: u64,
+    /// The SHA-256 hash of the whole content of the batch.
+    pub sha256: ByteBuf,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum AssetError {
+    NotController,
+    InvalidKey(String),
+    InvalidContentType(String),
+    TooManyPendingBatches { max_pending_batches: u64 },
+    BatchNotFound { batch_id: u64 },
+    AssetTooLarge { max_size: u64 },
+    HashMismatch { expected: String, actual: String },
+    InvalidLayers(String),
+    AssetInUse { key: String },
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct RevealStatus {
+    pub hidden: bool,
+    pub provenance_hash: Option<String>,
+    /// The seed from which the traits are derived, only published after the reveal.
+    pub seed: Option<String>,
+    pub reveal_block_number: Option<Nat>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Token {
+    pub token_id: Nat,
+    /// The additional collection of the token, if not the collection configured at init.
+    pub collection: Option<String>,
+    /// The current owner of the token, as last seen by the minter.
+    pub owner: String,
+    /// The number of editions in circulation, which is at most one for ERC-721 tokens.
+    pub supply: Nat,
+    pub transaction_hash: String,
+    pub block_number: Nat,
+    /// The traits of the token, empty until a hidden collection is revealed.
+    pub attributes: Vec<TokenAttribute>,
+    /// The URL of the ERC-721 metadata JSON of the token.
+    pub metadata_url: String,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct TokenAttribute {
+    pub trait_type: String,
+    pub value: String,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ListTokensArg {
+    pub start: u64,
+    pub length: u64,
+    /// Lists the tokens of the given additional collection instead of the collection configured at init.
+    pub collection: Option<String>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct CollectionInfo {
+    pub id: String,
+    pub ethereum_contract_address: String,
+    pub first_scraped_block_number: Nat,
+    pub last_scraped_block_number: Nat,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum GenerationStatus {
+    /// The minter did not see any transfer of the token.
+    NotFound,
+    /// The minter accepted the transfer but did not generate the token yet.
+    Pending,
+    /// The token was generated and its metadata is served.
+    Generated,
+    /// The transfer of the token was rejected.
+    Invalid { reason: String },
+}
+
+/// The result of the last audit of the NFT contracts against the minter state.
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ConsistencyAudit {
+    /// The time in nanoseconds since the epoch at which the audit completed.
+    pub timestamp: u64,
+    pub collections: Vec<CollectionAudit>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct CollectionAudit {
+    /// The additional collection, if not the collection configured at init.
+    pub collection: Option<String>,
+    pub ethereum_contract_address: String,
+    /// The block at which the contract was read, i.e., the last block scraped for the collection.
+    pub block_number: Nat,
+    /// The `totalSupply()` of the contract, if the call succeeded.
+    pub onchain_total_supply: Option<Nat>,
+    /// The number of tokens minted or to mint by the minter, and not burned.
+    pub expected_total_supply: Nat,
+    /// The IDs of the tokens whose `ownerOf` was checked.
+    pub sampled_token_ids: Vec<Nat>,
+    pub discrepancies: Vec<Discrepancy>,
+    /// The calls that failed, leaving part of the collection unchecked.
+    pub errors: Vec<String>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum Discrepancy {
+    /// The contract has a different number of tokens than the minter.
+    TotalSupply { onchain: Nat, expected: Nat },
+    /// The token has a different owner on the contract than recorded by the minter.
+    Owner {
+        token_id: Nat,
+        onchain: String,
+        expected: String,
+    },
+    /// `ownerOf` reverted, i.e., the token does not exist on the contract.
+    MissingToken { token_id: Nat, error: String },
+}
+
 pub mod events {
     use crate::lifecycle::init::InitArg;
     use crate::lifecycle::upgrade::UpgradeArg;
@@ -157,6 +329,7 @@ pub mod events {
     pub struct EventSource {
         pub transaction_hash: String,
         pub log_index: Nat,
+        pub batch_index: Option<u32>,
     }
 
     #[derive(CandidType, Deserialize, Debug, Clone, PartialEq, Eq)]
@@ -205,16 +378,59 @@ pub mod events {
             from_address: String,
             to_address: String,
             token_id: Nat,
+            batch_index: Option<u32>,
+            value: Option<Nat>,
+            collection: Option<String>,
+            block_hash: Option<String>,
+        },
+        AcceptedEdition {
+            transaction_hash: String,
+            block_number: Nat,
+            log_index: Nat,
+            from_address: String,
+            to_address: String,
+            token_id: Nat,
+            batch_index: Option<u32>,
+            value: Option<Nat>,
+            collection: Option<String>,
+            block_hash: Option<String>,
         },
         InvalidTransfer {
             event_source: EventSource,
             reason: String,
+            token_id: Option<Nat>,
+            collection: Option<String>,
+        },
+        TransferredNft {
+            transaction_hash: String,
+            block_number: Nat,
+            log_index: Nat,
+            from_address: String,
+            to_address: String,
+            token_id: Nat,
+            batch_index: Option<u32>,
+            value: Option<Nat>,
+            collection: Option<String>,
+            block_hash: Option<String>,
+        },
+        BurnedNft {
+            transaction_hash: String,
+            block_number: Nat,
+            log_index: Nat,
+            from_address: String,
+            token_id: Nat,
+            batch_index: Option<u32>,
+            value: Option<Nat>,
+            collection: Option<String>,
+            block_hash: Option<String>,
         },
         MintedNft {
             event_source: EventSource,
         },
         SyncedToBlock {
             block_number: Nat,
+            collection: Option<String>,
+            block_hash: Option<String>,
         },
         AcceptedEthWithdrawalRequest {
             withdrawal_amount: Nat,
@@ -248,6 +464,34 @@ pub mod events {
         },
         SkippedBlock {
             block_number: Nat,
+            collection: Option<String>,
+        },
+        CommittedAsset {
+            key: String,
+            content_type: String,
+            sha256: String,
+            size: u64,
+        },
+        ReceivedRandomness {
+            seed: String,
+            event_sources: Vec<EventSource>,
+        },
+        CommittedProvenance {
+            provenance_hash: String,
+        },
+        Revealed {
+            seed: String,
+        },
+        ChainReorganized {
+            block_number: Nat,
+        },
+        ScrapedSkippedBlock {
+            block_number: Nat,
+            collection: Option<String>,
+        },
+        RejectedMint {
+            event_source: EventSource,
+            reason: String,
         },
     }
 }
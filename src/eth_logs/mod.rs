@@ -1,13 +1,18 @@
 #[cfg(test)]
 mod tests;
 
+use crate::abi::{read_uint256_array, read_word};
 use crate::address::Address;
-use crate::eth_rpc::{FixedSizeData, Hash, LogEntry};
+use crate::collections::{CollectionId, TokenKey};
+use crate::eth_rpc::{BlockTransactions, FixedSizeData, Hash, LogEntry, Topic};
+use crate::eth_rpc_client::responses::{TransactionReceipt, TransactionStatus};
 use crate::eth_rpc_client::{EthRpcClient, MultiCallError};
 use crate::logs::{DEBUG, INFO};
 use crate::numeric::{BlockNumber, LogIndex};
 use crate::state::read_state;
 
+use candid::{CandidType, Deserialize};
+
 use ethnum::u256;
 use hex_literal::hex;
 use ic_canister_log::log;
@@ -15,9 +20,79 @@ use minicbor::{Decode, Encode};
 use std::fmt;
 use thiserror::Error;
 
+/// `Transfer(address,address,uint256)` of ERC-721.
 pub(crate) const TRANSFER_EVENT_TOPIC: [u8; 32] =
     hex!("ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef");
 
+/// `TransferSingle(address,address,address,uint256,uint256)` of ERC-1155.
+pub(crate) const TRANSFER_SINGLE_EVENT_TOPIC: [u8; 32] =
+    hex!("c3d58168c5ae7397731d063d5bbf3d657854427343f4c083240f7aacaa2d0f62");
+
+/// `TransferBatch(address,address,address,uint256[],uint256[])` of ERC-1155.
+pub(crate) const TRANSFER_BATCH_EVENT_TOPIC: [u8; 32] =
+    hex!("4a39dc06d4c0dbc64b70af90fd698a233a518aa5d07e595d983b8c0526c8f7fb");
+
+/// The token standard implemented by the NFT contract.
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, Default, Encode, Decode, PartialEq, Eq)]
+#[cbor(index_only)]
+pub enum TokenStandard {
+    /// Non-fungible tokens, each minted once by a `Transfer` event.
+    #[default]
+    #[n(0)]
+    Erc721,
+    /// Multi-token contracts, where each token ID can be minted in many editions
+    /// by `TransferSingle` and `TransferBatch` events.
+    #[n(1)]
+    Erc1155,
+}
+
+/// Which `Transfer` events of the NFT contract the minter scrapes.
+/// Burns, i.e., transfers to the zero address, are always scraped.
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, Default, Encode, Decode, PartialEq, Eq)]
+#[cbor(index_only)]
+pub enum ScrapingMode {
+    /// Only mints, i.e., transfers from the zero address, and burns.
+    /// Mints and burns match different topic filters, so each scraped block range costs
+    /// two `eth_getLogs` requests instead of one.
+    #[default]
+    #[n(0)]
+    Mints,
+    /// All transfers, so that the minter keeps track of the owner of each token.
+    #[n(1)]
+    AllTransfers,
+}
+
+/// A transfer of a single token of the NFT contract.
+/// An ERC-1155 `TransferBatch` event results in one transfer per token ID.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Encode, Decode)]
+pub struct TransferEvent {
+    #[n(0)]
+    pub transaction_hash: Hash,
+    #[n(1)]
+    pub block_number: BlockNumber,
+    #[n(2)]
+    pub log_index: LogIndex,
+    #[n(3)]
+    pub from_address: Address,
+    #[n(4)]
+    pub to_address: Address,
+    #[cbor(n(5), with = "crate::cbor::u256")]
+    pub token_id: u256,
+    /// The position of the token in the IDs of an ERC-1155 `TransferBatch` event.
+    #[n(6)]
+    pub batch_index: Option<u32>,
+    /// The number of transferred editions of an ERC-1155 token.
+    #[cbor(n(7), with = "crate::cbor::u256::option")]
+    pub value: Option<u256>,
+    /// The additional collection of the contract, if not the collection configured at init.
+    #[n(8)]
+    pub collection: Option<CollectionId>,
+    /// The hash of the block including the event.
+    /// Unknown for events scraped before the minter recorded block hashes.
+    #[n(9)]
+    pub block_hash: Option<Hash>,
+}
+
 #[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Encode, Decode)]
 pub struct MintEvent {
     #[n(0)]
@@ -32,6 +107,14 @@ pub struct MintEvent {
     pub to_address: Address,
     #[cbor(n(5), with = "crate::cbor::u256")]
     pub token_id: u256,
+    #[n(6)]
+    pub batch_index: Option<u32>,
+    #[cbor(n(7), with = "crate::cbor::u256::option")]
+    pub value: Option<u256>,
+    #[n(8)]
+    pub collection: Option<CollectionId>,
+    #[n(9)]
+    pub block_hash: Option<Hash>,
 }
 
 impl fmt::Debug for MintEvent {
@@ -41,25 +124,35 @@ impl fmt::Debug for MintEvent {
             .field("block_number", &self.block_number)
             .field("log_index", &self.log_index)
             .field("from_address", &self.from_address)
-            .field("to_address", &self.from_address)
+            .field("to_address", &self.to_address)
             .field("token_id", &self.token_id)
+            .field("batch_index", &self.batch_index)
+            .field("value", &self.value)
+            .field("collection", &self.collection)
+            .field("block_hash", &self.block_hash)
             .finish()
     }
 }
 
 /// A unique identifier of the event source: the source transaction hash and the log
-/// entry index.
+/// entry index, as well as the position of the token in an ERC-1155 `TransferBatch` event.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Encode, Decode)]
 pub struct EventSource {
     #[n(0)]
     pub transaction_hash: Hash,
     #[n(1)]
     pub log_index: LogIndex,
+    #[n(2)]
+    pub batch_index: Option<u32>,
 }
 
 impl fmt::Display for EventSource {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "0x{}:{}", self.transaction_hash, self.log_index)
+        write!(f, "0x{}:{}", self.transaction_hash, self.log_index)?;
+        if let Some(batch_index) = self.batch_index {
+            write!(f, ":{batch_index}")?;
+        }
+        Ok(())
     }
 }
 
@@ -68,43 +161,287 @@ impl MintEvent {
         EventSource {
             transaction_hash: self.transaction_hash,
             log_index: self.log_index,
+            batch_index: self.batch_index,
+        }
+    }
+
+    /// The number of minted editions, which is always one for ERC-721 tokens.
+    pub fn amount(&self) -> u256 {
+        self.value.unwrap_or(u256::ONE)
+    }
+
+    pub fn token_key(&self) -> TokenKey {
+        TokenKey {
+            collection: self.collection.clone(),
+            token_id: self.token_id,
+        }
+    }
+}
+
+impl TransferEvent {
+    pub fn source(&self) -> EventSource {
+        EventSource {
+            transaction_hash: self.transaction_hash,
+            log_index: self.log_index,
+            batch_index: self.batch_index,
+        }
+    }
+
+    /// The number of transferred editions, which is always one for ERC-721 tokens.
+    pub fn amount(&self) -> u256 {
+        self.value.unwrap_or(u256::ONE)
+    }
+
+    pub fn token_key(&self) -> TokenKey {
+        TokenKey {
+            collection: self.collection.clone(),
+            token_id: self.token_id,
         }
     }
+
+    /// Whether the transfer destroys the token, i.e., sends it to the zero address.
+    pub fn is_burn(&self) -> bool {
+        self.to_address == Address::ZERO
+    }
 }
 
+/// Returns the transfers of the contract of the given collection in the given block range,
+/// in the order in which they were emitted.
 pub async fn last_received_eth_events(
+    collection: Option<&CollectionId>,
     contract_address: Address,
     from: BlockNumber,
     to: BlockNumber,
-) -> Result<(Vec<MintEvent>, Vec<TransferEventError>), MultiCallError<Vec<LogEntry>>> {
-    use crate::eth_rpc::GetLogsParam;
-
+    mode: ScrapingMode,
+    standard: TokenStandard,
+) -> Result<(Vec<TransferEvent>, Vec<TransferEventError>), MultiCallError<Vec<LogEntry>>> {
     if from > to {
         ic_cdk::trap(&format!(
             "BUG: invalid block range. {:?} should not be greater than {:?}",
             from, to
         ));
     }
+    received_eth_events(
+        collection,
+        contract_address,
+        from,
+        to,
+        standard,
+        transfer_filters(mode, standard),
+    )
+    .await
+}
+
+/// Returns the transfers of the contract of the given collection in a block that was skipped,
+/// in the order in which they were emitted.
+///
+/// The transfers are retrieved by the queries of [`skipped_block_queries`],
+/// whose responses are smaller than when scraping a block range.
+pub async fn skipped_block_eth_events(
+    collection: Option<&CollectionId>,
+    contract_address: Address,
+    block_number: BlockNumber,
+    mode: ScrapingMode,
+    standard: TokenStandard,
+) -> Result<(Vec<TransferEvent>, Vec<TransferEventError>), SkippedBlockError> {
+    let mut entries = Vec::new();
+    for query in skipped_block_queries(mode, standard) {
+        match query {
+            SkippedBlockQuery::Logs(topics) => entries.extend(
+                get_logs(contract_address, block_number, block_number, topics)
+                    .await
+                    .map_err(SkippedBlockError::Logs)?,
+            ),
+            SkippedBlockQuery::Receipts(filters) => {
+                entries.extend(receipt_log_entries(contract_address, block_number, &filters).await?)
+            }
+        }
+    }
+    Ok(parse_log_entries(collection, entries, standard))
+}
+
+/// The failure to retrieve the transfers of a skipped block, which remains skipped.
+#[derive(Debug, PartialEq, Eq)]
+pub enum SkippedBlockError {
+    Logs(MultiCallError<Vec<LogEntry>>),
+    Block(MultiCallError<BlockTransactions>),
+    Receipt(MultiCallError<Option<TransactionReceipt>>),
+    /// A transaction of the block has no receipt or a receipt in another block,
+    /// i.e., the block was reorganized while its receipts were retrieved.
+    ReorganizedBlock {
+        transaction_hash: Hash,
+    },
+}
+
+/// A query retrieving the transfers of a skipped block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum SkippedBlockQuery {
+    /// The logs of the block matching the topic filter.
+    Logs(Vec<Topic>),
+    /// The logs matching one of the topic filters in the receipts of the transactions of the block.
+    Receipts(Vec<Vec<Topic>>),
+}
+
+/// Returns the queries retrieving the transfers scraped in the given mode in a skipped block.
+///
+/// The logs of ERC-1155 contracts are queried with one filter per event signature.
+/// The ERC-721 filters have a single event signature, and the recipients and token IDs are unknown
+/// beforehand, so the logs are read from the receipts of the transactions of the block instead,
+/// each of which is much smaller than the logs of the whole block.
+fn skipped_block_queries(mode: ScrapingMode, standard: TokenStandard) -> Vec<SkippedBlockQuery> {
+    let filters = transfer_filters(mode, standard);
+    match standard {
+        TokenStandard::Erc721 => vec![SkippedBlockQuery::Receipts(filters)],
+        TokenStandard::Erc1155 => filters
+            .into_iter()
+            .flat_map(|topics| match topics[0].clone() {
+                Topic::OneOf(signatures) => signatures
+                    .into_iter()
+                    .map(|signature| {
+                        let mut single_event_topics = topics.clone();
+                        single_event_topics[0] = Topic::from(signature);
+                        single_event_topics
+                    })
+                    .collect(),
+                _ => vec![topics],
+            })
+            .map(SkippedBlockQuery::Logs)
+            .collect(),
+    }
+}
 
-    let result = read_state(EthRpcClient::from_state)
+/// Returns the logs of the contract matching one of the topic filters
+/// in the receipts of the transactions of the given block, in the order in which they were emitted.
+async fn receipt_log_entries(
+    contract_address: Address,
+    block_number: BlockNumber,
+    filters: &[Vec<Topic>],
+) -> Result<Vec<LogEntry>, SkippedBlockError> {
+    let client = read_state(EthRpcClient::from_state);
+    let block = client
+        .eth_get_block_transactions(block_number)
+        .await
+        .map_err(SkippedBlockError::Block)?;
+    let mut entries = Vec::new();
+    for transaction_hash in block.transactions {
+        let receipt = client
+            .eth_get_transaction_receipt(transaction_hash)
+            .await
+            .map_err(SkippedBlockError::Receipt)?
+            .filter(|receipt| receipt.block_hash == block.hash)
+            .ok_or(SkippedBlockError::ReorganizedBlock { transaction_hash })?;
+        entries.extend(receipt.logs.into_iter().filter(|entry| {
+            entry.address == contract_address
+                && filters
+                    .iter()
+                    .any(|topics| matches_topics(topics, &entry.topics))
+        }));
+    }
+    Ok(entries)
+}
+
+/// Whether the topics of a log entry match the topic filter, as evaluated by `eth_getLogs`.
+fn matches_topics(filter: &[Topic], topics: &[FixedSizeData]) -> bool {
+    filter.len() <= topics.len()
+        && filter
+            .iter()
+            .zip(topics)
+            .all(|(expected, topic)| match expected {
+                Topic::Any => true,
+                Topic::Exact(expected) => expected == topic,
+                Topic::OneOf(expected) => expected.contains(topic),
+            })
+}
+
+/// Returns the topic filters matching the transfers scraped in the given mode,
+/// whose first topic is the event signature.
+fn transfer_filters(mode: ScrapingMode, standard: TokenStandard) -> Vec<Vec<Topic>> {
+    // The position of the sender topic, which is followed by the recipient topic.
+    let (transfer, sender_position) = match standard {
+        TokenStandard::Erc721 => (Topic::from(FixedSizeData(TRANSFER_EVENT_TOPIC)), 1),
+        TokenStandard::Erc1155 => (
+            Topic::OneOf(vec![
+                FixedSizeData(TRANSFER_SINGLE_EVENT_TOPIC),
+                FixedSizeData(TRANSFER_BATCH_EVENT_TOPIC),
+            ]),
+            2,
+        ),
+    };
+    let zero_address_at = |position: usize| {
+        let mut topics = vec![Topic::Any; position + 1];
+        topics[0] = transfer.clone();
+        topics[position] = Topic::from(Address::ZERO.to_fixed_size_data());
+        topics
+    };
+    match mode {
+        // Topics cannot match either the sender or the recipient,
+        // hence mints and burns are queried separately, which doubles the requests per block range.
+        // Burns are always scraped, since burned tokens would otherwise keep serving live metadata,
+        // and scraping all transfers in a single query usually returns far more logs.
+        ScrapingMode::Mints => vec![
+            zero_address_at(sender_position),
+            zero_address_at(sender_position + 1),
+        ],
+        ScrapingMode::AllTransfers => vec![vec![transfer.clone()]],
+    }
+}
+
+async fn received_eth_events(
+    collection: Option<&CollectionId>,
+    contract_address: Address,
+    from: BlockNumber,
+    to: BlockNumber,
+    standard: TokenStandard,
+    filters: Vec<Vec<Topic>>,
+) -> Result<(Vec<TransferEvent>, Vec<TransferEventError>), MultiCallError<Vec<LogEntry>>> {
+    let mut result = Vec::new();
+    for topics in filters {
+        result.extend(get_logs(contract_address, from, to, topics).await?);
+    }
+    Ok(parse_log_entries(collection, result, standard))
+}
+
+async fn get_logs(
+    contract_address: Address,
+    from: BlockNumber,
+    to: BlockNumber,
+    topics: Vec<Topic>,
+) -> Result<Vec<LogEntry>, MultiCallError<Vec<LogEntry>>> {
+    use crate::eth_rpc::GetLogsParam;
+
+    read_state(EthRpcClient::from_state)
         .eth_get_logs(GetLogsParam {
             from_block: from.into(),
             to_block: to.into(),
             address: vec![contract_address],
-            topics: vec![
-                FixedSizeData(TRANSFER_EVENT_TOPIC),
-                Address::ZERO.to_fixed_size_data(), // this ensures we only receive mint events
-            ],
+            topics,
         })
-        .await?;
+        .await
+}
 
-    let (ok, not_ok): (Vec<_>, Vec<_>) = result
+fn parse_log_entries(
+    collection: Option<&CollectionId>,
+    entries: Vec<LogEntry>,
+    standard: TokenStandard,
+) -> (Vec<TransferEvent>, Vec<TransferEventError>) {
+    let (ok, not_ok): (Vec<_>, Vec<_>) = entries
         .into_iter()
-        .map(MintEvent::try_from)
+        .map(|entry| parse_transfer_events(entry, standard))
         .partition(Result::is_ok);
-    let valid_transactions: Vec<MintEvent> = ok.into_iter().map(Result::unwrap).collect();
+    let mut valid_transactions: Vec<TransferEvent> = ok
+        .into_iter()
+        .flat_map(Result::unwrap)
+        .map(|event| TransferEvent {
+            collection: collection.cloned(),
+            ..event
+        })
+        .collect();
+    // The providers' responses are sorted by hash to reach consensus and mints and burns
+    // may come from separate queries, while ownership changes must be applied in chain order.
+    valid_transactions
+        .sort_unstable_by_key(|event| (event.block_number, event.log_index, event.batch_index));
     let errors: Vec<TransferEventError> = not_ok.into_iter().map(Result::unwrap_err).collect();
-    Ok((valid_transactions, errors))
+    (valid_transactions, errors)
 }
 
 pub fn report_transaction_error(error: TransferEventError) {
@@ -115,7 +452,7 @@ pub fn report_transaction_error(error: TransferEventError) {
                 "[report_transaction_error]: ignoring pending log entry",
             );
         }
-        TransferEventError::InvalidEventSource { source, error } => {
+        TransferEventError::InvalidEventSource { source, error, .. } => {
             log!(
                 INFO,
                 "[report_transaction_error]: cannot process {source} due to {error}",
@@ -129,6 +466,9 @@ pub enum TransferEventError {
     PendingLogEntry,
     InvalidEventSource {
         source: EventSource,
+        /// The transferred token, if the log entry has the expected topic.
+        token_id: Option<u256>,
+        block_number: BlockNumber,
         error: EventSourceError,
     },
 }
@@ -146,31 +486,128 @@ pub enum EventSourceError {
     InvalidEvent(String),
 }
 
-impl TryFrom<LogEntry> for MintEvent {
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReceiptVerificationError {
+    /// The providers do not know the transaction yet, e.g., because they lag behind the scraped block,
+    /// or anymore, because the block of the event was reorganized, which is detected afterwards.
+    MissingReceipt,
+    /// The transaction is included in another block than the event,
+    /// which is rolled back once the chain reorganization is detected.
+    OtherBlock,
+    /// The receipt contradicts the event.
+    Mismatch(String),
+}
+
+/// Checks that the receipt of the transaction of a mint event confirms the event:
+/// the transaction succeeded, and emitted at the same index a log of the given contract
+/// from which the same mint is parsed, i.e., with the same topics and data.
+pub fn verify_mint_receipt(
+    event: &MintEvent,
+    contract_address: Address,
+    standard: TokenStandard,
+    receipt: Option<&TransactionReceipt>,
+) -> Result<(), ReceiptVerificationError> {
+    let mismatch = |reason: String| ReceiptVerificationError::Mismatch(reason);
+    let source = event.source();
+    let receipt = receipt.ok_or(ReceiptVerificationError::MissingReceipt)?;
+    if receipt.block_number != event.block_number
+        || event
+            .block_hash
+            .is_some_and(|block_hash| block_hash != receipt.block_hash)
+    {
+        return Err(ReceiptVerificationError::OtherBlock);
+    }
+    if receipt.status != TransactionStatus::Success {
+        return Err(mismatch(format!("transaction of event {source} failed")));
+    }
+    let log = receipt
+        .logs
+        .iter()
+        .find(|log| log.log_index == Some(event.log_index))
+        .ok_or_else(|| {
+            mismatch(format!(
+                "receipt of event {source} has no log at index {}",
+                event.log_index
+            ))
+        })?;
+    if log.address != contract_address {
+        return Err(mismatch(format!(
+            "log of event {source} was emitted by {} instead of contract {contract_address}",
+            log.address
+        )));
+    }
+    let transfers = parse_transfer_events(log.clone(), standard).map_err(|e| {
+        mismatch(format!(
+            "log of event {source} in the receipt is invalid: {e:?}"
+        ))
+    })?;
+    let is_same_mint = transfers.into_iter().any(|transfer| {
+        MintEvent::try_from(TransferEvent {
+            collection: event.collection.clone(),
+            block_hash: event.block_hash,
+            ..transfer
+        })
+        .is_ok_and(|mint| mint == *event)
+    });
+    if !is_same_mint {
+        return Err(mismatch(format!(
+            "log of event {source} in the receipt does not match the event"
+        )));
+    }
+    Ok(())
+}
+
+/// Parses the transfers of a contract implementing the given token standard from a log entry.
+pub fn parse_transfer_events(
+    entry: LogEntry,
+    standard: TokenStandard,
+) -> Result<Vec<TransferEvent>, TransferEventError> {
+    match standard {
+        TokenStandard::Erc721 => TransferEvent::try_from(entry).map(|event| vec![event]),
+        TokenStandard::Erc1155 => parse_erc1155_transfer_events(entry),
+    }
+}
+
+/// Returns the source, the block number and the block hash of a log entry that is included in a block.
+fn included_log_entry(
+    entry: &LogEntry,
+) -> Result<(EventSource, BlockNumber, Hash), TransferEventError> {
+    let block_hash = entry
+        .block_hash
+        .ok_or(TransferEventError::PendingLogEntry)?;
+    let block_number = entry
+        .block_number
+        .ok_or(TransferEventError::PendingLogEntry)?;
+    let transaction_hash = entry
+        .transaction_hash
+        .ok_or(TransferEventError::PendingLogEntry)?;
+    let _transaction_index = entry
+        .transaction_index
+        .ok_or(TransferEventError::PendingLogEntry)?;
+    let log_index = entry.log_index.ok_or(TransferEventError::PendingLogEntry)?;
+    let event_source = EventSource {
+        transaction_hash,
+        log_index,
+        batch_index: None,
+    };
+    Ok((event_source, block_number, block_hash))
+}
+
+impl TryFrom<LogEntry> for TransferEvent {
     type Error = TransferEventError;
 
     fn try_from(entry: LogEntry) -> Result<Self, Self::Error> {
-        let _block_hash = entry
-            .block_hash
-            .ok_or(TransferEventError::PendingLogEntry)?;
-        let block_number = entry
-            .block_number
-            .ok_or(TransferEventError::PendingLogEntry)?;
-        let transaction_hash = entry
-            .transaction_hash
-            .ok_or(TransferEventError::PendingLogEntry)?;
-        let _transaction_index = entry
-            .transaction_index
-            .ok_or(TransferEventError::PendingLogEntry)?;
-        let log_index = entry.log_index.ok_or(TransferEventError::PendingLogEntry)?;
-        let event_source = EventSource {
-            transaction_hash,
-            log_index,
-        };
+        let (event_source, block_number, block_hash) = included_log_entry(&entry)?;
+        let token_id = entry
+            .topics
+            .get(3)
+            .map(|topic| u256::from_be_bytes(topic.0));
 
         if entry.removed {
             return Err(TransferEventError::InvalidEventSource {
                 source: event_source,
+                token_id,
+                block_number,
                 error: EventSourceError::InvalidEvent(
                     "this event has been removed from the chain".to_string(),
                 ),
@@ -180,6 +617,8 @@ impl TryFrom<LogEntry> for MintEvent {
         if entry.topics.len() != 4 {
             return Err(TransferEventError::InvalidEventSource {
                 source: event_source,
+                token_id,
+                block_number,
                 error: EventSourceError::InvalidEvent(format!(
                     "Expected exactly 4 topics, got {}",
                     entry.topics.len()
@@ -189,6 +628,8 @@ impl TryFrom<LogEntry> for MintEvent {
         let from_address = Address::try_from(&entry.topics[1].0).map_err(|err| {
             TransferEventError::InvalidEventSource {
                 source: event_source,
+                token_id,
+                block_number,
                 error: EventSourceError::InvalidEvent(format!(
                     "Invalid address in log entry: {}",
                     err
@@ -198,42 +639,135 @@ impl TryFrom<LogEntry> for MintEvent {
         let to_address = Address::try_from(&entry.topics[2].0).map_err(|err| {
             TransferEventError::InvalidEventSource {
                 source: event_source,
+                token_id,
+                block_number,
                 error: EventSourceError::InvalidEvent(format!(
                     "Invalid address in log entry: {}",
                     err
                 )),
             }
         })?;
-        let token_id = u256::from_be_bytes(entry.topics[3].0);
+        let token_id = token_id.expect("BUG: log entry has 4 topics");
 
-        Ok(MintEvent {
-            transaction_hash,
+        Ok(TransferEvent {
+            transaction_hash: event_source.transaction_hash,
             block_number,
-            log_index,
+            log_index: event_source.log_index,
             from_address,
             to_address,
             token_id,
+            batch_index: None,
+            value: None,
+            collection: None,
+            block_hash: Some(block_hash),
         })
     }
 }
 
-// impl TryFrom<TransferEvent> for MintEvent {
-//     type Error = MintEventError;
+/// Parses an ERC-1155 `TransferSingle` or `TransferBatch` event,
+/// whose topics are the event signature, the operator, the sender and the recipient,
+/// and whose data is the ABI encoding of the token IDs and of the transferred values.
+fn parse_erc1155_transfer_events(
+    entry: LogEntry,
+) -> Result<Vec<TransferEvent>, TransferEventError> {
+    let (event_source, block_number, block_hash) = included_log_entry(&entry)?;
+    let invalid_event = |reason: String| TransferEventError::InvalidEventSource {
+        source: event_source,
+        token_id: None,
+        block_number,
+        error: EventSourceError::InvalidEvent(reason),
+    };
 
-//     fn try_from(transfer_event: TransferEvent) -> Result<Self, Self::Error> {
-//         // check if from_address is the zero address
-//         if transfer_event.from_address != Address::ZERO {
-//             return Err(MintEventError::NoMintEvent);
-//         }
+    if entry.removed {
+        return Err(invalid_event(
+            "this event has been removed from the chain".to_string(),
+        ));
+    }
+    if entry.topics.len() != 4 {
+        return Err(invalid_event(format!(
+            "Expected exactly 4 topics, got {}",
+            entry.topics.len()
+        )));
+    }
+    let from_address = Address::try_from(&entry.topics[2].0)
+        .map_err(|err| invalid_event(format!("Invalid address in log entry: {}", err)))?;
+    let to_address = Address::try_from(&entry.topics[3].0)
+        .map_err(|err| invalid_event(format!("Invalid address in log entry: {}", err)))?;
 
-//         Ok(MintEvent(transfer_event))
-//     }
-// }
+    let transfers: Vec<(Option<u32>, u256, u256)> = match entry.topics[0].0 {
+        TRANSFER_SINGLE_EVENT_TOPIC => {
+            if entry.data.0.len() != 64 {
+                return Err(invalid_event(format!(
+                    "Expected 64 bytes of TransferSingle data, got {}",
+                    entry.data.0.len()
+                )));
+            }
+            let token_id = read_word(&entry.data.0, 0).map_err(invalid_event)?;
+            let value = read_word(&entry.data.0, 32).map_err(invalid_event)?;
+            vec![(None, token_id, value)]
+        }
+        TRANSFER_BATCH_EVENT_TOPIC => {
+            let token_ids = read_uint256_array(&entry.data.0, 0).map_err(invalid_event)?;
+            let values = read_uint256_array(&entry.data.0, 32).map_err(invalid_event)?;
+            if token_ids.len() != values.len() {
+                return Err(invalid_event(format!(
+                    "TransferBatch has {} token IDs but {} values",
+                    token_ids.len(),
+                    values.len()
+                )));
+            }
+            let batch_size = u32::try_from(token_ids.len())
+                .map_err(|_| invalid_event("TransferBatch is too large".to_string()))?;
+            (0..batch_size)
+                .map(Some)
+                .zip(token_ids)
+                .zip(values)
+                .map(|((batch_index, token_id), value)| (batch_index, token_id, value))
+                .collect()
+        }
+        topic => {
+            return Err(invalid_event(format!(
+                "Unexpected event signature 0x{}",
+                hex::encode(topic)
+            )))
+        }
+    };
 
-// impl Deref for MintEvent {
-//     type Target = TransferEvent;
+    Ok(transfers
+        .into_iter()
+        .map(|(batch_index, token_id, value)| TransferEvent {
+            transaction_hash: event_source.transaction_hash,
+            block_number,
+            log_index: event_source.log_index,
+            from_address,
+            to_address,
+            token_id,
+            batch_index,
+            value: Some(value),
+            collection: None,
+            block_hash: Some(block_hash),
+        })
+        .collect())
+}
+
+impl TryFrom<TransferEvent> for MintEvent {
+    type Error = MintEventError;
 
-//     fn deref(&self) -> &Self::Target {
-//         &self.0
-//     }
-// }
+    fn try_from(transfer_event: TransferEvent) -> Result<Self, Self::Error> {
+        if transfer_event.from_address != Address::ZERO {
+            return Err(MintEventError::NoMintEvent);
+        }
+        Ok(MintEvent {
+            transaction_hash: transfer_event.transaction_hash,
+            block_number: transfer_event.block_number,
+            log_index: transfer_event.log_index,
+            from_address: transfer_event.from_address,
+            to_address: transfer_event.to_address,
+            token_id: transfer_event.token_id,
+            batch_index: transfer_event.batch_index,
+            value: transfer_event.value,
+            collection: transfer_event.collection,
+            block_hash: transfer_event.block_hash,
+        })
+    }
+}
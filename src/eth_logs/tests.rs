@@ -1,119 +1,341 @@
-mod parse_principal_from_slice {
-    use crate::eth_logs::parse_principal_from_slice;
-    use crate::eth_rpc::FixedSizeData;
+mod erc1155 {
+    use crate::address::Address;
+    use crate::eth_logs::{
+        parse_transfer_events, TokenStandard, TransferEvent, TransferEventError,
+        TRANSFER_BATCH_EVENT_TOPIC, TRANSFER_SINGLE_EVENT_TOPIC,
+    };
+    use crate::eth_rpc::{Data, FixedSizeData, Hash, LogEntry, Quantity};
+    use crate::numeric::{BlockNumber, LogIndex};
+    use crate::test_fixtures::{self, holder};
     use assert_matches::assert_matches;
-    use candid::Principal;
-    use std::str::FromStr;
+    use ethnum::u256;
+    use ic_crypto_sha3::Keccak256;
 
-    const PRINCIPAL: &str = "2chl6-4hpzw-vqaaa-aaaaa-c";
+    #[test]
+    fn should_have_correct_topics() {
+        assert_eq!(
+            Keccak256::hash("TransferSingle(address,address,address,uint256,uint256)"),
+            TRANSFER_SINGLE_EVENT_TOPIC
+        );
+        assert_eq!(
+            Keccak256::hash("TransferBatch(address,address,address,uint256[],uint256[])"),
+            TRANSFER_BATCH_EVENT_TOPIC
+        );
+    }
 
     #[test]
-    fn should_deserialize_principal() {
-        let principal = Principal::from_str(PRINCIPAL).unwrap();
-        let encoded_principal = to_bytes_with_size_prefix(&principal);
-        let parsed_principal = parse_principal_from_slice(&encoded_principal);
+    fn should_parse_transfer_single() {
+        let data = [word(42), word(7)].concat();
+        let entry = log_entry(TRANSFER_SINGLE_EVENT_TOPIC, data);
 
-        assert_eq!(parsed_principal, Ok(principal));
+        assert_eq!(
+            parse_transfer_events(entry, TokenStandard::Erc1155),
+            Ok(vec![transfer_event(None, 42, 7)])
+        );
     }
 
     #[test]
-    fn should_fail_when_first_byte_is_zero() {
-        let principal = Principal::from_str(PRINCIPAL).unwrap();
-        let mut encoded_principal = to_bytes_with_size_prefix(&principal);
-        encoded_principal.insert(0, 0);
-        let parsed_principal = parse_principal_from_slice(&encoded_principal);
+    fn should_expand_transfer_batch_into_one_transfer_per_id() {
+        let data = [
+            word(64),
+            word(160),
+            word(2),
+            word(42),
+            word(43),
+            word(2),
+            word(1),
+            word(100),
+        ]
+        .concat();
+        let entry = log_entry(TRANSFER_BATCH_EVENT_TOPIC, data);
+
+        let transfers = parse_transfer_events(entry, TokenStandard::Erc1155).unwrap();
 
-        assert_matches!(parsed_principal, Err(_));
+        assert_eq!(
+            transfers,
+            vec![
+                transfer_event(Some(0), 42, 1),
+                transfer_event(Some(1), 43, 100)
+            ]
+        );
+        assert_ne!(transfers[0].source(), transfers[1].source());
     }
 
     #[test]
-    fn should_fail_when_first_byte_larger_than_29() {
-        let principal = Principal::from_str(PRINCIPAL).unwrap();
-        for i in 30..u8::MAX {
-            let mut encoded_principal = to_bytes_with_size_prefix(&principal);
-            encoded_principal.insert(0, i);
-            assert_matches!(parse_principal_from_slice(&encoded_principal), Err(_));
+    fn should_reject_malformed_data() {
+        for (topic, data) in [
+            (TRANSFER_SINGLE_EVENT_TOPIC, word(42).to_vec()),
+            (TRANSFER_BATCH_EVENT_TOPIC, [word(64), word(1024)].concat()),
+            (
+                TRANSFER_BATCH_EVENT_TOPIC,
+                [word(64), word(128), word(1), word(42), word(0)].concat(),
+            ),
+            (
+                TRANSFER_BATCH_EVENT_TOPIC,
+                [word(64), word(64), word(u64::MAX)].concat(),
+            ),
+        ] {
+            assert_matches!(
+                parse_transfer_events(log_entry(topic, data), TokenStandard::Erc1155),
+                Err(TransferEventError::InvalidEventSource { .. })
+            );
         }
     }
 
     #[test]
-    fn should_fail_when_length_shorter_than_value_in_first_byte() {
-        let principal = Principal::from_str(PRINCIPAL).unwrap();
-        let mut encoded_principal = to_bytes_with_size_prefix(&principal);
+    fn should_reject_erc721_transfer() {
+        let entry = log_entry(crate::eth_logs::TRANSFER_EVENT_TOPIC, vec![]);
+
+        assert_matches!(
+            parse_transfer_events(entry, TokenStandard::Erc1155),
+            Err(TransferEventError::InvalidEventSource { .. })
+        );
+    }
 
-        while encoded_principal.pop().is_some() {
-            assert_matches!(parse_principal_from_slice(&encoded_principal), Err(_));
+    fn log_entry(topic: [u8; 32], data: Vec<u8>) -> LogEntry {
+        let recipient = holder();
+        LogEntry {
+            address: "0xb44B5e756A894775FC32EDdf3314Bb1B1944dC34"
+                .parse()
+                .unwrap(),
+            topics: vec![
+                FixedSizeData(topic),
+                recipient.to_fixed_size_data(),
+                Address::ZERO.to_fixed_size_data(),
+                recipient.to_fixed_size_data(),
+            ],
+            data: Data(data),
+            block_number: Some(BlockNumber::new(3960623)),
+            transaction_hash: Some(Hash([0x11; 32])),
+            transaction_index: Some(Quantity::new(6)),
+            block_hash: Some(Hash([0x22; 32])),
+            log_index: Some(LogIndex::from(29_u8)),
+            removed: false,
         }
     }
 
+    fn transfer_event(batch_index: Option<u32>, token_id: u64, value: u64) -> TransferEvent {
+        TransferEvent {
+            transaction_hash: Hash([0x11; 32]),
+            batch_index,
+            value: Some(u256::from(value)),
+            block_hash: Some(Hash([0x22; 32])),
+            ..test_fixtures::transfer_event(29, Address::ZERO, holder(), token_id)
+        }
+    }
+
+    fn word(value: u64) -> [u8; 32] {
+        u256::from(value).to_be_bytes()
+    }
+}
+
+mod verify_mint_receipt {
+    use crate::address::Address;
+    use crate::eth_logs::{
+        verify_mint_receipt, MintEvent, ReceiptVerificationError, TokenStandard,
+        TRANSFER_EVENT_TOPIC,
+    };
+    use crate::eth_rpc::{Data, FixedSizeData, Hash, LogEntry, Quantity};
+    use crate::eth_rpc_client::responses::{TransactionReceipt, TransactionStatus};
+    use crate::numeric::{BlockNumber, GasAmount, LogIndex, WeiPerGas};
+    use crate::test_fixtures::{self, holder, CONTRACT};
+    use assert_matches::assert_matches;
+    use ethnum::u256;
+
     #[test]
-    fn should_fail_when_non_trailing_zeroes() {
-        let principal = Principal::from_str(PRINCIPAL).unwrap();
-        let mut encoded_principal = to_bytes_with_size_prefix(&principal);
-        encoded_principal.append(&mut vec![0, 0, 0, 1, 0]);
+    fn should_accept_event_confirmed_by_receipt() {
+        assert_eq!(verify(&mint_event(), Some(&receipt())), Ok(()));
+    }
 
-        assert_matches!(parse_principal_from_slice(&encoded_principal), Err(_));
+    #[test]
+    fn should_retry_missing_receipt() {
+        assert_eq!(
+            verify(&mint_event(), None),
+            Err(ReceiptVerificationError::MissingReceipt)
+        );
     }
 
     #[test]
-    fn should_fail_when_slice_longer_than_32_bytes() {
-        let mut encoded_principal = [0_u8; 34];
-        encoded_principal[0] = 33;
+    fn should_reject_failed_transaction() {
+        let receipt = TransactionReceipt {
+            status: TransactionStatus::Failure,
+            ..receipt()
+        };
 
-        assert_matches!(parse_principal_from_slice(&encoded_principal), Err(_));
+        assert_matches!(
+            verify(&mint_event(), Some(&receipt)),
+            Err(ReceiptVerificationError::Mismatch(reason)) if reason.contains("failed")
+        );
     }
 
     #[test]
-    fn should_not_accept_management_canister_principal() {
-        let principal = Principal::management_canister();
-        let encoded_principal = to_bytes_with_size_prefix(&principal);
-        let parsed_principal = parse_principal_from_slice(&encoded_principal);
+    fn should_reject_missing_log() {
+        let event = MintEvent {
+            log_index: LogIndex::from(30_u8),
+            ..mint_event()
+        };
 
-        assert_matches!(parsed_principal, Err(err) if err.contains("management canister"));
+        assert_matches!(
+            verify(&event, Some(&receipt())),
+            Err(ReceiptVerificationError::Mismatch(reason)) if reason.contains("no log at index 30")
+        );
     }
 
     #[test]
-    fn should_not_accept_anonymous_principal() {
-        let principal = Principal::anonymous();
-        let encoded_principal = to_bytes_with_size_prefix(&principal);
-        let parsed_principal = parse_principal_from_slice(&encoded_principal);
+    fn should_reject_log_of_other_contract() {
+        let mut receipt = receipt();
+        receipt.logs[0].address = Address::ZERO;
 
-        assert_matches!(parsed_principal, Err(err) if err.contains("anonymous principal"));
+        assert_matches!(
+            verify(&mint_event(), Some(&receipt)),
+            Err(ReceiptVerificationError::Mismatch(reason)) if reason.contains("instead of contract")
+        );
     }
 
     #[test]
-    fn should_encode_to_and_decode_from_eth_hex_string() {
-        let principal = Principal::from_str(PRINCIPAL).unwrap();
-        let encoded_principal = format!(
-            "0x{}",
-            hex::encode(to_32_bytes_with_size_prefix(&principal))
+    fn should_reject_log_with_other_topics() {
+        let event = MintEvent {
+            token_id: u256::from(43_u8),
+            ..mint_event()
+        };
+
+        assert_matches!(
+            verify(&event, Some(&receipt())),
+            Err(ReceiptVerificationError::Mismatch(reason)) if reason.contains("does not match")
         );
+    }
+
+    #[test]
+    fn should_wait_for_reorganization_when_included_in_other_block() {
+        let receipt = TransactionReceipt {
+            block_hash: Hash([0x33; 32]),
+            ..receipt()
+        };
+
         assert_eq!(
-            encoded_principal,
-            "0x09efcdab00000000000100000000000000000000000000000000000000000000"
+            verify(&mint_event(), Some(&receipt)),
+            Err(ReceiptVerificationError::OtherBlock)
         );
+    }
 
-        let decoded_principal = parse_principal_from_slice(
-            FixedSizeData::from_str(&encoded_principal)
-                .unwrap()
-                .as_ref(),
-        );
+    fn verify(
+        event: &MintEvent,
+        receipt: Option<&TransactionReceipt>,
+    ) -> Result<(), ReceiptVerificationError> {
+        verify_mint_receipt(
+            event,
+            CONTRACT.parse().unwrap(),
+            TokenStandard::Erc721,
+            receipt,
+        )
+    }
 
-        assert_eq!(decoded_principal, Ok(principal));
+    fn mint_event() -> MintEvent {
+        MintEvent {
+            transaction_hash: Hash([0x11; 32]),
+            block_hash: Some(Hash([0x22; 32])),
+            ..test_fixtures::mint_event(29, 42)
+        }
     }
 
-    fn to_bytes_with_size_prefix(principal: &Principal) -> Vec<u8> {
-        let mut principal_bytes = principal.as_slice().to_vec();
-        let size = principal_bytes.len() as u8;
-        principal_bytes.insert(0, size);
-        principal_bytes
+    fn receipt() -> TransactionReceipt {
+        let recipient = holder();
+        TransactionReceipt {
+            block_hash: Hash([0x22; 32]),
+            block_number: BlockNumber::new(3960623),
+            effective_gas_price: WeiPerGas::new(0xfefbee3e),
+            gas_used: GasAmount::new(0x5208),
+            status: TransactionStatus::Success,
+            transaction_hash: Hash([0x11; 32]),
+            logs: vec![LogEntry {
+                address: CONTRACT.parse().unwrap(),
+                topics: vec![
+                    FixedSizeData(TRANSFER_EVENT_TOPIC),
+                    Address::ZERO.to_fixed_size_data(),
+                    recipient.to_fixed_size_data(),
+                    FixedSizeData(u256::from(42_u8).to_be_bytes()),
+                ],
+                data: Data(vec![]),
+                block_number: Some(BlockNumber::new(3960623)),
+                transaction_hash: Some(Hash([0x11; 32])),
+                transaction_index: Some(Quantity::new(6)),
+                block_hash: Some(Hash([0x22; 32])),
+                log_index: Some(LogIndex::from(29_u8)),
+                removed: false,
+            }],
+        }
     }
+}
 
-    fn to_32_bytes_with_size_prefix(principal: &Principal) -> [u8; 32] {
-        let mut principal_bytes = [0_u8; 32];
-        for (index, byte) in to_bytes_with_size_prefix(principal).iter().enumerate() {
-            principal_bytes[index] = *byte;
+mod skipped_block_queries {
+    use crate::address::Address;
+    use crate::eth_logs::{
+        matches_topics, skipped_block_queries, transfer_filters, ScrapingMode, SkippedBlockQuery,
+        TokenStandard, TRANSFER_BATCH_EVENT_TOPIC, TRANSFER_EVENT_TOPIC,
+        TRANSFER_SINGLE_EVENT_TOPIC,
+    };
+    use crate::eth_rpc::{FixedSizeData, Topic};
+    use crate::test_fixtures::holder;
+
+    #[test]
+    fn should_read_receipts_of_erc721_skipped_blocks() {
+        for mode in [ScrapingMode::Mints, ScrapingMode::AllTransfers] {
+            let filters = transfer_filters(mode, TokenStandard::Erc721);
+            let queries = skipped_block_queries(mode, TokenStandard::Erc721);
+
+            assert_eq!(queries, vec![SkippedBlockQuery::Receipts(filters.clone())]);
+            for topics in filters {
+                assert!(!queries.contains(&SkippedBlockQuery::Logs(topics)));
+            }
         }
-        principal_bytes
+    }
+
+    #[test]
+    fn should_query_each_erc1155_event_signature_separately() {
+        let queries = skipped_block_queries(ScrapingMode::AllTransfers, TokenStandard::Erc1155);
+
+        assert_eq!(
+            queries,
+            vec![
+                SkippedBlockQuery::Logs(vec![Topic::from(FixedSizeData(
+                    TRANSFER_SINGLE_EVENT_TOPIC
+                ))]),
+                SkippedBlockQuery::Logs(vec![Topic::from(FixedSizeData(
+                    TRANSFER_BATCH_EVENT_TOPIC
+                ))]),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_match_receipt_logs_like_get_logs() {
+        let [mints, burns]: [Vec<Topic>; 2] =
+            transfer_filters(ScrapingMode::Mints, TokenStandard::Erc721)
+                .try_into()
+                .unwrap();
+        let transfer = |from: Address, to: Address| {
+            vec![
+                FixedSizeData(TRANSFER_EVENT_TOPIC),
+                from.to_fixed_size_data(),
+                to.to_fixed_size_data(),
+                FixedSizeData([0; 32]),
+            ]
+        };
+
+        assert!(matches_topics(&mints, &transfer(Address::ZERO, holder())));
+        assert!(!matches_topics(&burns, &transfer(Address::ZERO, holder())));
+        assert!(matches_topics(&burns, &transfer(holder(), Address::ZERO)));
+        assert!(!matches_topics(&mints, &transfer(holder(), holder())));
+        assert!(!matches_topics(
+            &mints,
+            &[FixedSizeData(TRANSFER_EVENT_TOPIC)]
+        ));
+        assert!(matches_topics(
+            &[Topic::OneOf(vec![
+                FixedSizeData(TRANSFER_SINGLE_EVENT_TOPIC),
+                FixedSizeData(TRANSFER_EVENT_TOPIC)
+            ])],
+            &transfer(holder(), holder())
+        ));
     }
 }
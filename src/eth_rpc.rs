@@ -3,13 +3,15 @@
 
 use crate::address::Address;
 use crate::endpoints::CandidBlockTag;
+use crate::eth_rpc_client::responses::TransactionReceipt;
+use crate::eth_rpc_client::transport::RpcTransport;
 use crate::logs::{DEBUG, TRACE_HTTP};
 use crate::numeric::{BlockNumber, LogIndex, TransactionCount, Wei};
 use crate::state::{mutate_state, State};
-use candid::{candid_method, CandidType, Principal};
+use candid::{candid_method, CandidType};
 use ethnum;
 use ic_canister_log::log;
-use ic_cdk::api::call::{call_with_payment128, RejectionCode};
+use ic_cdk::api::call::RejectionCode;
 use ic_cdk::api::management_canister::http_request::{
     CanisterHttpRequestArgument, HttpHeader, HttpMethod, HttpResponse, TransformArgs,
     TransformContext,
@@ -44,6 +46,17 @@ pub fn into_nat(quantity: Quantity) -> candid::Nat {
     candid::Nat::from(BigUint::from_bytes_be(&quantity.to_be_bytes()))
 }
 
+/// Converts a natural number to a quantity, or returns `None` if it does not fit in 256 bits.
+pub fn try_from_nat(nat: &candid::Nat) -> Option<Quantity> {
+    let bytes = nat.0.to_bytes_be();
+    if bytes.len() > 32 {
+        return None;
+    }
+    let mut be_bytes = [0u8; 32];
+    be_bytes[32 - bytes.len()..].copy_from_slice(&bytes);
+    Some(Quantity::from_be_bytes(be_bytes))
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
 #[serde(transparent)]
 pub struct Data(#[serde(with = "crate::serde_data")] pub Vec<u8>);
@@ -153,6 +166,8 @@ impl std::str::FromStr for Hash {
 
 impl HttpResponsePayload for Hash {}
 
+impl HttpResponsePayload for Data {}
+
 /// Block tags.
 /// See <https://ethereum.org/en/developers/docs/apis/json-rpc/#default-block>
 #[derive(Debug, Default, Copy, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -171,22 +186,71 @@ pub enum BlockTag {
     Finalized,
 }
 
-impl From<CandidBlockTag> for BlockTag {
-    fn from(block_tag: CandidBlockTag) -> BlockTag {
+impl Display for BlockTag {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Latest => write!(f, "latest"),
+            Self::Safe => write!(f, "safe"),
+            Self::Finalized => write!(f, "finalized"),
+        }
+    }
+}
+
+/// The height of the blocks observed by the minter, up to which it scrapes the logs.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum BlockHeight {
+    /// The block with the given tag.
+    Tag(BlockTag),
+    /// The block with the given number of confirmations, i.e., the given number of blocks below the latest block.
+    LatestMinusConfirmations(u64),
+}
+
+impl Default for BlockHeight {
+    fn default() -> Self {
+        Self::Tag(BlockTag::default())
+    }
+}
+
+impl BlockHeight {
+    /// The tag of the block to query in order to determine the height.
+    pub fn block_tag(&self) -> BlockTag {
+        match self {
+            Self::Tag(tag) => *tag,
+            Self::LatestMinusConfirmations(_) => BlockTag::Latest,
+        }
+    }
+
+    /// Returns the number of the block at this height, given the block with the tag [`Self::block_tag`].
+    pub fn block_number(&self, tagged_block_number: BlockNumber) -> BlockNumber {
+        match self {
+            Self::Tag(_) => tagged_block_number,
+            Self::LatestMinusConfirmations(confirmations) => tagged_block_number
+                .checked_sub(BlockNumber::from(*confirmations))
+                .unwrap_or(BlockNumber::ZERO),
+        }
+    }
+}
+
+impl From<CandidBlockTag> for BlockHeight {
+    fn from(block_tag: CandidBlockTag) -> BlockHeight {
         match block_tag {
-            CandidBlockTag::Latest => BlockTag::Latest,
-            CandidBlockTag::Safe => BlockTag::Safe,
-            CandidBlockTag::Finalized => BlockTag::Finalized,
+            CandidBlockTag::Latest => BlockHeight::Tag(BlockTag::Latest),
+            CandidBlockTag::Safe => BlockHeight::Tag(BlockTag::Safe),
+            CandidBlockTag::Finalized => BlockHeight::Tag(BlockTag::Finalized),
+            CandidBlockTag::LatestMinusConfirmations(confirmations) => {
+                BlockHeight::LatestMinusConfirmations(confirmations)
+            }
         }
     }
 }
 
-impl Display for BlockTag {
+impl Display for BlockHeight {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         match self {
-            Self::Latest => write!(f, "latest"),
-            Self::Safe => write!(f, "safe"),
-            Self::Finalized => write!(f, "finalized"),
+            Self::Tag(tag) => write!(f, "{tag}"),
+            Self::LatestMinusConfirmations(confirmations) => {
+                write!(f, "latest - {confirmations}")
+            }
         }
     }
 }
@@ -225,6 +289,24 @@ impl std::str::FromStr for BlockSpec {
     }
 }
 
+/// A topic filter of the [`eth_getLogs`](https://ethereum.org/en/developers/docs/apis/json-rpc/#eth_getlogs) call.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(untagged)]
+pub enum Topic {
+    /// Matches any topic, serialized as `null`.
+    Any,
+    /// Matches exactly the given topic.
+    Exact(FixedSizeData),
+    /// Matches any of the given topics.
+    OneOf(Vec<FixedSizeData>),
+}
+
+impl From<FixedSizeData> for Topic {
+    fn from(topic: FixedSizeData) -> Self {
+        Topic::Exact(topic)
+    }
+}
+
 /// Parameters of the [`eth_getLogs`](https://ethereum.org/en/developers/docs/apis/json-rpc/#eth_getlogs) call.
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -237,9 +319,9 @@ pub struct GetLogsParam {
     pub address: Vec<Address>,
     /// Array of 32 Bytes DATA topics.
     /// Topics are order-dependent.
-    /// Each topic can also be an array of DATA with "or" options.
+    /// Each topic can also be an array of DATA with "or" options, or `null` to match any topic.
     #[serde(skip_serializing_if = "Vec::is_empty")]
-    pub topics: Vec<FixedSizeData>,
+    pub topics: Vec<Topic>,
 }
 
 /// An entry of the [`eth_getLogs`](https://ethereum.org/en/developers/docs/apis/json-rpc/#eth_getlogs) call reply.
@@ -326,6 +408,8 @@ pub struct Block {
     pub number: BlockNumber,
     /// Base fee value of this block
     pub base_fee_per_gas: Wei,
+    /// The hash of this block, which commits to all its ancestors.
+    pub hash: Hash,
 }
 
 impl HttpResponsePayload for Block {
@@ -334,6 +418,22 @@ impl HttpResponsePayload for Block {
     }
 }
 
+/// A block together with the hashes of its transactions.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockTransactions {
+    pub number: BlockNumber,
+    pub hash: Hash,
+    /// The hashes of the transactions included in the block, in execution order.
+    pub transactions: Vec<Hash>,
+}
+
+impl HttpResponsePayload for BlockTransactions {
+    fn response_transform() -> Option<ResponseTransform> {
+        Some(ResponseTransform::BlockTransactions)
+    }
+}
+
 /// An envelope for all JSON-RPC requests.
 #[derive(Clone, Serialize, Deserialize)]
 pub struct JsonRpcRequest<T> {
@@ -369,6 +469,10 @@ pub enum ResponseTransform {
     Block,
     #[n(1)]
     LogEntries,
+    #[n(2)]
+    TransactionReceipt,
+    #[n(3)]
+    BlockTransactions,
 }
 
 impl ResponseTransform {
@@ -407,6 +511,9 @@ impl ResponseTransform {
         match self {
             Self::Block => redact_response::<Block>(body_bytes),
             Self::LogEntries => redact_collection_response::<LogEntry>(body_bytes),
+            // Unknown transactions have no receipt.
+            Self::TransactionReceipt => redact_response::<Option<TransactionReceipt>>(body_bytes),
+            Self::BlockTransactions => redact_response::<BlockTransactions>(body_bytes),
         }
     }
 }
@@ -450,6 +557,8 @@ pub enum HttpOutcallError {
         body: String,
         parsing_error: Option<String>,
     },
+    /// The provider was not called because it is unhealthy.
+    UnhealthyProvider,
 }
 
 impl HttpOutcallError {
@@ -511,16 +620,25 @@ pub trait HttpResponsePayload {
     }
 }
 
-impl<T: HttpResponsePayload> HttpResponsePayload for Option<T> {}
+impl<T: HttpResponsePayload> HttpResponsePayload for Option<T> {
+    fn response_transform() -> Option<ResponseTransform> {
+        T::response_transform()
+    }
+}
 
 impl HttpResponsePayload for TransactionCount {}
 
-/// Calls a JSON-RPC method on an Ethereum node at the specified URL.
+/// Calls a JSON-RPC method on an Ethereum node at the specified URL, with the given additional headers,
+/// by sending the request with the given transport.
+/// The response size estimate is never adjusted beyond `max_response_size`.
 pub async fn call<I, O>(
+    transport: &impl RpcTransport,
     url: impl Into<String>,
+    headers: Vec<HttpHeader>,
     method: impl Into<String>,
     params: I,
-    mut response_size_estimate: ResponseSizeEstimate,
+    response_size_estimate: ResponseSizeEstimate,
+    max_response_size: u64,
 ) -> HttpOutcallResult<JsonRpcResult<O>>
 where
     I: Serialize,
@@ -534,6 +652,8 @@ where
         id: 1,
     };
     let url = url.into();
+    let max_response_size = ResponseSizeEstimate::new(max_response_size);
+    let mut response_size_estimate = response_size_estimate.min(max_response_size);
     let mut retries = 0;
 
     loop {
@@ -559,10 +679,12 @@ where
             url: url.clone(),
             max_response_bytes: Some(effective_size_estimate),
             method: HttpMethod::POST,
-            headers: vec![HttpHeader {
+            headers: std::iter::once(HttpHeader {
                 name: "Content-Type".to_string(),
                 value: "application/json".to_string(),
-            }],
+            })
+            .chain(headers.iter().cloned())
+            .collect(),
             body: Some(payload.as_bytes().to_vec()),
             transform: Some(TransformContext::from_name(
                 "cleanup_response".to_owned(),
@@ -578,17 +700,10 @@ where
         const SUBNET_SIZE: u128 = 34;
         let cycles = base_cycles * SUBNET_SIZE / BASE_SUBNET_SIZE;
 
-        let response: HttpResponse = match call_with_payment128(
-            Principal::management_canister(),
-            "http_request",
-            (request,),
-            cycles,
-        )
-        .await
-        {
-            Ok((response,)) => response,
+        let response: HttpResponse = match transport.http_request(request, cycles).await {
+            Ok(response) => response,
             Err((code, message)) if is_response_too_large(&code, &message) => {
-                let new_estimate = response_size_estimate.adjust();
+                let new_estimate = response_size_estimate.adjust().min(max_response_size);
                 if response_size_estimate == new_estimate {
                     return Err(HttpOutcallError::IcError { code, message });
                 }
@@ -697,6 +812,10 @@ pub(super) mod metrics {
     pub struct HttpMetrics {
         /// Retry counts histograms indexed by the ETH RCP method name.
         retry_histogram_per_method: BTreeMap<String, RetryHistogram>,
+        /// The number of results that differed from the consensus, by method and provider URL.
+        provider_disagreements: BTreeMap<(String, String), u64>,
+        /// The number of parallel calls without consensus among the providers, by method.
+        inconsistent_results_per_method: BTreeMap<String, u64>,
     }
 
     impl HttpMetrics {
@@ -707,6 +826,20 @@ pub(super) mod metrics {
                 .observe_retry_count(count);
         }
 
+        pub fn observe_provider_disagreement(&mut self, method: String, provider: String) {
+            *self
+                .provider_disagreements
+                .entry((method, provider))
+                .or_default() += 1;
+        }
+
+        pub fn observe_inconsistent_results(&mut self, method: String) {
+            *self
+                .inconsistent_results_per_method
+                .entry(method)
+                .or_default() += 1;
+        }
+
         #[cfg(test)]
         pub fn count_retries_in_bucket(&self, method: &str, count: usize) -> u64 {
             match self.retry_histogram_per_method.get(method) {
@@ -719,6 +852,30 @@ pub(super) mod metrics {
             &self,
             encoder: &mut MetricsEncoder<W>,
         ) -> std::io::Result<()> {
+            if !self.provider_disagreements.is_empty() {
+                let mut counter_vec = encoder.counter_vec(
+                    "cketh_eth_rpc_provider_disagreements",
+                    "The number of results that differed from the consensus, by method and provider.",
+                )?;
+                for ((method, provider), count) in &self.provider_disagreements {
+                    counter_vec = counter_vec.value(
+                        &[("method", method.as_str()), ("provider", provider.as_str())],
+                        *count as f64,
+                    )?;
+                }
+            }
+
+            if !self.inconsistent_results_per_method.is_empty() {
+                let mut counter_vec = encoder.counter_vec(
+                    "cketh_eth_rpc_inconsistent_results",
+                    "The number of parallel calls without consensus among the providers, by method.",
+                )?;
+                for (method, count) in &self.inconsistent_results_per_method {
+                    counter_vec =
+                        counter_vec.value(&[("method", method.as_str())], *count as f64)?;
+                }
+            }
+
             if self.retry_histogram_per_method.is_empty() {
                 return Ok(());
             }
@@ -749,6 +906,24 @@ pub(super) mod metrics {
         METRICS.with(|metrics| metrics.borrow_mut().observe_retry_count(method, count));
     }
 
+    /// Record that the provider returned a result differing from the consensus.
+    pub fn observe_provider_disagreement(method: &str, provider: &str) {
+        METRICS.with(|metrics| {
+            metrics
+                .borrow_mut()
+                .observe_provider_disagreement(method.to_string(), provider.to_string())
+        });
+    }
+
+    /// Record that the providers did not reach consensus.
+    pub fn observe_inconsistent_results(method: &str) {
+        METRICS.with(|metrics| {
+            metrics
+                .borrow_mut()
+                .observe_inconsistent_results(method.to_string())
+        });
+    }
+
     /// Encodes the metrics related to ETH RPC method calls.
     pub fn encode<W: std::io::Write>(encoder: &mut MetricsEncoder<W>) -> std::io::Result<()> {
         METRICS.with(|metrics| metrics.borrow().encode(encoder))
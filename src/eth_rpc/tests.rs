@@ -17,102 +17,6 @@ fn check_response_normalization<O: HttpResponsePayload>(left: &str, right: &str)
     assert_eq!(left_string, right_string);
 }
 
-#[test]
-fn fee_history_normalization() {
-    check_response_normalization::<FeeHistory>(
-        r#"{
-        "baseFeePerGas": [
-            "0x729d3f3b3",
-            "0x766e503ea",
-            "0x75b51b620",
-            "0x74094f2b4",
-            "0x716724f03",
-            "0x73b467f76"
-        ],
-        "gasUsedRatio": [
-            0.6332004,
-            0.47556506666666665,
-            0.4432122666666667,
-            0.4092196,
-            0.5811903
-        ],
-        "oldestBlock": "0x10f73fc",
-        "reward": [
-            [
-                "0x5f5e100",
-                "0x5f5e100",
-                "0x68e7780"
-            ],
-            [
-                "0x55d4a80",
-                "0x5f5e100",
-                "0x5f5e100"
-            ],
-            [
-                "0x5f5e100",
-                "0x5f5e100",
-                "0x5f5e100"
-            ],
-            [
-                "0x5f5e100",
-                "0x5f5e100",
-                "0x5f5e100"
-            ],
-            [
-                "0x5f5e100",
-                "0x5f5e100",
-                "0x180789e0"
-            ]
-        ]
-    }"#,
-        r#"{
-        "gasUsedRatio": [
-            0.6332004,
-            0.47556506666666665,
-            0.4432122666666667,
-            0.4092196,
-            0.5811903
-        ],
-        "baseFeePerGas": [
-            "0x729d3f3b3",
-            "0x766e503ea",
-            "0x75b51b620",
-            "0x74094f2b4",
-            "0x716724f03",
-            "0x73b467f76"
-        ],
-        "reward": [
-            [
-                "0x5f5e100",
-                "0x5f5e100",
-                "0x68e7780"
-            ],
-            [
-                "0x55d4a80",
-                "0x5f5e100",
-                "0x5f5e100"
-            ],
-            [
-                "0x5f5e100",
-                "0x5f5e100",
-                "0x5f5e100"
-            ],
-            [
-                "0x5f5e100",
-                "0x5f5e100",
-                "0x5f5e100"
-            ],
-            [
-                "0x5f5e100",
-                "0x5f5e100",
-                "0x180789e0"
-            ]
-        ],
-        "oldestBlock": "0x10f73fc"
-    }"#,
-    )
-}
-
 #[test]
 fn block_normalization() {
     check_response_normalization::<Block>(
@@ -500,3 +404,84 @@ cketh_eth_rpc_call_retry_count_count{method="eth_test2"} 4 12346789
         .trim()
     );
 }
+
+#[test]
+fn should_convert_quantity_to_and_from_nat() {
+    for quantity in [Quantity::ZERO, Quantity::from(42_u8), Quantity::MAX] {
+        assert_eq!(try_from_nat(&into_nat(quantity)), Some(quantity));
+    }
+    let too_large = into_nat(Quantity::MAX) + candid::Nat::from(1_u8);
+    assert_eq!(try_from_nat(&too_large), None);
+}
+
+#[test]
+fn should_serialize_topic_filters() {
+    let topic = FixedSizeData([0x11; 32]);
+    let param = GetLogsParam {
+        from_block: BlockSpec::Number(BlockNumber::new(0x3ca487)),
+        to_block: BlockSpec::Tag(BlockTag::Finalized),
+        address: vec![],
+        topics: vec![
+            Topic::from(topic.clone()),
+            Topic::Any,
+            Topic::OneOf(vec![topic.clone(), topic]),
+        ],
+    };
+    let hex = format!("0x{}", "11".repeat(32));
+
+    assert_eq!(
+        serde_json::to_value(param).unwrap(),
+        serde_json::json!({
+            "fromBlock": "0x3ca487",
+            "toBlock": "finalized",
+            "address": [],
+            "topics": [hex, null, [hex, hex]],
+        })
+    );
+}
+
+#[test]
+fn should_compute_block_number_at_confirmation_depth() {
+    let latest = BlockNumber::new(100);
+
+    assert_eq!(
+        BlockHeight::Tag(BlockTag::Finalized).block_number(latest),
+        latest
+    );
+    assert_eq!(
+        BlockHeight::LatestMinusConfirmations(12).block_number(latest),
+        BlockNumber::new(88)
+    );
+    assert_eq!(
+        BlockHeight::LatestMinusConfirmations(101).block_number(latest),
+        BlockNumber::ZERO
+    );
+    assert_eq!(
+        BlockHeight::LatestMinusConfirmations(12).block_tag(),
+        BlockTag::Latest
+    );
+}
+
+#[test]
+fn should_decode_block_tags_encoded_before_confirmation_depth() {
+    use crate::endpoints::CandidBlockTag;
+
+    for (index, block_tag) in [
+        CandidBlockTag::Latest,
+        CandidBlockTag::Safe,
+        CandidBlockTag::Finalized,
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        let mut buf = vec![];
+        minicbor::encode(index as u32, &mut buf).unwrap();
+        assert_eq!(minicbor::decode::<CandidBlockTag>(&buf).unwrap(), block_tag);
+    }
+
+    let block_tag = CandidBlockTag::LatestMinusConfirmations(12);
+    let mut buf = vec![];
+    minicbor::encode(&block_tag, &mut buf).unwrap();
+    assert_eq!(minicbor::decode::<CandidBlockTag>(&buf).unwrap(), block_tag);
+    assert_eq!(BlockHeight::from(block_tag).to_string(), "latest - 12");
+}
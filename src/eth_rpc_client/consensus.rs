@@ -0,0 +1,83 @@
+use candid::{CandidType, Deserialize};
+use minicbor::{Decode, Encode};
+
+/// How the results returned by the providers to the same query are reduced to a single result.
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, Default, Encode, Decode, PartialEq, Eq)]
+pub enum ConsensusStrategy {
+    /// All providers must return the same result.
+    #[n(0)]
+    #[default]
+    Equality,
+    /// A strict majority of the providers must return the same result,
+    /// and none of the providers may fail.
+    #[n(1)]
+    StrictMajority,
+    /// At least `min` providers must return the same result,
+    /// while the other providers may fail or return other results.
+    /// If several results are returned by at least `min` providers, no result is selected.
+    #[n(2)]
+    Threshold {
+        #[n(0)]
+        min: u8,
+    },
+}
+
+/// The consensus strategy of each JSON-RPC method queried from all providers.
+/// Methods without strategy expect all providers to return the same result.
+#[derive(CandidType, Deserialize, Clone, Debug, Default, Encode, Decode, PartialEq, Eq)]
+pub struct ConsensusConfig {
+    #[n(0)]
+    pub eth_get_logs: Option<ConsensusStrategy>,
+    #[n(1)]
+    pub eth_get_block_by_number: Option<ConsensusStrategy>,
+    #[n(2)]
+    pub eth_call: Option<ConsensusStrategy>,
+    #[n(3)]
+    pub eth_get_transaction_receipt: Option<ConsensusStrategy>,
+}
+
+impl ConsensusConfig {
+    /// Updates the strategies of the methods set in the other configuration.
+    pub fn update(&mut self, other: ConsensusConfig) {
+        let ConsensusConfig {
+            eth_get_logs,
+            eth_get_block_by_number,
+            eth_call,
+            eth_get_transaction_receipt,
+        } = other;
+        if eth_get_logs.is_some() {
+            self.eth_get_logs = eth_get_logs;
+        }
+        if eth_get_block_by_number.is_some() {
+            self.eth_get_block_by_number = eth_get_block_by_number;
+        }
+        if eth_call.is_some() {
+            self.eth_call = eth_call;
+        }
+        if eth_get_transaction_receipt.is_some() {
+            self.eth_get_transaction_receipt = eth_get_transaction_receipt;
+        }
+    }
+
+    /// Checks that the strategies can be satisfied by the given number of providers.
+    pub fn validate(&self, num_providers: usize) -> Result<(), String> {
+        for (method, strategy) in [
+            ("eth_getLogs", self.eth_get_logs),
+            ("eth_getBlockByNumber", self.eth_get_block_by_number),
+            ("eth_call", self.eth_call),
+            (
+                "eth_getTransactionReceipt",
+                self.eth_get_transaction_receipt,
+            ),
+        ] {
+            if let Some(ConsensusStrategy::Threshold { min }) = strategy {
+                if min == 0 || min as usize > num_providers {
+                    return Err(format!(
+                        "threshold of {method} must be between 1 and the number of providers ({num_providers})"
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,124 @@
+//! Typed reads of the state of the NFT contracts.
+
+use crate::abi::{self, AbiType, AbiValue};
+use crate::address::Address;
+use crate::eth_rpc::{BlockSpec, Data};
+use crate::eth_rpc_client::{EthRpcClient, MultiCallError};
+use ethnum::u256;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ContractCallError {
+    /// The call failed, e.g. because it reverted, or the providers did not agree on its output.
+    Rpc(MultiCallError<Data>),
+    /// The output of the call is not the ABI encoding of the expected type.
+    InvalidOutput(String),
+}
+
+impl EthRpcClient {
+    /// Returns the owner of the given ERC-721 token, i.e., `ownerOf(uint256)`.
+    pub async fn owner_of(
+        &self,
+        contract: Address,
+        token_id: u256,
+        block: BlockSpec,
+    ) -> Result<Address, ContractCallError> {
+        match self
+            .call_function(
+                contract,
+                "ownerOf(uint256)",
+                &[AbiValue::Uint256(token_id)],
+                AbiType::Address,
+                block,
+            )
+            .await?
+        {
+            AbiValue::Address(owner) => Ok(owner),
+            value => unreachable!("BUG: decoded {value:?} instead of an address"),
+        }
+    }
+
+    /// Returns the metadata URI of the given ERC-721 token, i.e., `tokenURI(uint256)`.
+    pub async fn token_uri(
+        &self,
+        contract: Address,
+        token_id: u256,
+        block: BlockSpec,
+    ) -> Result<String, ContractCallError> {
+        self.call_string_function(
+            contract,
+            "tokenURI(uint256)",
+            &[AbiValue::Uint256(token_id)],
+            block,
+        )
+        .await
+    }
+
+    /// Returns the number of tokens in circulation, i.e., `totalSupply()` of ERC-721 enumerable contracts.
+    pub async fn total_supply(
+        &self,
+        contract: Address,
+        block: BlockSpec,
+    ) -> Result<u256, ContractCallError> {
+        match self
+            .call_function(contract, "totalSupply()", &[], AbiType::Uint256, block)
+            .await?
+        {
+            AbiValue::Uint256(supply) => Ok(supply),
+            value => unreachable!("BUG: decoded {value:?} instead of a uint256"),
+        }
+    }
+
+    /// Returns the name of the collection, i.e., `name()`.
+    pub async fn name(
+        &self,
+        contract: Address,
+        block: BlockSpec,
+    ) -> Result<String, ContractCallError> {
+        self.call_string_function(contract, "name()", &[], block)
+            .await
+    }
+
+    /// Returns the symbol of the collection, i.e., `symbol()`.
+    pub async fn symbol(
+        &self,
+        contract: Address,
+        block: BlockSpec,
+    ) -> Result<String, ContractCallError> {
+        self.call_string_function(contract, "symbol()", &[], block)
+            .await
+    }
+
+    async fn call_string_function(
+        &self,
+        contract: Address,
+        signature: &str,
+        args: &[AbiValue],
+        block: BlockSpec,
+    ) -> Result<String, ContractCallError> {
+        match self
+            .call_function(contract, signature, args, AbiType::String, block)
+            .await?
+        {
+            AbiValue::String(string) => Ok(string),
+            value => unreachable!("BUG: decoded {value:?} instead of a string"),
+        }
+    }
+
+    /// Calls the function with the given canonical signature, and decodes its single output of the given type.
+    async fn call_function(
+        &self,
+        contract: Address,
+        signature: &str,
+        args: &[AbiValue],
+        output_type: AbiType,
+        block: BlockSpec,
+    ) -> Result<AbiValue, ContractCallError> {
+        let output = self
+            .eth_call(contract, abi::encode_call(signature, args), block)
+            .await
+            .map_err(ContractCallError::Rpc)?;
+        let mut values = abi::decode(&[output_type], &output.0)
+            .map_err(|e| ContractCallError::InvalidOutput(format!("output of {signature}: {e}")))?;
+        Ok(values.remove(0))
+    }
+}
@@ -0,0 +1,181 @@
+//! Health of the JSON-RPC providers.
+//!
+//! The health of each provider is tracked over its most recent calls, and is reset by upgrades.
+//! A provider failing [`MAX_CONSECUTIVE_FAILURES`] calls in a row is unhealthy: during [`UNHEALTHY_PROVIDER_COOLDOWN`],
+//! it is not called by parallel calls, unless all providers are unhealthy, and it is queried last by sequential calls.
+//! The results of parallel calls are then reduced among the called providers,
+//! except for thresholds, which must still be reached among all providers.
+//! It is probed again once the cooldown elapsed, and stays unhealthy for another cooldown if the probe fails.
+//! Responses exceeding the expected size and JSON-RPC errors answering the request, e.g., reverted calls,
+//! are not failures of the provider.
+
+use crate::eth_rpc::{HttpOutcallResult, JsonRpcResult};
+use ic_metrics_encoder::MetricsEncoder;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, VecDeque};
+use std::time::Duration;
+
+/// The number of consecutive failures after which a provider is unhealthy.
+pub const MAX_CONSECUTIVE_FAILURES: u32 = 3;
+
+/// The duration during which an unhealthy provider is avoided.
+pub const UNHEALTHY_PROVIDER_COOLDOWN: Duration = Duration::from_secs(10 * 60);
+
+/// The number of most recent calls over which the success rate and the latency are computed.
+const HEALTH_WINDOW: usize = 20;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProviderHealth {
+    /// The outcome and the latency in nanoseconds of the most recent calls, oldest first.
+    recent_calls: VecDeque<(bool, u64)>,
+    pub consecutive_failures: u32,
+    /// The time in nanoseconds since the epoch until which the provider is avoided.
+    pub unhealthy_until: Option<u64>,
+}
+
+impl ProviderHealth {
+    fn observe(&mut self, success: bool, latency_nanos: u64, now: u64) {
+        if self.recent_calls.len() == HEALTH_WINDOW {
+            self.recent_calls.pop_front();
+        }
+        self.recent_calls.push_back((success, latency_nanos));
+        if success {
+            self.consecutive_failures = 0;
+            self.unhealthy_until = None;
+        } else {
+            self.consecutive_failures += 1;
+            if self.consecutive_failures >= MAX_CONSECUTIVE_FAILURES {
+                self.unhealthy_until =
+                    Some(now.saturating_add(UNHEALTHY_PROVIDER_COOLDOWN.as_nanos() as u64));
+            }
+        }
+    }
+
+    /// Whether the provider should be queried at the given time.
+    pub fn is_available(&self, now: u64) -> bool {
+        !matches!(self.unhealthy_until, Some(until) if until > now)
+    }
+
+    /// The ratio of successful calls among the most recent calls.
+    pub fn success_rate(&self) -> Option<f64> {
+        if self.recent_calls.is_empty() {
+            return None;
+        }
+        let successes = self
+            .recent_calls
+            .iter()
+            .filter(|(success, _)| *success)
+            .count();
+        Some(successes as f64 / self.recent_calls.len() as f64)
+    }
+
+    /// The average latency of the most recent calls.
+    pub fn average_latency(&self) -> Option<Duration> {
+        if self.recent_calls.is_empty() {
+            return None;
+        }
+        let total: u64 = self.recent_calls.iter().map(|(_, latency)| latency).sum();
+        Some(Duration::from_nanos(total / self.recent_calls.len() as u64))
+    }
+}
+
+thread_local! {
+    /// The health of the providers, by URL.
+    static HEALTH: RefCell<BTreeMap<String, ProviderHealth>> = RefCell::default();
+}
+
+/// Returns the health of the provider with the given URL.
+pub fn provider_health(url: &str) -> ProviderHealth {
+    HEALTH.with(|health| health.borrow().get(url).cloned().unwrap_or_default())
+}
+
+/// Records the outcome of a call to the provider with the given URL, which started at `start` and ended at `end`.
+pub(crate) fn observe_call<T>(
+    url: &str,
+    result: &HttpOutcallResult<JsonRpcResult<T>>,
+    start: u64,
+    end: u64,
+) {
+    let success = match result {
+        Ok(JsonRpcResult::Result(_)) => true,
+        Ok(JsonRpcResult::Error { code, message }) => !is_provider_error(*code, message),
+        Err(error) if error.is_response_too_large() => return,
+        Err(_) => false,
+    };
+    HEALTH.with(|health| {
+        health
+            .borrow_mut()
+            .entry(url.to_string())
+            .or_default()
+            .observe(success, end.saturating_sub(start), end)
+    });
+}
+
+/// Whether the JSON-RPC error is a failure of the provider, e.g., a rate limit or an internal error,
+/// rather than a valid answer of a healthy node, e.g., a reverted call or a too large block range.
+fn is_provider_error(code: i64, message: &str) -> bool {
+    const INTERNAL_ERROR: i64 = -32603;
+    const LIMIT_EXCEEDED: i64 = -32005;
+
+    let message = message.to_lowercase();
+    code == INTERNAL_ERROR
+        || code == LIMIT_EXCEEDED
+        || message.contains("rate limit")
+        || message.contains("too many requests")
+}
+
+/// Encodes the health of the providers that were called since the last upgrade.
+pub fn encode_metrics<W: std::io::Write>(encoder: &mut MetricsEncoder<W>) -> std::io::Result<()> {
+    HEALTH.with(|health| {
+        let health = health.borrow();
+        if health.is_empty() {
+            return Ok(());
+        }
+        let now = ic_cdk::api::time();
+
+        let mut gauge = encoder.gauge_vec(
+            "cketh_eth_rpc_provider_success_rate",
+            "The ratio of successful calls among the most recent calls, by provider.",
+        )?;
+        for (url, provider) in health.iter() {
+            gauge = gauge.value(
+                &[("provider", url.as_str())],
+                provider.success_rate().unwrap_or_default(),
+            )?;
+        }
+
+        let mut gauge = encoder.gauge_vec(
+            "cketh_eth_rpc_provider_latency_seconds",
+            "The average latency of the most recent calls, by provider.",
+        )?;
+        for (url, provider) in health.iter() {
+            gauge = gauge.value(
+                &[("provider", url.as_str())],
+                provider.average_latency().unwrap_or_default().as_secs_f64(),
+            )?;
+        }
+
+        let mut gauge = encoder.gauge_vec(
+            "cketh_eth_rpc_provider_consecutive_failures",
+            "The number of consecutive failed calls, by provider.",
+        )?;
+        for (url, provider) in health.iter() {
+            gauge = gauge.value(
+                &[("provider", url.as_str())],
+                provider.consecutive_failures as f64,
+            )?;
+        }
+
+        let mut gauge = encoder.gauge_vec(
+            "cketh_eth_rpc_provider_available",
+            "Whether the provider is queried (1) or avoided until its cooldown elapsed (0).",
+        )?;
+        for (url, provider) in health.iter() {
+            gauge = gauge.value(
+                &[("provider", url.as_str())],
+                if provider.is_available(now) { 1.0 } else { 0.0 },
+            )?;
+        }
+        Ok(())
+    })
+}
@@ -1,22 +1,35 @@
+use crate::address::Address;
 use crate::eth_rpc::{
-    self, are_errors_consistent, Block, BlockSpec, GetLogsParam, Hash, HttpOutcallError,
-    HttpOutcallResult, HttpResponsePayload, JsonRpcResult, LogEntry, ResponseSizeEstimate,
+    self, are_errors_consistent, Block, BlockSpec, BlockTransactions, Data, GetLogsParam, Hash,
+    HttpOutcallError, HttpOutcallResult, HttpResponsePayload, JsonRpcResult, LogEntry,
+    ResponseSizeEstimate,
+};
+use crate::eth_rpc_client::consensus::{ConsensusConfig, ConsensusStrategy};
+use crate::eth_rpc_client::providers::{
+    RpcNodeProvider, RpcProvider, MAINNET_PROVIDERS, SEPOLIA_PROVIDERS,
 };
-use crate::eth_rpc_client::providers::{RpcNodeProvider, MAINNET_PROVIDERS, SEPOLIA_PROVIDERS};
 
+use crate::eth_rpc_client::requests::{EthCallParams, TransactionRequest};
 use crate::eth_rpc_client::responses::TransactionReceipt;
+use crate::eth_rpc_client::transport::RpcTransportConfig;
 use crate::lifecycle::EthereumNetwork;
 use crate::logs::{DEBUG, INFO};
+use crate::numeric::BlockNumber;
 
 use crate::state::State;
 use ic_canister_log::log;
+use ic_cdk::api::management_canister::http_request::HttpHeader;
 use serde::{de::DeserializeOwned, Serialize};
 use std::collections::BTreeMap;
 use std::fmt::Debug;
 
-mod providers;
+pub mod consensus;
+pub mod contract;
+pub mod health;
+pub mod providers;
 pub mod requests;
 pub mod responses;
+pub mod transport;
 
 #[cfg(test)]
 mod tests;
@@ -24,21 +37,90 @@ mod tests;
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct EthRpcClient {
     chain: EthereumNetwork,
+    /// The providers configured by an upgrade, replacing the default providers of the chain if not empty.
+    rpc_providers: Vec<RpcProvider>,
+    consensus_config: ConsensusConfig,
+    transport: RpcTransportConfig,
 }
 
 impl EthRpcClient {
-    const fn new(chain: EthereumNetwork) -> Self {
-        Self { chain }
+    fn new(chain: EthereumNetwork, rpc_providers: Vec<RpcProvider>) -> Self {
+        Self {
+            chain,
+            rpc_providers,
+            consensus_config: ConsensusConfig::default(),
+            transport: RpcTransportConfig::default(),
+        }
     }
 
-    pub const fn from_state(state: &State) -> Self {
-        Self::new(state.ethereum_network())
+    pub fn from_state(state: &State) -> Self {
+        Self {
+            consensus_config: state.consensus_config.clone(),
+            transport: state.rpc_transport.clone(),
+            ..Self::new(
+                state.ethereum_network().clone(),
+                state.rpc_providers.clone(),
+            )
+        }
     }
 
-    fn providers(&self) -> &[RpcNodeProvider] {
-        match self.chain {
-            EthereumNetwork::Mainnet => &MAINNET_PROVIDERS,
-            EthereumNetwork::Sepolia => &SEPOLIA_PROVIDERS,
+    /// The number of providers queried by parallel calls.
+    pub fn provider_count(&self) -> usize {
+        self.providers().len()
+    }
+
+    /// The maximum number of blocks queried by a single `eth_getLogs` request,
+    /// which is accepted by the network and by every provider.
+    pub fn max_block_spread(&self) -> u64 {
+        self.providers()
+            .iter()
+            .filter_map(RpcNodeProvider::max_block_spread)
+            .fold(self.chain.max_block_spread(), u64::min)
+    }
+
+    /// The URLs of the providers, in the order in which they are queried when healthy.
+    pub fn provider_urls(&self) -> Vec<String> {
+        self.providers()
+            .iter()
+            .map(|provider| provider.url().to_string())
+            .collect()
+    }
+
+    /// Splits the providers into the ones called at the given time and the unhealthy ones that are avoided,
+    /// unless all providers are unhealthy, in which case all of them are called.
+    fn partition_available_providers(
+        &self,
+        now: u64,
+    ) -> (Vec<RpcNodeProvider>, Vec<RpcNodeProvider>) {
+        let (available, unavailable): (Vec<_>, Vec<_>) = self
+            .providers()
+            .into_iter()
+            .partition(|provider| health::provider_health(provider.url()).is_available(now));
+        if available.is_empty() {
+            (unavailable, vec![])
+        } else {
+            (available, unavailable)
+        }
+    }
+
+    fn providers(&self) -> Vec<RpcNodeProvider> {
+        if !self.rpc_providers.is_empty() {
+            return self
+                .rpc_providers
+                .iter()
+                .cloned()
+                .map(RpcNodeProvider::Custom)
+                .collect();
+        }
+        match &self.chain {
+            EthereumNetwork::Mainnet => MAINNET_PROVIDERS.to_vec(),
+            EthereumNetwork::Sepolia => SEPOLIA_PROVIDERS.to_vec(),
+            EthereumNetwork::Custom(chain_spec) => chain_spec
+                .providers
+                .iter()
+                .cloned()
+                .map(RpcNodeProvider::Custom)
+                .collect(),
         }
     }
 
@@ -58,14 +140,20 @@ impl EthRpcClient {
         O: DeserializeOwned + HttpResponsePayload + Debug,
     {
         let mut last_result: Option<HttpOutcallResult<JsonRpcResult<O>>> = None;
-        for provider in self.providers() {
+        // Unhealthy providers are queried last.
+        let now = ic_cdk::api::time();
+        let mut providers = self.providers();
+        providers
+            .sort_by_key(|provider| !health::provider_health(provider.url()).is_available(now));
+        for provider in providers {
             log!(
                 DEBUG,
                 "[sequential_call_until_ok]: calling provider: {:?}",
                 provider
             );
-            let result = eth_rpc::call(
-                provider.url().to_string(),
+            let result = call_provider(
+                &self.transport,
+                &provider,
                 method.clone(),
                 params.clone(),
                 response_size_estimate,
@@ -104,13 +192,15 @@ impl EthRpcClient {
         I: Serialize + Clone,
         O: DeserializeOwned + HttpResponsePayload,
     {
-        let providers = self.providers();
+        let (providers, unhealthy_providers) =
+            self.partition_available_providers(ic_cdk::api::time());
         let results = {
             let mut fut = Vec::with_capacity(providers.len());
-            for provider in providers {
+            for provider in &providers {
                 log!(DEBUG, "[parallel_call]: will call provider: {:?}", provider);
-                fut.push(eth_rpc::call(
-                    provider.url().to_string(),
+                fut.push(call_provider(
+                    &self.transport,
+                    provider,
                     method.clone(),
                     params.clone(),
                     response_size_estimate,
@@ -118,7 +208,15 @@ impl EthRpcClient {
             }
             futures::future::join_all(fut).await
         };
-        MultiCallResults::from_non_empty_iter(providers.iter().cloned().zip(results.into_iter()))
+        // Unhealthy providers are not called, but count as failed calls
+        // when the results are reduced with a threshold.
+        MultiCallResults::from_non_empty_iter(
+            providers.into_iter().zip(results).chain(
+                unhealthy_providers
+                    .into_iter()
+                    .map(|provider| (provider, Err(HttpOutcallError::UnhealthyProvider))),
+            ),
+        )
     }
 
     pub async fn eth_get_logs(
@@ -129,7 +227,16 @@ impl EthRpcClient {
         let results: MultiCallResults<Vec<LogEntry>> = self
             .parallel_call("eth_getLogs", vec![params], ResponseSizeEstimate::new(100))
             .await;
-        results.reduce_with_equality()
+        // Providers returning the same logs must return the same log contents.
+        results.reduce_with_strategy(
+            "eth_getLogs",
+            self.consensus_config.eth_get_logs.unwrap_or_default(),
+            |logs| {
+                logs.iter()
+                    .map(|log| (log.transaction_hash, log.log_index))
+                    .collect::<Vec<_>>()
+            },
+        )
     }
 
     pub async fn eth_get_block_by_number(
@@ -138,11 +245,6 @@ impl EthRpcClient {
     ) -> Result<Block, MultiCallError<Block>> {
         use crate::eth_rpc::GetBlockByNumberParams;
 
-        let expected_block_size = match self.chain {
-            EthereumNetwork::Sepolia => 12 * 1024,
-            EthereumNetwork::Mainnet => 24 * 1024,
-        };
-
         let results: MultiCallResults<Block> = self
             .parallel_call(
                 "eth_getBlockByNumber",
@@ -150,11 +252,137 @@ impl EthRpcClient {
                     block,
                     include_full_transactions: false,
                 },
-                ResponseSizeEstimate::new(expected_block_size),
+                ResponseSizeEstimate::new(self.chain.expected_block_size()),
             )
             .await;
-        results.reduce_with_equality()
+        results.reduce_with_strategy(
+            "eth_getBlockByNumber",
+            self.consensus_config
+                .eth_get_block_by_number
+                .unwrap_or_default(),
+            |block| (block.number, block.hash),
+        )
     }
+
+    /// Returns the hashes of the transactions included in the given block.
+    pub async fn eth_get_block_transactions(
+        &self,
+        block_number: BlockNumber,
+    ) -> Result<BlockTransactions, MultiCallError<BlockTransactions>> {
+        use crate::eth_rpc::GetBlockByNumberParams;
+
+        let results: MultiCallResults<BlockTransactions> = self
+            .parallel_call(
+                "eth_getBlockByNumber",
+                GetBlockByNumberParams {
+                    block: BlockSpec::Number(block_number),
+                    include_full_transactions: false,
+                },
+                ResponseSizeEstimate::new(self.chain.expected_block_size()),
+            )
+            .await;
+        // The hash of a block commits to its transactions.
+        results.reduce_with_strategy(
+            "eth_getBlockByNumber",
+            self.consensus_config
+                .eth_get_block_by_number
+                .unwrap_or_default(),
+            |block| (block.number, block.hash),
+        )
+    }
+
+    /// Executes a message call to the given contract at the state of the given block,
+    /// without creating a transaction, and returns the output of the call.
+    pub async fn eth_call(
+        &self,
+        contract: Address,
+        data: Vec<u8>,
+        block: BlockSpec,
+    ) -> Result<Data, MultiCallError<Data>> {
+        let results: MultiCallResults<Data> = self
+            .parallel_call(
+                "eth_call",
+                EthCallParams {
+                    transaction: TransactionRequest {
+                        to: contract,
+                        data: Data(data),
+                    },
+                    block,
+                },
+                ResponseSizeEstimate::new(1024),
+            )
+            .await;
+        results.reduce_with_strategy(
+            "eth_call",
+            self.consensus_config.eth_call.unwrap_or_default(),
+            |output| output.0.clone(),
+        )
+    }
+
+    /// Returns the receipt of the given transaction, or `None` if the transaction is unknown.
+    pub async fn eth_get_transaction_receipt(
+        &self,
+        tx_hash: Hash,
+    ) -> Result<Option<TransactionReceipt>, MultiCallError<Option<TransactionReceipt>>> {
+        // A receipt without logs takes about 1KiB, mints usually emit a few logs.
+        let results: MultiCallResults<Option<TransactionReceipt>> = self
+            .parallel_call(
+                "eth_getTransactionReceipt",
+                vec![tx_hash],
+                ResponseSizeEstimate::new(4 * 1024),
+            )
+            .await;
+        // Providers returning a receipt in the same block must return the same receipt.
+        results.reduce_with_strategy(
+            "eth_getTransactionReceipt",
+            self.consensus_config
+                .eth_get_transaction_receipt
+                .unwrap_or_default(),
+            |receipt| {
+                receipt
+                    .as_ref()
+                    .map(|receipt| (receipt.transaction_hash, receipt.block_hash))
+            },
+        )
+    }
+}
+
+/// Calls the provider and records the outcome of the call in the health of the provider.
+async fn call_provider<I, O>(
+    transport: &RpcTransportConfig,
+    provider: &RpcNodeProvider,
+    method: impl Into<String>,
+    params: I,
+    response_size_estimate: ResponseSizeEstimate,
+) -> HttpOutcallResult<JsonRpcResult<O>>
+where
+    I: Serialize,
+    O: DeserializeOwned + HttpResponsePayload,
+{
+    let start = ic_cdk::api::time();
+    let result = eth_rpc::call(
+        transport,
+        provider.url().to_string(),
+        http_headers(provider),
+        method,
+        params,
+        response_size_estimate,
+        provider.max_response_size(),
+    )
+    .await;
+    health::observe_call(provider.url(), &result, start, ic_cdk::api::time());
+    result
+}
+
+fn http_headers(provider: &RpcNodeProvider) -> Vec<HttpHeader> {
+    provider
+        .headers()
+        .iter()
+        .map(|header| HttpHeader {
+            name: header.name.clone(),
+            value: header.value.clone(),
+        })
+        .collect()
 }
 
 /// Aggregates responses of different providers to the same query.
@@ -165,7 +393,7 @@ pub struct MultiCallResults<T> {
 }
 
 impl<T> MultiCallResults<T> {
-    fn from_non_empty_iter<
+    pub(crate) fn from_non_empty_iter<
         I: IntoIterator<Item = (RpcNodeProvider, HttpOutcallResult<JsonRpcResult<T>>)>,
     >(
         iter: I,
@@ -178,6 +406,25 @@ impl<T> MultiCallResults<T> {
     }
 }
 
+impl<T> MultiCallResults<T> {
+    /// Removes the results of the unhealthy providers, which were not called,
+    /// for the strategies expecting every provider to return a result.
+    /// A threshold is instead reached among all providers, where unhealthy providers count as failed calls.
+    fn without_unhealthy_providers(self) -> Self {
+        let results: BTreeMap<_, _> = self
+            .results
+            .into_iter()
+            .filter(|(_provider, result)| {
+                !matches!(result, Err(HttpOutcallError::UnhealthyProvider))
+            })
+            .collect();
+        if results.is_empty() {
+            panic!("BUG: unhealthy providers are called when all providers are unhealthy");
+        }
+        Self { results }
+    }
+}
+
 impl<T: PartialEq> MultiCallResults<T> {
     /// Expects all results to be ok or return the following error:
     /// * MultiCallError::ConsistentJsonRpcError: all errors are the same JSON-RPC error.
@@ -251,6 +498,80 @@ impl<T> MultiCallError<T> {
             }
         }
     }
+
+    /// Returns the code and message of a JSON-RPC error matching the predicate returned by any provider,
+    /// unless another provider returned a result.
+    pub fn json_rpc_error_matching<P: Fn(i64, &str) -> bool>(
+        &self,
+        predicate: P,
+    ) -> Option<(i64, &str)> {
+        match self {
+            MultiCallError::ConsistentHttpOutcallError(_) => None,
+            MultiCallError::ConsistentJsonRpcError { code, message } => {
+                predicate(*code, message).then_some((*code, message.as_str()))
+            }
+            MultiCallError::InconsistentResults(results) => {
+                if results
+                    .results
+                    .values()
+                    .any(|result| matches!(result, Ok(JsonRpcResult::Result(_))))
+                {
+                    return None;
+                }
+                results.results.values().find_map(|result| match result {
+                    Ok(JsonRpcResult::Error { code, message }) if predicate(*code, message) => {
+                        Some((*code, message.as_str()))
+                    }
+                    _ => None,
+                })
+            }
+        }
+    }
+}
+
+impl<T: Clone + Debug + PartialEq> MultiCallResults<T> {
+    /// Reduces the results with the given strategy.
+    /// The providers whose result differs from the reduced one are logged and counted in the metrics,
+    /// and so are failures to reach consensus.
+    pub fn reduce_with_strategy<F: Fn(&T) -> K, K: Ord>(
+        self,
+        method: &str,
+        strategy: ConsensusStrategy,
+        extractor: F,
+    ) -> Result<T, MultiCallError<T>> {
+        let results = self.results.clone();
+        let reduced = match strategy {
+            ConsensusStrategy::Equality => {
+                self.without_unhealthy_providers().reduce_with_equality()
+            }
+            ConsensusStrategy::StrictMajority => self
+                .without_unhealthy_providers()
+                .reduce_with_strict_majority_by_key(extractor),
+            ConsensusStrategy::Threshold { min } => self.reduce_with_threshold(min as usize),
+        };
+        match &reduced {
+            Ok(value) => {
+                for (provider, result) in results {
+                    if !matches!(
+                        &result,
+                        Ok(JsonRpcResult::Result(other)) if other == value
+                    ) && !matches!(&result, Err(HttpOutcallError::UnhealthyProvider))
+                    {
+                        log!(
+                            INFO,
+                            "[reduce_with_strategy]: provider {provider:?} disagrees with the consensus on {method}: {result:?}"
+                        );
+                        eth_rpc::metrics::observe_provider_disagreement(method, provider.url());
+                    }
+                }
+            }
+            Err(MultiCallError::InconsistentResults(_)) => {
+                eth_rpc::metrics::observe_inconsistent_results(method);
+            }
+            Err(_) => {}
+        }
+        reduced
+    }
 }
 
 impl<T: Debug + PartialEq> MultiCallResults<T> {
@@ -278,6 +599,55 @@ impl<T: Debug + PartialEq> MultiCallResults<T> {
         Ok(base_result)
     }
 
+    /// Expects at least `min` providers to return equal results,
+    /// ignoring the errors and the other results returned by the other providers.
+    pub fn reduce_with_threshold(self, min: usize) -> Result<T, MultiCallError<T>> {
+        let winners: Vec<RpcNodeProvider> = {
+            let mut ballots: Vec<(&RpcNodeProvider, &T, usize)> = Vec::new();
+            for (provider, result) in &self.results {
+                if let Ok(JsonRpcResult::Result(value)) = result {
+                    match ballots.iter_mut().find(|(_, other, _)| *other == value) {
+                        Some((_, _, votes)) => *votes += 1,
+                        None => ballots.push((provider, value, 1)),
+                    }
+                }
+            }
+            ballots
+                .into_iter()
+                .filter(|(_, _, votes)| *votes >= min)
+                .map(|(provider, _, _)| provider.clone())
+                .collect()
+        };
+        match winners.as_slice() {
+            [winner] => {
+                let mut results = self.results;
+                match results.remove(winner) {
+                    Some(Ok(JsonRpcResult::Result(value))) => Ok(value),
+                    _ => panic!("BUG: the winner should have returned a result"),
+                }
+            }
+            _ => {
+                let has_result = self
+                    .results
+                    .values()
+                    .any(|result| matches!(result, Ok(JsonRpcResult::Result(_))));
+                if !has_result {
+                    // All providers failed, report the errors as if all results were expected to be ok.
+                    return match self.all_ok() {
+                        Err(error) => Err(error),
+                        Ok(_) => panic!("BUG: all results should be errors"),
+                    };
+                }
+                let error = MultiCallError::InconsistentResults(self);
+                log!(
+                    INFO,
+                    "[reduce_with_threshold]: no result returned by exactly one group of at least {min} providers {error:?}"
+                );
+                Err(error)
+            }
+        }
+    }
+
     pub fn reduce_with_min_by_key<F: FnMut(&T) -> K, K: Ord>(
         self,
         extractor: F,
@@ -1,3 +1,15 @@
+use crate::eth_rpc::MAX_PAYLOAD_SIZE;
+use candid::{CandidType, Deserialize};
+use minicbor::{Decode, Encode};
+use std::collections::BTreeSet;
+use std::fmt;
+
+/// The maximum number of JSON-RPC providers queried by the minter.
+pub const MAX_PROVIDERS: usize = 8;
+
+/// Replaces the values of headers in the events and in the logs.
+const REDACTED: &str = "<redacted>";
+
 pub(crate) const MAINNET_PROVIDERS: [RpcNodeProvider; 3] = [
     RpcNodeProvider::Ethereum(EthereumProvider::Ankr),
     RpcNodeProvider::Ethereum(EthereumProvider::PublicNode),
@@ -9,10 +21,12 @@ pub(crate) const SEPOLIA_PROVIDERS: [RpcNodeProvider; 2] = [
     RpcNodeProvider::Sepolia(SepoliaProvider::PublicNode),
 ];
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Ord, PartialOrd, Hash)]
+#[derive(Clone, Debug, PartialEq, Eq, Ord, PartialOrd, Hash)]
 pub(crate) enum RpcNodeProvider {
     Ethereum(EthereumProvider),
     Sepolia(SepoliaProvider),
+    /// A provider of a custom chain, or configured by an upgrade.
+    Custom(RpcProvider),
 }
 
 impl RpcNodeProvider {
@@ -20,8 +34,135 @@ impl RpcNodeProvider {
         match self {
             Self::Ethereum(provider) => provider.ethereum_mainnet_endpoint_url(),
             Self::Sepolia(provider) => provider.ethereum_sepolia_endpoint_url(),
+            Self::Custom(provider) => &provider.url,
+        }
+    }
+
+    pub(crate) fn headers(&self) -> &[RpcHeader] {
+        match self {
+            Self::Ethereum(_) | Self::Sepolia(_) => &[],
+            Self::Custom(provider) => provider.headers.as_deref().unwrap_or_default(),
+        }
+    }
+
+    /// The maximum number of blocks the provider accepts in a single `eth_getLogs` request, if limited.
+    pub(crate) fn max_block_spread(&self) -> Option<u64> {
+        match self {
+            Self::Ethereum(_) | Self::Sepolia(_) => None,
+            Self::Custom(provider) => provider.max_block_spread,
+        }
+    }
+
+    /// The maximum number of bytes of the response bodies returned by the provider.
+    pub(crate) fn max_response_size(&self) -> u64 {
+        match self {
+            Self::Ethereum(_) | Self::Sepolia(_) => MAX_PAYLOAD_SIZE,
+            Self::Custom(provider) => provider.max_response_size.unwrap_or(MAX_PAYLOAD_SIZE),
+        }
+    }
+}
+
+/// A JSON-RPC provider configured by an upgrade.
+///
+/// The provider URL is public: it is shown in the events and in the logs,
+/// whereas the values of the headers are redacted. API keys must therefore be passed as headers.
+#[derive(
+    CandidType, Deserialize, Clone, Debug, Encode, Decode, PartialEq, Eq, PartialOrd, Ord, Hash,
+)]
+pub struct RpcProvider {
+    #[n(0)]
+    pub url: String,
+    /// Headers added to every request, e.g., to authenticate with an API key.
+    #[n(1)]
+    pub headers: Option<Vec<RpcHeader>>,
+    /// The maximum number of bytes of the response bodies returned by the provider.
+    #[n(2)]
+    pub max_response_size: Option<u64>,
+    /// The maximum number of blocks the provider accepts in a single `eth_getLogs` request.
+    #[n(3)]
+    pub max_block_spread: Option<u64>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Encode, Decode, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RpcHeader {
+    #[n(0)]
+    pub name: String,
+    #[n(1)]
+    pub value: String,
+}
+
+impl fmt::Debug for RpcHeader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RpcHeader")
+            .field("name", &self.name)
+            .field("value", &REDACTED)
+            .finish()
+    }
+}
+
+impl RpcProvider {
+    /// Returns the provider with the values of its headers redacted.
+    pub fn redacted(self) -> Self {
+        Self {
+            headers: self.headers.map(|headers| {
+                headers
+                    .into_iter()
+                    .map(|header| RpcHeader {
+                        value: REDACTED.to_string(),
+                        ..header
+                    })
+                    .collect()
+            }),
+            ..self
+        }
+    }
+}
+
+impl From<String> for RpcProvider {
+    fn from(url: String) -> Self {
+        Self {
+            url,
+            headers: None,
+            max_response_size: None,
+            max_block_spread: None,
+        }
+    }
+}
+
+/// Checks that the providers can be queried by the minter.
+pub fn validate_providers(providers: &[RpcProvider]) -> Result<(), String> {
+    if providers.len() > MAX_PROVIDERS {
+        return Err(format!("there can be up to {MAX_PROVIDERS} providers"));
+    }
+    let mut urls = BTreeSet::new();
+    for provider in providers {
+        let url = &provider.url;
+        if !url.starts_with("https://") {
+            return Err(format!("provider URL '{url}' must use HTTPS"));
+        }
+        if !urls.insert(url) {
+            return Err(format!("provider URL '{url}' is duplicated"));
+        }
+        for header in provider.headers.iter().flatten() {
+            if header.name.trim().is_empty() {
+                return Err(format!("provider '{url}' has a header without name"));
+            }
+        }
+        if provider
+            .max_response_size
+            .is_some_and(|size| size == 0 || size > MAX_PAYLOAD_SIZE)
+        {
+            return Err(format!(
+                "max response size of provider '{url}' must be between 1 and {MAX_PAYLOAD_SIZE} bytes"
+            ));
+        }
+        if provider.max_block_spread == Some(0) {
+            return Err(format!(
+                "max block spread of provider '{url}' cannot be zero"
+            ));
         }
     }
+    Ok(())
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Ord, PartialOrd, Hash)]
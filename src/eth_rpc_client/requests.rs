@@ -1,5 +1,5 @@
 use crate::address::Address;
-use crate::eth_rpc::BlockSpec;
+use crate::eth_rpc::{BlockSpec, Data};
 use serde::Serialize;
 
 /// Parameters of the [`eth_getTransactionCount`](https://ethereum.org/en/developers/docs/apis/json-rpc/#eth_gettransactioncount) call.
@@ -17,3 +17,28 @@ impl From<GetTransactionCountParams> for (Address, BlockSpec) {
         (params.address, params.block)
     }
 }
+
+/// Parameters of the [`eth_call`](https://ethereum.org/en/developers/docs/apis/json-rpc/#eth_call) call.
+#[derive(Debug, Serialize, Clone)]
+#[serde(into = "(TransactionRequest, BlockSpec)")]
+pub struct EthCallParams {
+    /// The message call, which is executed without creating a transaction.
+    pub transaction: TransactionRequest,
+    /// The block at whose state the call is executed.
+    pub block: BlockSpec,
+}
+
+impl From<EthCallParams> for (TransactionRequest, BlockSpec) {
+    fn from(params: EthCallParams) -> Self {
+        (params.transaction, params.block)
+    }
+}
+
+/// A message call to a contract.
+#[derive(Debug, Serialize, Clone)]
+pub struct TransactionRequest {
+    /// The address of the called contract.
+    pub to: Address,
+    /// The ABI-encoded call, i.e., the function selector followed by the encoded arguments.
+    pub data: Data,
+}
@@ -1,35 +1,39 @@
-use crate::eth_rpc::{Hash, HttpResponsePayload, ResponseTransform};
+use crate::eth_rpc::{Hash, HttpResponsePayload, LogEntry, ResponseTransform};
 use crate::numeric::{BlockNumber, GasAmount, Wei, WeiPerGas};
 use minicbor::{Decode, Encode};
 use serde::{Deserialize, Serialize};
 use std::fmt::{Display, Formatter};
 
-#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq, Encode, Decode)]
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct TransactionReceipt {
     /// The hash of the block containing the transaction.
-    #[n(0)]
     pub block_hash: Hash,
 
     /// The number of the block containing the transaction.
-    #[n(1)]
     pub block_number: BlockNumber,
 
     /// The total base charge plus tip paid for each unit of gas
-    #[n(2)]
     pub effective_gas_price: WeiPerGas,
 
     /// The amount of gas used by this specific transaction alone
-    #[n(3)]
     pub gas_used: GasAmount,
 
     /// Status of the transaction.
-    #[n(4)]
     pub status: TransactionStatus,
 
     /// The hash of the transaction
-    #[n(5)]
     pub transaction_hash: Hash,
+
+    /// The logs emitted by the transaction.
+    #[serde(default)]
+    pub logs: Vec<LogEntry>,
+}
+
+impl HttpResponsePayload for TransactionReceipt {
+    fn response_transform() -> Option<ResponseTransform> {
+        Some(ResponseTransform::TransactionReceipt)
+    }
 }
 
 impl TransactionReceipt {
@@ -1,11 +1,13 @@
 mod eth_rpc_client {
-    use crate::eth_rpc_client::providers::{EthereumProvider, RpcNodeProvider, SepoliaProvider};
+    use crate::eth_rpc_client::providers::{
+        EthereumProvider, RpcNodeProvider, RpcProvider, SepoliaProvider,
+    };
     use crate::eth_rpc_client::EthRpcClient;
-    use crate::lifecycle::EthereumNetwork;
+    use crate::lifecycle::{ChainSpec, EthereumNetwork};
 
     #[test]
     fn should_retrieve_sepolia_providers_in_stable_order() {
-        let client = EthRpcClient::new(EthereumNetwork::Sepolia);
+        let client = EthRpcClient::new(EthereumNetwork::Sepolia, vec![]);
 
         let providers = client.providers();
 
@@ -20,7 +22,7 @@ mod eth_rpc_client {
 
     #[test]
     fn should_retrieve_mainnet_providers_in_stable_order() {
-        let client = EthRpcClient::new(EthereumNetwork::Mainnet);
+        let client = EthRpcClient::new(EthereumNetwork::Mainnet, vec![]);
 
         let providers = client.providers();
 
@@ -33,6 +35,54 @@ mod eth_rpc_client {
             ]
         );
     }
+
+    #[test]
+    fn should_retrieve_custom_chain_providers_in_configured_order() {
+        let client = EthRpcClient::new(
+            EthereumNetwork::Custom(ChainSpec {
+                chain_id: 10,
+                name: "OP Mainnet".to_string(),
+                expected_block_size: 32 * 1024,
+                max_block_spread: 500,
+                providers: vec![
+                    RpcProvider::from("https://mainnet.optimism.io".to_string()),
+                    RpcProvider {
+                        max_block_spread: Some(50),
+                        ..RpcProvider::from("https://optimism.publicnode.com".to_string())
+                    },
+                ],
+            }),
+            vec![],
+        );
+
+        let providers = client.providers();
+
+        assert_eq!(
+            providers,
+            &[
+                RpcNodeProvider::Custom(RpcProvider::from(
+                    "https://mainnet.optimism.io".to_string()
+                )),
+                RpcNodeProvider::Custom(RpcProvider {
+                    max_block_spread: Some(50),
+                    ..RpcProvider::from("https://optimism.publicnode.com".to_string())
+                })
+            ]
+        );
+    }
+    #[test]
+    fn should_retrieve_configured_providers_instead_of_default_ones() {
+        let provider = RpcProvider {
+            max_response_size: Some(1024),
+            ..RpcProvider::from("https://eth.llamarpc.com".to_string())
+        };
+        let client = EthRpcClient::new(EthereumNetwork::Mainnet, vec![provider.clone()]);
+
+        let providers = client.providers();
+
+        assert_eq!(providers, &[RpcNodeProvider::Custom(provider)]);
+        assert_eq!(providers[0].max_response_size(), 1024);
+    }
 }
 
 mod multi_call_results {
@@ -190,7 +240,7 @@ mod multi_call_results {
     }
 
     mod reduce_with_min_by_key {
-        use crate::eth_rpc::{Block, JsonRpcResult};
+        use crate::eth_rpc::{Block, Hash, JsonRpcResult};
         use crate::eth_rpc_client::tests::multi_call_results::{ANKR, PUBLIC_NODE};
         use crate::eth_rpc_client::MultiCallResults;
         use crate::numeric::{BlockNumber, Wei};
@@ -203,6 +253,7 @@ mod multi_call_results {
                     Ok(JsonRpcResult::Result(Block {
                         number: BlockNumber::new(0x411cda),
                         base_fee_per_gas: Wei::new(0x10),
+                        hash: Hash([0x11; 32]),
                     })),
                 ),
                 (
@@ -210,6 +261,7 @@ mod multi_call_results {
                     Ok(JsonRpcResult::Result(Block {
                         number: BlockNumber::new(0x411cd9),
                         base_fee_per_gas: Wei::new(0x10),
+                        hash: Hash([0x11; 32]),
                     })),
                 ),
             ]);
@@ -221,160 +273,143 @@ mod multi_call_results {
                 Ok(Block {
                     number: BlockNumber::new(0x411cd9),
                     base_fee_per_gas: Wei::new(0x10),
+                    hash: Hash([0x11; 32]),
                 })
             );
         }
     }
 
     mod reduce_with_stable_majority_by_key {
-        use crate::eth_rpc::{FeeHistory, JsonRpcResult};
+        use crate::eth_rpc::{Block, Hash, JsonRpcResult};
         use crate::eth_rpc_client::tests::multi_call_results::{ANKR, CLOUDFLARE, PUBLIC_NODE};
         use crate::eth_rpc_client::MultiCallError::ConsistentJsonRpcError;
         use crate::eth_rpc_client::{MultiCallError, MultiCallResults};
-        use crate::numeric::{BlockNumber, WeiPerGas};
+        use crate::numeric::{BlockNumber, Wei};
 
         #[test]
-        fn should_get_unanimous_fee_history() {
-            let results: MultiCallResults<FeeHistory> =
-                MultiCallResults::from_non_empty_iter(vec![
-                    (ANKR, Ok(JsonRpcResult::Result(fee_history()))),
-                    (PUBLIC_NODE, Ok(JsonRpcResult::Result(fee_history()))),
-                    (CLOUDFLARE, Ok(JsonRpcResult::Result(fee_history()))),
-                ]);
+        fn should_get_unanimous_block() {
+            let results: MultiCallResults<Block> = MultiCallResults::from_non_empty_iter(vec![
+                (ANKR, Ok(JsonRpcResult::Result(block()))),
+                (PUBLIC_NODE, Ok(JsonRpcResult::Result(block()))),
+                (CLOUDFLARE, Ok(JsonRpcResult::Result(block()))),
+            ]);
 
-            let reduced =
-                results.reduce_with_strict_majority_by_key(|fee_history| fee_history.oldest_block);
+            let reduced = results.reduce_with_strict_majority_by_key(|block| block.number);
 
-            assert_eq!(reduced, Ok(fee_history()));
+            assert_eq!(reduced, Ok(block()));
         }
 
         #[test]
-        fn should_get_fee_history_with_2_out_of_3() {
+        fn should_get_block_with_2_out_of_3() {
             for index_non_majority in 0..3_usize {
                 let index_majority = (index_non_majority + 1) % 3;
-                let mut fees = [fee_history(), fee_history(), fee_history()];
-                fees[index_non_majority].oldest_block = BlockNumber::new(0x10f73fd);
+                let mut blocks = [block(), block(), block()];
+                blocks[index_non_majority].number = BlockNumber::new(0x10f73fd);
                 assert_ne!(
-                    fees[index_non_majority].oldest_block,
-                    fees[index_majority].oldest_block
+                    blocks[index_non_majority].number,
+                    blocks[index_majority].number
                 );
-                let majority_fee = fees[index_majority].clone();
-                let [ankr_fee_history, cloudflare_fee_history, public_node_fee_history] = fees;
-                let results: MultiCallResults<FeeHistory> =
-                    MultiCallResults::from_non_empty_iter(vec![
-                        (ANKR, Ok(JsonRpcResult::Result(ankr_fee_history))),
-                        (
-                            CLOUDFLARE,
-                            Ok(JsonRpcResult::Result(cloudflare_fee_history)),
-                        ),
-                        (
-                            PUBLIC_NODE,
-                            Ok(JsonRpcResult::Result(public_node_fee_history)),
-                        ),
-                    ]);
-
-                let reduced = results
-                    .reduce_with_strict_majority_by_key(|fee_history| fee_history.oldest_block);
-
-                assert_eq!(reduced, Ok(majority_fee));
+                let majority_block = blocks[index_majority].clone();
+                let [ankr_block, cloudflare_block, public_node_block] = blocks;
+                let results: MultiCallResults<Block> = MultiCallResults::from_non_empty_iter(vec![
+                    (ANKR, Ok(JsonRpcResult::Result(ankr_block))),
+                    (CLOUDFLARE, Ok(JsonRpcResult::Result(cloudflare_block))),
+                    (PUBLIC_NODE, Ok(JsonRpcResult::Result(public_node_block))),
+                ]);
+
+                let reduced = results.reduce_with_strict_majority_by_key(|block| block.number);
+
+                assert_eq!(reduced, Ok(majority_block));
             }
         }
 
         #[test]
         fn should_fail_when_no_strict_majority() {
-            let ankr_fee_history = FeeHistory {
-                oldest_block: BlockNumber::new(0x10f73fd),
-                ..fee_history()
+            let ankr_block = Block {
+                number: BlockNumber::new(0x10f73fd),
+                ..block()
             };
-            let cloudflare_fee_history = FeeHistory {
-                oldest_block: BlockNumber::new(0x10f73fc),
-                ..fee_history()
+            let cloudflare_block = Block {
+                number: BlockNumber::new(0x10f73fc),
+                ..block()
             };
-            let public_node_fee_history = FeeHistory {
-                oldest_block: BlockNumber::new(0x10f73fe),
-                ..fee_history()
+            let public_node_block = Block {
+                number: BlockNumber::new(0x10f73fe),
+                ..block()
             };
-            let three_distinct_results: MultiCallResults<FeeHistory> =
+            let three_distinct_results: MultiCallResults<Block> =
                 MultiCallResults::from_non_empty_iter(vec![
-                    (ANKR, Ok(JsonRpcResult::Result(ankr_fee_history.clone()))),
+                    (ANKR, Ok(JsonRpcResult::Result(ankr_block.clone()))),
                     (
                         PUBLIC_NODE,
-                        Ok(JsonRpcResult::Result(public_node_fee_history.clone())),
+                        Ok(JsonRpcResult::Result(public_node_block.clone())),
                     ),
                 ]);
 
             let reduced = three_distinct_results
                 .clone()
-                .reduce_with_strict_majority_by_key(|fee_history| fee_history.oldest_block);
+                .reduce_with_strict_majority_by_key(|block| block.number);
 
             assert_eq!(
                 reduced,
                 Err(MultiCallError::InconsistentResults(
                     MultiCallResults::from_non_empty_iter(vec![
-                        (ANKR, Ok(JsonRpcResult::Result(ankr_fee_history.clone()))),
-                        (
-                            PUBLIC_NODE,
-                            Ok(JsonRpcResult::Result(public_node_fee_history))
-                        ),
+                        (ANKR, Ok(JsonRpcResult::Result(ankr_block.clone()))),
+                        (PUBLIC_NODE, Ok(JsonRpcResult::Result(public_node_block))),
                     ])
                 ))
             );
 
-            let two_distinct_results: MultiCallResults<FeeHistory> =
+            let two_distinct_results: MultiCallResults<Block> =
                 MultiCallResults::from_non_empty_iter(vec![
-                    (ANKR, Ok(JsonRpcResult::Result(ankr_fee_history.clone()))),
+                    (ANKR, Ok(JsonRpcResult::Result(ankr_block.clone()))),
                     (
                         PUBLIC_NODE,
-                        Ok(JsonRpcResult::Result(cloudflare_fee_history.clone())),
+                        Ok(JsonRpcResult::Result(cloudflare_block.clone())),
                     ),
                 ]);
 
             let reduced = two_distinct_results
                 .clone()
-                .reduce_with_strict_majority_by_key(|fee_history| fee_history.oldest_block);
+                .reduce_with_strict_majority_by_key(|block| block.number);
 
             assert_eq!(
                 reduced,
                 Err(MultiCallError::InconsistentResults(
                     MultiCallResults::from_non_empty_iter(vec![
-                        (ANKR, Ok(JsonRpcResult::Result(ankr_fee_history))),
-                        (
-                            PUBLIC_NODE,
-                            Ok(JsonRpcResult::Result(cloudflare_fee_history))
-                        ),
+                        (ANKR, Ok(JsonRpcResult::Result(ankr_block))),
+                        (PUBLIC_NODE, Ok(JsonRpcResult::Result(cloudflare_block))),
                     ])
                 ))
             );
         }
 
         #[test]
-        fn should_fail_when_fee_history_inconsistent_for_same_oldest_block() {
-            let (fee, inconsistent_fee) = {
-                let fee = fee_history();
-                let mut inconsistent_fee = fee.clone();
-                inconsistent_fee.base_fee_per_gas[0] = WeiPerGas::new(0x729d3f3b4);
-                assert_ne!(fee, inconsistent_fee);
-                (fee, inconsistent_fee)
+        fn should_fail_when_block_inconsistent_for_same_number() {
+            let (block, inconsistent_block) = {
+                let block = block();
+                let mut inconsistent_block = block.clone();
+                inconsistent_block.hash = Hash([0x22; 32]);
+                assert_ne!(block, inconsistent_block);
+                (block, inconsistent_block)
             };
 
-            let results: MultiCallResults<FeeHistory> =
-                MultiCallResults::from_non_empty_iter(vec![
-                    (ANKR, Ok(JsonRpcResult::Result(fee.clone()))),
-                    (
-                        PUBLIC_NODE,
-                        Ok(JsonRpcResult::Result(inconsistent_fee.clone())),
-                    ),
-                ]);
+            let results: MultiCallResults<Block> = MultiCallResults::from_non_empty_iter(vec![
+                (ANKR, Ok(JsonRpcResult::Result(block.clone()))),
+                (
+                    PUBLIC_NODE,
+                    Ok(JsonRpcResult::Result(inconsistent_block.clone())),
+                ),
+            ]);
 
-            let reduced =
-                results.reduce_with_strict_majority_by_key(|fee_history| fee_history.oldest_block);
+            let reduced = results.reduce_with_strict_majority_by_key(|block| block.number);
 
             assert_eq!(
                 reduced,
                 Err(MultiCallError::InconsistentResults(
                     MultiCallResults::from_non_empty_iter(vec![
-                        (ANKR, Ok(JsonRpcResult::Result(fee.clone()))),
-                        (PUBLIC_NODE, Ok(JsonRpcResult::Result(inconsistent_fee))),
+                        (ANKR, Ok(JsonRpcResult::Result(block.clone()))),
+                        (PUBLIC_NODE, Ok(JsonRpcResult::Result(inconsistent_block))),
                     ])
                 ))
             );
@@ -382,21 +417,20 @@ mod multi_call_results {
 
         #[test]
         fn should_fail_upon_any_error() {
-            let results: MultiCallResults<FeeHistory> =
-                MultiCallResults::from_non_empty_iter(vec![
-                    (ANKR, Ok(JsonRpcResult::Result(fee_history()))),
-                    (
-                        PUBLIC_NODE,
-                        Ok(JsonRpcResult::Error {
-                            code: -32700,
-                            message: "error".to_string(),
-                        }),
-                    ),
-                ]);
+            let results: MultiCallResults<Block> = MultiCallResults::from_non_empty_iter(vec![
+                (ANKR, Ok(JsonRpcResult::Result(block()))),
+                (
+                    PUBLIC_NODE,
+                    Ok(JsonRpcResult::Error {
+                        code: -32700,
+                        message: "error".to_string(),
+                    }),
+                ),
+            ]);
 
             let reduced = results
                 .clone()
-                .reduce_with_strict_majority_by_key(|fee_history| fee_history.oldest_block);
+                .reduce_with_strict_majority_by_key(|block| block.number);
 
             assert_eq!(
                 reduced,
@@ -407,24 +441,122 @@ mod multi_call_results {
             );
         }
 
-        fn fee_history() -> FeeHistory {
-            FeeHistory {
-                oldest_block: BlockNumber::new(0x10f73fc),
-                base_fee_per_gas: vec![
-                    WeiPerGas::new(0x729d3f3b3),
-                    WeiPerGas::new(0x766e503ea),
-                    WeiPerGas::new(0x75b51b620),
-                    WeiPerGas::new(0x74094f2b4),
-                    WeiPerGas::new(0x716724f03),
-                    WeiPerGas::new(0x73b467f76),
-                ],
-                reward: vec![
-                    vec![WeiPerGas::new(0x5f5e100)],
-                    vec![WeiPerGas::new(0x55d4a80)],
-                    vec![WeiPerGas::new(0x5f5e100)],
-                    vec![WeiPerGas::new(0x5f5e100)],
-                    vec![WeiPerGas::new(0x5f5e100)],
-                ],
+        fn block() -> Block {
+            Block {
+                number: BlockNumber::new(0x10f73fc),
+                base_fee_per_gas: Wei::new(0x729d3f3b3),
+                hash: Hash([0x11; 32]),
+            }
+        }
+    }
+
+    mod reduce_with_threshold {
+        use crate::eth_rpc::{HttpOutcallError, JsonRpcResult};
+        use crate::eth_rpc_client::tests::multi_call_results::{ANKR, CLOUDFLARE, PUBLIC_NODE};
+        use crate::eth_rpc_client::{MultiCallError, MultiCallResults};
+        use ic_cdk::api::call::RejectionCode;
+
+        #[test]
+        fn should_tolerate_lagging_and_failing_providers() {
+            let results: MultiCallResults<String> = MultiCallResults::from_non_empty_iter(vec![
+                (ANKR, Ok(JsonRpcResult::Result("hello".to_string()))),
+                (PUBLIC_NODE, Ok(JsonRpcResult::Result("world".to_string()))),
+                (CLOUDFLARE, Ok(JsonRpcResult::Result("hello".to_string()))),
+            ]);
+            assert_eq!(results.reduce_with_threshold(2), Ok("hello".to_string()));
+
+            let results: MultiCallResults<String> = MultiCallResults::from_non_empty_iter(vec![
+                (ANKR, Ok(JsonRpcResult::Result("hello".to_string()))),
+                (PUBLIC_NODE, Err(reject())),
+            ]);
+            assert_eq!(results.reduce_with_threshold(1), Ok("hello".to_string()));
+        }
+
+        #[test]
+        fn should_be_inconsistent_when_threshold_not_reached() {
+            let results: MultiCallResults<String> = MultiCallResults::from_non_empty_iter(vec![
+                (ANKR, Ok(JsonRpcResult::Result("hello".to_string()))),
+                (PUBLIC_NODE, Ok(JsonRpcResult::Result("world".to_string()))),
+                (CLOUDFLARE, Err(reject())),
+            ]);
+
+            let reduced = results.clone().reduce_with_threshold(2);
+
+            assert_eq!(reduced, Err(MultiCallError::InconsistentResults(results)));
+        }
+
+        #[test]
+        fn should_be_inconsistent_when_several_results_reach_threshold() {
+            let results: MultiCallResults<String> = MultiCallResults::from_non_empty_iter(vec![
+                (ANKR, Ok(JsonRpcResult::Result("hello".to_string()))),
+                (PUBLIC_NODE, Ok(JsonRpcResult::Result("world".to_string()))),
+            ]);
+
+            let reduced = results.clone().reduce_with_threshold(1);
+
+            assert_eq!(reduced, Err(MultiCallError::InconsistentResults(results)));
+        }
+
+        #[test]
+        fn should_be_consistent_error_when_all_providers_fail() {
+            let results: MultiCallResults<String> = MultiCallResults::from_non_empty_iter(vec![
+                (ANKR, Err(reject())),
+                (PUBLIC_NODE, Err(reject())),
+            ]);
+
+            let reduced = results.reduce_with_threshold(1);
+
+            assert_eq!(
+                reduced,
+                Err(MultiCallError::ConsistentHttpOutcallError(reject()))
+            );
+        }
+
+        fn reject() -> HttpOutcallError {
+            HttpOutcallError::IcError {
+                code: RejectionCode::CanisterReject,
+                message: "reject".to_string(),
+            }
+        }
+    }
+
+    mod reduce_with_strategy {
+        use crate::eth_rpc::JsonRpcResult;
+        use crate::eth_rpc_client::consensus::ConsensusStrategy;
+        use crate::eth_rpc_client::tests::multi_call_results::{ANKR, CLOUDFLARE, PUBLIC_NODE};
+        use crate::eth_rpc_client::{MultiCallError, MultiCallResults};
+
+        #[test]
+        fn should_reduce_with_configured_strategy() {
+            let results: MultiCallResults<String> = MultiCallResults::from_non_empty_iter(vec![
+                (ANKR, Ok(JsonRpcResult::Result("hello".to_string()))),
+                (PUBLIC_NODE, Ok(JsonRpcResult::Result("world".to_string()))),
+                (CLOUDFLARE, Ok(JsonRpcResult::Result("hello".to_string()))),
+            ]);
+
+            assert_eq!(
+                results.clone().reduce_with_strategy(
+                    "eth_getLogs",
+                    ConsensusStrategy::Equality,
+                    String::clone
+                ),
+                Err(MultiCallError::InconsistentResults(
+                    MultiCallResults::from_non_empty_iter(vec![
+                        (ANKR, Ok(JsonRpcResult::Result("hello".to_string()))),
+                        (PUBLIC_NODE, Ok(JsonRpcResult::Result("world".to_string()))),
+                    ])
+                ))
+            );
+            for strategy in [
+                ConsensusStrategy::StrictMajority,
+                ConsensusStrategy::Threshold { min: 2 },
+            ] {
+                assert_eq!(
+                    results
+                        .clone()
+                        .reduce_with_strategy("eth_getLogs", strategy, String::clone),
+                    Ok("hello".to_string())
+                );
             }
         }
     }
@@ -542,6 +674,7 @@ mod eth_get_transaction_receipt {
                     "0x0e59bd032b9b22aca5e2784e4cf114783512db00988c716cf17a1cc755a0a93d"
                 )
                 .unwrap(),
+                logs: vec![],
             }
         )
     }
@@ -611,3 +744,189 @@ mod eth_get_transaction_count {
         assert_eq!(count, TransactionCount::from(0x3d8_u32));
     }
 }
+
+mod provider_health {
+    use crate::eth_rpc::{HttpOutcallError, HttpOutcallResult, JsonRpcResult};
+    use crate::eth_rpc_client::consensus::ConsensusStrategy;
+    use crate::eth_rpc_client::health::{
+        observe_call, provider_health, MAX_CONSECUTIVE_FAILURES, UNHEALTHY_PROVIDER_COOLDOWN,
+    };
+    use crate::eth_rpc_client::providers::{RpcNodeProvider, RpcProvider};
+    use crate::eth_rpc_client::{EthRpcClient, MultiCallResults};
+    use crate::lifecycle::EthereumNetwork;
+    use ic_cdk::api::call::RejectionCode;
+
+    const URL: &str = "https://rpc.example.org";
+
+    #[test]
+    fn should_avoid_provider_after_consecutive_failures() {
+        for i in 0..MAX_CONSECUTIVE_FAILURES {
+            assert!(provider_health(URL).is_available(i as u64));
+            observe_call(URL, &failure(), i as u64, i as u64 + 1);
+        }
+
+        let health = provider_health(URL);
+        let cooldown = UNHEALTHY_PROVIDER_COOLDOWN.as_nanos() as u64;
+        assert_eq!(health.consecutive_failures, MAX_CONSECUTIVE_FAILURES);
+        assert!(!health.is_available(MAX_CONSECUTIVE_FAILURES as u64));
+        assert!(!health.is_available(cooldown));
+        assert!(health.is_available(MAX_CONSECUTIVE_FAILURES as u64 + cooldown));
+    }
+
+    #[test]
+    fn should_reset_failures_on_success() {
+        observe_call(URL, &failure(), 0, 1);
+        observe_call(URL, &failure(), 1, 2);
+        observe_call(URL, &success(), 2, 3);
+        observe_call(URL, &failure(), 3, 4);
+
+        let health = provider_health(URL);
+        assert_eq!(health.consecutive_failures, 1);
+        assert_eq!(health.unhealthy_until, None);
+        assert_eq!(health.success_rate(), Some(0.25));
+    }
+
+    #[test]
+    fn should_compute_success_rate_and_latency_over_recent_calls() {
+        assert_eq!(provider_health(URL).success_rate(), None);
+
+        observe_call(URL, &failure(), 0, 1_000);
+        for _ in 0..20 {
+            observe_call(URL, &success(), 0, 3_000);
+        }
+
+        let health = provider_health(URL);
+        assert_eq!(health.success_rate(), Some(1.0));
+        assert_eq!(
+            health.average_latency(),
+            Some(std::time::Duration::from_nanos(3_000))
+        );
+    }
+
+    #[test]
+    fn should_not_count_too_large_responses_as_failures() {
+        let too_large: HttpOutcallResult<JsonRpcResult<u64>> = Err(HttpOutcallError::IcError {
+            code: RejectionCode::SysFatal,
+            message: "Http body exceeds size limit of 2000000 bytes.".to_string(),
+        });
+        for _ in 0..MAX_CONSECUTIVE_FAILURES {
+            observe_call(URL, &too_large, 0, 1);
+        }
+
+        let health = provider_health(URL);
+        assert_eq!(health.consecutive_failures, 0);
+        assert_eq!(health.success_rate(), None);
+    }
+
+    #[test]
+    fn should_only_count_provider_side_json_rpc_errors_as_failures() {
+        let json_rpc_error = |code, message: &str| -> HttpOutcallResult<JsonRpcResult<u64>> {
+            Ok(JsonRpcResult::Error {
+                code,
+                message: message.to_string(),
+            })
+        };
+        for _ in 0..MAX_CONSECUTIVE_FAILURES {
+            observe_call(URL, &json_rpc_error(3, "execution reverted"), 0, 1);
+            observe_call(
+                URL,
+                &json_rpc_error(-32602, "block range too large"),
+                0,
+                1,
+            );
+        }
+        assert_eq!(provider_health(URL).consecutive_failures, 0);
+
+        observe_call(URL, &json_rpc_error(-32005, "limit exceeded"), 0, 1);
+        observe_call(URL, &json_rpc_error(-32603, "internal error"), 0, 1);
+        observe_call(
+            URL,
+            &json_rpc_error(-32000, "Too many requests, rate limit reached"),
+            0,
+            1,
+        );
+        assert_eq!(provider_health(URL).consecutive_failures, 3);
+    }
+
+    #[test]
+    fn should_not_call_unhealthy_providers_unless_all_are_unhealthy() {
+        const OTHER_URL: &str = "https://rpc.example.com";
+        let client = EthRpcClient::new(
+            EthereumNetwork::Sepolia,
+            vec![
+                RpcProvider::from(URL.to_string()),
+                RpcProvider::from(OTHER_URL.to_string()),
+            ],
+        );
+        for i in 0..MAX_CONSECUTIVE_FAILURES as u64 {
+            observe_call(URL, &failure(), i, i + 1);
+        }
+
+        let (called, unhealthy) = client.partition_available_providers(10);
+        assert_eq!(called, vec![provider(OTHER_URL)]);
+        assert_eq!(unhealthy, vec![provider(URL)]);
+
+        let (called, unhealthy) = EthRpcClient::new(
+            EthereumNetwork::Sepolia,
+            vec![RpcProvider::from(URL.to_string())],
+        )
+        .partition_available_providers(10);
+        assert_eq!(called, vec![provider(URL)]);
+        assert_eq!(unhealthy, vec![]);
+    }
+
+    #[test]
+    fn should_reduce_among_called_providers_when_every_result_is_expected() {
+        let results = || -> MultiCallResults<u64> {
+            MultiCallResults::from_non_empty_iter(vec![
+                (provider("https://rpc1.example.org"), success()),
+                (provider("https://rpc2.example.org"), success()),
+                (
+                    provider("https://rpc3.example.org"),
+                    Err(HttpOutcallError::UnhealthyProvider),
+                ),
+            ])
+        };
+
+        for strategy in [
+            ConsensusStrategy::Equality,
+            ConsensusStrategy::StrictMajority,
+        ] {
+            assert_eq!(
+                results().reduce_with_strategy("eth_getLogs", strategy, |value| *value),
+                Ok(1)
+            );
+        }
+        assert_eq!(
+            results().reduce_with_strategy(
+                "eth_getLogs",
+                ConsensusStrategy::Threshold { min: 2 },
+                |value| *value
+            ),
+            Ok(1)
+        );
+        assert!(results()
+            .reduce_with_strategy(
+                "eth_getLogs",
+                ConsensusStrategy::Threshold { min: 3 },
+                |value| *value
+            )
+            .is_err());
+    }
+
+    fn provider(url: &str) -> RpcNodeProvider {
+        RpcNodeProvider::Custom(RpcProvider::from(url.to_string()))
+    }
+
+    fn success() -> HttpOutcallResult<JsonRpcResult<u64>> {
+        Ok(JsonRpcResult::Result(1))
+    }
+
+    fn failure() -> HttpOutcallResult<JsonRpcResult<u64>> {
+        Err(HttpOutcallError::InvalidHttpJsonRpcResponse {
+            status: 500,
+            body: "Internal Server Error".to_string(),
+            parsing_error: None,
+        })
+    }
+}
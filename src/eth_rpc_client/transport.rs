@@ -0,0 +1,337 @@
+//! Transports sending the JSON-RPC requests to the providers.
+//!
+//! Requests are either sent directly with HTTPS outcalls,
+//! or forwarded to an EVM RPC canister, which makes the HTTPS outcalls on behalf of the minter.
+//! The transport is chosen per deployment by an upgrade, and is transparent to the callers of [`EthRpcClient`](super::EthRpcClient).
+
+#[cfg(test)]
+mod tests;
+
+use candid::{CandidType, Deserialize, Nat, Principal};
+use futures::future::LocalBoxFuture;
+use ic_cdk::api::call::{call_with_payment128, CallResult, RejectionCode};
+use ic_cdk::api::management_canister::http_request::{
+    CanisterHttpRequestArgument, HttpHeader, HttpResponse,
+};
+use minicbor::{Decode, Encode};
+use num_traits::ToPrimitive;
+
+/// Sends HTTP requests to the JSON-RPC providers.
+pub trait RpcTransport {
+    /// Sends the request, paying for it with the given cycles.
+    fn http_request(
+        &self,
+        request: CanisterHttpRequestArgument,
+        cycles: u128,
+    ) -> LocalBoxFuture<'_, CallResult<HttpResponse>>;
+}
+
+/// The transport of the JSON-RPC requests of a deployment.
+#[derive(CandidType, Deserialize, Clone, Debug, Default, Encode, Decode, PartialEq, Eq)]
+pub enum RpcTransportConfig {
+    /// HTTPS outcalls made by the minter.
+    #[n(0)]
+    #[default]
+    HttpOutcall,
+    /// Inter-canister calls to the `request` method of an EVM RPC canister.
+    #[n(1)]
+    EvmRpcCanister {
+        #[cbor(n(0), with = "crate::cbor::principal")]
+        canister_id: Principal,
+    },
+}
+
+impl RpcTransport for RpcTransportConfig {
+    fn http_request(
+        &self,
+        request: CanisterHttpRequestArgument,
+        cycles: u128,
+    ) -> LocalBoxFuture<'_, CallResult<HttpResponse>> {
+        match self {
+            RpcTransportConfig::HttpOutcall => HttpOutcall.http_request(request, cycles),
+            RpcTransportConfig::EvmRpcCanister { canister_id } => {
+                let transport = EvmRpcCanister {
+                    canister_id: *canister_id,
+                };
+                Box::pin(async move { transport.http_request(request, cycles).await })
+            }
+        }
+    }
+}
+
+/// Sends the requests with HTTPS outcalls.
+pub struct HttpOutcall;
+
+impl RpcTransport for HttpOutcall {
+    fn http_request(
+        &self,
+        request: CanisterHttpRequestArgument,
+        cycles: u128,
+    ) -> LocalBoxFuture<'_, CallResult<HttpResponse>> {
+        Box::pin(async move {
+            call_with_payment128(
+                Principal::management_canister(),
+                "http_request",
+                (request,),
+                cycles,
+            )
+            .await
+            .map(|(response,)| response)
+        })
+    }
+}
+
+/// The maximum multiple of the attached cycles paid when retrying a request
+/// for which the EVM RPC canister expects more cycles.
+const MAX_RETRY_CYCLES_MULTIPLIER: u128 = 4;
+
+/// Forwards the requests to an EVM RPC canister.
+///
+/// The response transform of the request is not applied, since the EVM RPC canister uses its own.
+/// If the attached cycles do not cover the fees of the EVM RPC canister,
+/// the request is sent again once with the expected cycles,
+/// unless they exceed [`MAX_RETRY_CYCLES_MULTIPLIER`] times the attached cycles.
+pub struct EvmRpcCanister {
+    pub canister_id: Principal,
+}
+
+/// The `request` method of an EVM RPC canister.
+trait EvmRpc {
+    fn request<'a>(
+        &'a self,
+        request: &'a EvmRpcRequest,
+        cycles: u128,
+    ) -> LocalBoxFuture<'a, CallResult<RequestResult>>;
+}
+
+impl EvmRpc for EvmRpcCanister {
+    fn request<'a>(
+        &'a self,
+        request: &'a EvmRpcRequest,
+        cycles: u128,
+    ) -> LocalBoxFuture<'a, CallResult<RequestResult>> {
+        Box::pin(async move {
+            call_with_payment128(
+                self.canister_id,
+                "request",
+                (
+                    request.service.clone(),
+                    request.json.clone(),
+                    request.max_response_bytes,
+                ),
+                cycles,
+            )
+            .await
+            .map(|(result,)| result)
+        })
+    }
+}
+
+impl RpcTransport for EvmRpcCanister {
+    fn http_request(
+        &self,
+        request: CanisterHttpRequestArgument,
+        cycles: u128,
+    ) -> LocalBoxFuture<'_, CallResult<HttpResponse>> {
+        Box::pin(request_with_cycles_retry(
+            self,
+            EvmRpcRequest::from(request),
+            cycles,
+        ))
+    }
+}
+
+async fn request_with_cycles_retry<R: EvmRpc>(
+    rpc: &R,
+    request: EvmRpcRequest,
+    cycles: u128,
+) -> CallResult<HttpResponse> {
+    let mut result = rpc.request(&request, cycles).await?;
+    if let Some(expected) = retry_cycles(&result, cycles) {
+        result = rpc.request(&request, expected).await?;
+    }
+    into_http_response(result, request.json_rpc_id())
+}
+
+/// The cycles to attach when sending the request again, if the EVM RPC canister expects
+/// more cycles than attached, but no more than [`MAX_RETRY_CYCLES_MULTIPLIER`] times the attached cycles.
+fn retry_cycles(result: &RequestResult, cycles: u128) -> Option<u128> {
+    match result {
+        RequestResult::Err(RpcError::ProviderError(ProviderError::TooFewCycles {
+            expected,
+            ..
+        })) => expected
+            .0
+            .to_u128()
+            .filter(|expected| *expected <= cycles.saturating_mul(MAX_RETRY_CYCLES_MULTIPLIER)),
+        _ => None,
+    }
+}
+
+/// The arguments of the `request` method of the EVM RPC canister.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct EvmRpcRequest {
+    service: RpcService,
+    json: String,
+    max_response_bytes: u64,
+}
+
+impl From<CanisterHttpRequestArgument> for EvmRpcRequest {
+    fn from(request: CanisterHttpRequestArgument) -> Self {
+        // The EVM RPC canister sets the content type itself.
+        let headers: Vec<_> = request
+            .headers
+            .into_iter()
+            .filter(|header| !header.name.eq_ignore_ascii_case("Content-Type"))
+            .collect();
+        Self {
+            service: RpcService::Custom(RpcApi {
+                url: request.url,
+                headers: (!headers.is_empty()).then_some(headers),
+            }),
+            json: String::from_utf8_lossy(&request.body.unwrap_or_default()).to_string(),
+            max_response_bytes: request
+                .max_response_bytes
+                .unwrap_or(crate::eth_rpc::MAX_PAYLOAD_SIZE),
+        }
+    }
+}
+
+impl EvmRpcRequest {
+    /// The ID of the JSON-RPC request, which the reply must echo.
+    fn json_rpc_id(&self) -> serde_json::Value {
+        serde_json::from_str::<serde_json::Value>(&self.json)
+            .ok()
+            .and_then(|mut json| json.get_mut("id").map(serde_json::Value::take))
+            .unwrap_or_default()
+    }
+}
+
+/// Converts the result of the EVM RPC canister into the response of an HTTPS outcall,
+/// so that errors are handled as if the minter made the outcall itself.
+fn into_http_response(result: RequestResult, id: serde_json::Value) -> CallResult<HttpResponse> {
+    let error = match result {
+        RequestResult::Ok(body) => return Ok(ok_response(body)),
+        RequestResult::Err(error) => error,
+    };
+    match error {
+        // Keep the code of the IC error, so that responses that are too large are retried with a larger size.
+        RpcError::HttpOutcallError(HttpOutcallError::IcError { code, message }) => {
+            Err((code, message))
+        }
+        // Let the minter parse the response and report it as invalid.
+        RpcError::HttpOutcallError(HttpOutcallError::InvalidHttpJsonRpcResponse {
+            status,
+            body,
+            ..
+        }) => Ok(HttpResponse {
+            status: Nat::from(u64::from(status)),
+            headers: vec![],
+            body: body.into_bytes(),
+        }),
+        // The provider replied with an error, which is handled like any JSON-RPC error of the provider.
+        RpcError::JsonRpcError(JsonRpcError { code, message }) => Ok(ok_response(
+            serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": {"code": code, "message": message},
+            })
+            .to_string(),
+        )),
+        // The EVM RPC canister refuses the request, which fails the same way when sent again,
+        // including when it still expects more cycles once the request was retried.
+        error @ RpcError::ProviderError(
+            ProviderError::TooFewCycles { .. }
+            | ProviderError::MissingRequiredProvider
+            | ProviderError::ProviderNotFound
+            | ProviderError::NoPermission
+            | ProviderError::InvalidRpcConfig(_),
+        )
+        | error @ RpcError::ValidationError(
+            ValidationError::Custom(_)
+            | ValidationError::HostNotAllowed(_)
+            | ValidationError::UrlParseError(_)
+            | ValidationError::InvalidHex(_)
+            | ValidationError::CredentialPathNotAllowed
+            | ValidationError::CredentialHeaderNotAllowed,
+        ) => Err((
+            RejectionCode::CanisterError,
+            format!("EVM RPC canister error: {error:?}"),
+        )),
+    }
+}
+
+fn ok_response(body: String) -> HttpResponse {
+    HttpResponse {
+        status: Nat::from(200_u64),
+        headers: vec![],
+        body: body.into_bytes(),
+    }
+}
+
+#[derive(CandidType, Clone, Debug, PartialEq, Eq)]
+enum RpcService {
+    Custom(RpcApi),
+}
+
+#[derive(CandidType, Clone, Debug, PartialEq, Eq)]
+struct RpcApi {
+    url: String,
+    headers: Option<Vec<HttpHeader>>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq, Eq)]
+enum RequestResult {
+    Ok(String),
+    Err(RpcError),
+}
+
+// The error types mirror the candid interface of the EVM RPC canister,
+// including the variants of its former versions, so that any of its errors can be decoded.
+#[allow(clippy::enum_variant_names)]
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq, Eq)]
+enum RpcError {
+    ProviderError(ProviderError),
+    HttpOutcallError(HttpOutcallError),
+    JsonRpcError(JsonRpcError),
+    ValidationError(ValidationError),
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq, Eq)]
+enum ProviderError {
+    TooFewCycles { expected: Nat, received: Nat },
+    InvalidRpcConfig(String),
+    MissingRequiredProvider,
+    ProviderNotFound,
+    NoPermission,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq, Eq)]
+enum HttpOutcallError {
+    IcError {
+        code: RejectionCode,
+        message: String,
+    },
+    InvalidHttpJsonRpcResponse {
+        status: u16,
+        body: String,
+        #[serde(rename = "parsingError")]
+        parsing_error: Option<String>,
+    },
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq, Eq)]
+struct JsonRpcError {
+    code: i64,
+    message: String,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq, Eq)]
+enum ValidationError {
+    Custom(String),
+    HostNotAllowed(String),
+    UrlParseError(String),
+    InvalidHex(String),
+    CredentialPathNotAllowed,
+    CredentialHeaderNotAllowed,
+}
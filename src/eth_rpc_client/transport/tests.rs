@@ -0,0 +1,329 @@
+use crate::eth_rpc::{JsonRpcReply, JsonRpcResult};
+use crate::eth_rpc_client::transport::{
+    into_http_response, request_with_cycles_retry, EvmRpc, EvmRpcRequest, HttpOutcallError,
+    JsonRpcError, ProviderError, RequestResult, RpcApi, RpcError, RpcService, RpcTransportConfig,
+    ValidationError, MAX_RETRY_CYCLES_MULTIPLIER,
+};
+use candid::{Nat, Principal};
+use futures::future::LocalBoxFuture;
+use futures::FutureExt;
+use ic_cdk::api::call::{CallResult, RejectionCode};
+use ic_cdk::api::management_canister::http_request::{
+    CanisterHttpRequestArgument, HttpHeader, HttpMethod, HttpResponse,
+};
+use num_traits::ToPrimitive;
+
+mod evm_rpc_request {
+    use super::*;
+
+    #[test]
+    fn should_forward_url_payload_and_headers_except_content_type() {
+        let request = CanisterHttpRequestArgument {
+            url: "https://rpc.example.org".to_string(),
+            max_response_bytes: Some(2048),
+            method: HttpMethod::POST,
+            headers: vec![
+                header("Content-Type", "application/json"),
+                header("Authorization", "Bearer secret"),
+            ],
+            body: Some(br#"{"jsonrpc":"2.0","method":"eth_blockNumber","id":1}"#.to_vec()),
+            transform: None,
+        };
+
+        assert_eq!(
+            EvmRpcRequest::from(request),
+            EvmRpcRequest {
+                service: RpcService::Custom(RpcApi {
+                    url: "https://rpc.example.org".to_string(),
+                    headers: Some(vec![header("Authorization", "Bearer secret")]),
+                }),
+                json: r#"{"jsonrpc":"2.0","method":"eth_blockNumber","id":1}"#.to_string(),
+                max_response_bytes: 2048,
+            }
+        );
+    }
+
+    #[test]
+    fn should_omit_empty_headers() {
+        let request = CanisterHttpRequestArgument {
+            url: "https://rpc.example.org".to_string(),
+            max_response_bytes: None,
+            method: HttpMethod::POST,
+            headers: vec![header("Content-Type", "application/json")],
+            body: None,
+            transform: None,
+        };
+
+        assert_eq!(
+            EvmRpcRequest::from(request).service,
+            RpcService::Custom(RpcApi {
+                url: "https://rpc.example.org".to_string(),
+                headers: None,
+            })
+        );
+    }
+
+    #[test]
+    fn should_read_json_rpc_id_of_request() {
+        let request = |json: &str| EvmRpcRequest {
+            service: RpcService::Custom(RpcApi {
+                url: "https://rpc.example.org".to_string(),
+                headers: None,
+            }),
+            json: json.to_string(),
+            max_response_bytes: 2048,
+        };
+
+        assert_eq!(
+            request(r#"{"jsonrpc":"2.0","method":"eth_blockNumber","id":7}"#).json_rpc_id(),
+            serde_json::json!(7)
+        );
+        assert_eq!(request("not json").json_rpc_id(), serde_json::Value::Null);
+    }
+}
+
+mod into_http_response {
+    use super::*;
+
+    #[test]
+    fn should_return_body_as_successful_response() {
+        assert_eq!(
+            into_http_response(
+                RequestResult::Ok(r#"{"result":"0x1"}"#.to_string()),
+                serde_json::json!(1)
+            ),
+            Ok(HttpResponse {
+                status: Nat::from(200_u64),
+                headers: vec![],
+                body: br#"{"result":"0x1"}"#.to_vec(),
+            })
+        );
+    }
+
+    #[test]
+    fn should_keep_ic_errors_to_adjust_response_size() {
+        let message = "Http body exceeds size limit of 2048 bytes.".to_string();
+
+        assert_eq!(
+            into_http_response(
+                RequestResult::Err(RpcError::HttpOutcallError(HttpOutcallError::IcError {
+                    code: RejectionCode::SysFatal,
+                    message: message.clone(),
+                })),
+                serde_json::json!(1)
+            ),
+            Err((RejectionCode::SysFatal, message))
+        );
+    }
+
+    #[test]
+    fn should_return_invalid_responses_with_their_status() {
+        assert_eq!(
+            into_http_response(
+                RequestResult::Err(RpcError::HttpOutcallError(
+                    HttpOutcallError::InvalidHttpJsonRpcResponse {
+                        status: 503,
+                        body: "Service Unavailable".to_string(),
+                        parsing_error: None,
+                    }
+                )),
+                serde_json::json!(1)
+            ),
+            Ok(HttpResponse {
+                status: Nat::from(503_u64),
+                headers: vec![],
+                body: b"Service Unavailable".to_vec(),
+            })
+        );
+    }
+
+    #[test]
+    fn should_reply_with_json_rpc_errors_of_provider() {
+        let response = into_http_response(
+            RequestResult::Err(RpcError::JsonRpcError(JsonRpcError {
+                code: -32005,
+                message: "rate limit exceeded".to_string(),
+            })),
+            serde_json::json!(1),
+        )
+        .unwrap();
+
+        assert_eq!(response.status, Nat::from(200_u64));
+        assert_eq!(
+            serde_json::from_slice::<JsonRpcReply<String>>(&response.body).unwrap(),
+            JsonRpcReply {
+                id: 1,
+                jsonrpc: "2.0".to_string(),
+                result: JsonRpcResult::Error {
+                    code: -32005,
+                    message: "rate limit exceeded".to_string(),
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn should_reject_other_errors() {
+        for error in [
+            RpcError::ProviderError(ProviderError::TooFewCycles {
+                expected: Nat::from(2_000_u32),
+                received: Nat::from(1_000_u32),
+            }),
+            RpcError::ProviderError(ProviderError::InvalidRpcConfig(
+                "invalid config".to_string(),
+            )),
+            RpcError::ProviderError(ProviderError::MissingRequiredProvider),
+            RpcError::ProviderError(ProviderError::ProviderNotFound),
+            RpcError::ProviderError(ProviderError::NoPermission),
+            RpcError::ValidationError(ValidationError::Custom("custom".to_string())),
+            RpcError::ValidationError(ValidationError::HostNotAllowed("host".to_string())),
+            RpcError::ValidationError(ValidationError::UrlParseError("url".to_string())),
+            RpcError::ValidationError(ValidationError::InvalidHex("0xZ".to_string())),
+            RpcError::ValidationError(ValidationError::CredentialPathNotAllowed),
+            RpcError::ValidationError(ValidationError::CredentialHeaderNotAllowed),
+        ] {
+            assert_eq!(
+                into_http_response(RequestResult::Err(error.clone()), serde_json::json!(1)),
+                Err((
+                    RejectionCode::CanisterError,
+                    format!("EVM RPC canister error: {error:?}")
+                ))
+            );
+        }
+    }
+}
+
+mod request_with_cycles_retry {
+    use super::*;
+    use std::cell::RefCell;
+
+    const CYCLES: u128 = 1_000_000;
+
+    #[test]
+    fn should_send_request_once_with_enough_cycles() {
+        let canister = EvmRpcStandIn::expecting(CYCLES);
+
+        assert_eq!(canister.request_with_retry(CYCLES), Ok(response()));
+        assert_eq!(canister.attached_cycles(), vec![CYCLES]);
+    }
+
+    #[test]
+    fn should_retry_once_with_expected_cycles() {
+        let expected = 3 * CYCLES;
+        let canister = EvmRpcStandIn::expecting(expected);
+
+        assert_eq!(canister.request_with_retry(CYCLES), Ok(response()));
+        assert_eq!(canister.attached_cycles(), vec![CYCLES, expected]);
+    }
+
+    #[test]
+    fn should_not_retry_when_expected_cycles_exceed_cap() {
+        let expected = MAX_RETRY_CYCLES_MULTIPLIER * CYCLES + 1;
+        let canister = EvmRpcStandIn::expecting(expected);
+
+        let (code, message) = canister.request_with_retry(CYCLES).unwrap_err();
+
+        assert_eq!(code, RejectionCode::CanisterError);
+        assert!(message.contains("TooFewCycles"));
+        assert_eq!(canister.attached_cycles(), vec![CYCLES]);
+    }
+
+    #[test]
+    fn should_not_retry_when_expected_cycles_overflow() {
+        let canister = EvmRpcStandIn {
+            expected_cycles: Nat::from(u128::MAX) * Nat::from(2_u32),
+            attached_cycles: RefCell::default(),
+        };
+
+        assert!(canister.request_with_retry(CYCLES).is_err());
+        assert_eq!(canister.attached_cycles(), vec![CYCLES]);
+    }
+
+    /// A local stand-in for the EVM RPC canister, which rejects requests with fewer cycles than expected.
+    struct EvmRpcStandIn {
+        expected_cycles: Nat,
+        attached_cycles: RefCell<Vec<u128>>,
+    }
+
+    impl EvmRpcStandIn {
+        fn expecting(cycles: u128) -> Self {
+            Self {
+                expected_cycles: Nat::from(cycles),
+                attached_cycles: RefCell::default(),
+            }
+        }
+
+        fn request_with_retry(&self, cycles: u128) -> CallResult<HttpResponse> {
+            request_with_cycles_retry(self, request(), cycles)
+                .now_or_never()
+                .expect("the stand-in should reply immediately")
+        }
+
+        fn attached_cycles(&self) -> Vec<u128> {
+            self.attached_cycles.borrow().clone()
+        }
+    }
+
+    impl EvmRpc for EvmRpcStandIn {
+        fn request<'a>(
+            &'a self,
+            request: &'a EvmRpcRequest,
+            cycles: u128,
+        ) -> LocalBoxFuture<'a, CallResult<RequestResult>> {
+            self.attached_cycles.borrow_mut().push(cycles);
+            let result = if !matches!(self.expected_cycles.0.to_u128(), Some(expected) if cycles >= expected)
+            {
+                RequestResult::Err(RpcError::ProviderError(ProviderError::TooFewCycles {
+                    expected: self.expected_cycles.clone(),
+                    received: Nat::from(cycles),
+                }))
+            } else {
+                RequestResult::Ok(request.json.clone())
+            };
+            Box::pin(async move { Ok(result) })
+        }
+    }
+
+    fn request() -> EvmRpcRequest {
+        EvmRpcRequest {
+            service: RpcService::Custom(RpcApi {
+                url: "https://rpc.example.org".to_string(),
+                headers: None,
+            }),
+            json: r#"{"result":"0x1"}"#.to_string(),
+            max_response_bytes: 2048,
+        }
+    }
+
+    fn response() -> HttpResponse {
+        HttpResponse {
+            status: Nat::from(200_u64),
+            headers: vec![],
+            body: br#"{"result":"0x1"}"#.to_vec(),
+        }
+    }
+}
+
+#[test]
+fn should_encode_transport_config() {
+    for config in [
+        RpcTransportConfig::HttpOutcall,
+        RpcTransportConfig::EvmRpcCanister {
+            canister_id: Principal::from_text("7hfb6-caaaa-aaaar-qadga-cai").unwrap(),
+        },
+    ] {
+        let mut buf = vec![];
+        minicbor::encode(&config, &mut buf).unwrap();
+        assert_eq!(
+            minicbor::decode::<RpcTransportConfig>(&buf).unwrap(),
+            config
+        );
+    }
+}
+
+fn header(name: &str, value: &str) -> HttpHeader {
+    HttpHeader {
+        name: name.to_string(),
+        value: value.to_string(),
+    }
+}
@@ -1,65 +1,67 @@
-use crate::eth_rpc_error::{sanitize_send_raw_transaction_result, Parser};
+use crate::eth_rpc_error::{ErrorParser, Parser, SendRawTransactionError};
 
 #[test]
-fn should_sanitize_ok_response() {
-    let mut raw_response =
-        br#"{"id":1,"jsonrpc":"2.0","result":"0xcfa48c44dc89d18a898a42b4a5b02b6847a3c2019507d5571a481751c7a2f353"}"#.to_vec();
-    check_sanitize_send_raw_transaction_result(&mut raw_response, sanitized_ok_response());
-}
-
-#[test]
-fn should_ignore_already_known_error() {
-    let mut raw_response =
-        br#"{"jsonrpc": "2.0", "error": {"code": -32000, "message": "ALREADY_EXISTS: already known"}, "id": 1}"#.to_vec();
-    check_sanitize_send_raw_transaction_result(&mut raw_response, sanitized_ok_response());
+fn should_parse_already_known_error() {
+    check_parse(
+        -32_000,
+        "ALREADY_EXISTS: already known",
+        Some(SendRawTransactionError::AlreadyKnown),
+    );
+    check_parse(
+        -32_000,
+        "known transaction: 0x1234",
+        Some(SendRawTransactionError::AlreadyKnown),
+    );
 }
 
 #[test]
-fn should_sanitize_insufficient_funds_error() {
-    let mut raw_response =
-        br#"{"jsonrpc": "2.0", "error": {"code": -32000, "message": "out of gas"}, "id": 1}"#
-            .to_vec();
-    let sanitized_error = br#"{"id":1,"jsonrpc":"2.0","result":"InsufficientFunds"}"#.to_vec();
-    check_sanitize_send_raw_transaction_result(&mut raw_response, sanitized_error);
+fn should_parse_insufficient_funds_error() {
+    check_parse(
+        -32_000,
+        "insufficient funds for gas * price + value",
+        Some(SendRawTransactionError::InsufficientFunds),
+    );
+    check_parse(
+        -32_000,
+        "out of gas",
+        Some(SendRawTransactionError::InsufficientFunds),
+    );
 }
 
 #[test]
-fn should_keep_unknown_error_and_normalize_response() {
-    let mut raw_response =
-        br#"{"jsonrpc": "2.0", "error": {"code": -32000, "message": "weird unknown error"}, "id": 1}"#
-            .to_vec();
-    let mut other_raw_response = br#"{"error": {"code": -32000, "message": "weird unknown error"}, "jsonrpc": "2.0", "id": 1}"#
-        .to_vec();
-    let expected_response =
-        br#"{"id":1,"jsonrpc":"2.0","error":{"code":-32000,"message":"weird unknown error"}}"#
-            .to_vec();
-
-    check_sanitize_send_raw_transaction_result(&mut raw_response, &expected_response);
-    check_sanitize_send_raw_transaction_result(&mut other_raw_response, &expected_response);
+fn should_parse_nonce_errors() {
+    check_parse(
+        -32_000,
+        "nonce too low",
+        Some(SendRawTransactionError::NonceTooLow),
+    );
+    check_parse(
+        -32_001,
+        "Nonce too low",
+        Some(SendRawTransactionError::NonceTooLow),
+    );
+    check_parse(
+        -32_000,
+        "nonce too high",
+        Some(SendRawTransactionError::NonceTooHigh),
+    );
+    check_parse(
+        -32_006,
+        "Nonce too high",
+        Some(SendRawTransactionError::NonceTooHigh),
+    );
 }
 
 #[test]
-fn should_not_modify_response_when_deserialization_fails() {
-    let mut raw_response = br#"{"jsonrpc": "2.0", "unexpected": "value", "id": 1}"#.to_vec();
-    let unmodified_response = raw_response.clone();
-    check_sanitize_send_raw_transaction_result(&mut raw_response, unmodified_response);
+fn should_not_parse_unknown_error() {
+    check_parse(-32_000, "weird unknown error", None);
+    check_parse(-32_603, "nonce too low", None);
 }
 
-fn check_sanitize_send_raw_transaction_result<T: AsRef<[u8]>>(
-    raw_response: &mut Vec<u8>,
-    expected: T,
-) {
-    sanitize_send_raw_transaction_result(raw_response, Parser::new());
-    let expected_bytes = expected.as_ref();
+fn check_parse(code: i64, message: &str, expected: Option<SendRawTransactionError>) {
     assert_eq!(
-        raw_response,
-        expected_bytes,
-        "{:?} != {:?}",
-        String::from_utf8_lossy(raw_response),
-        String::from_utf8_lossy(expected_bytes)
+        Parser::new().try_parse_send_raw_transaction_error(code, message.to_string()),
+        expected,
+        "unexpected result for error {code}: {message}"
     );
 }
-
-fn sanitized_ok_response() -> Vec<u8> {
-    br#"{"id":1,"jsonrpc":"2.0","result":"Ok"}"#.to_vec()
-}
@@ -0,0 +1,167 @@
+//! Deterministic generation of token traits.
+//!
+//! Every minted token is assigned one value per trait type of the collection's
+//! [`GeneratorConfig`]. The drawn values only depend on the configuration, on the mint event,
+//! and on the seed recorded in the event log,
+//! so that every replica, and every replay of the event log, derives exactly the same traits.
+
+#[cfg(test)]
+mod tests;
+
+use crate::eth_logs::MintEvent;
+use crate::svg::Canvas;
+use candid::{CandidType, Deserialize};
+use minicbor::{Decode, Encode};
+use std::collections::BTreeSet;
+
+/// Domain separator of the hash used to draw trait values,
+/// so that the draws cannot be confused with any other hash computed by the minter.
+const DRAW_DOMAIN_SEPARATOR: &[u8] = b"iluvatar-generator-draw";
+
+/// Describes the traits of a generative collection.
+#[derive(CandidType, Deserialize, Clone, Debug, Default, Encode, Decode, PartialEq, Eq)]
+pub struct GeneratorConfig {
+    /// The trait types of the collection, in the order in which they are drawn.
+    #[n(0)]
+    pub trait_types: Vec<TraitType>,
+    /// The dimensions of the token images.
+    #[n(1)]
+    pub canvas: Option<Canvas>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, Encode, Decode, PartialEq, Eq)]
+pub struct TraitType {
+    /// The name of the trait type, e.g. "Background".
+    #[n(0)]
+    pub name: String,
+    /// The values that the trait type can take.
+    #[n(1)]
+    pub values: Vec<TraitValue>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, Encode, Decode, PartialEq, Eq)]
+pub struct TraitValue {
+    /// The name of the value, e.g. "Blue".
+    #[n(0)]
+    pub name: String,
+    /// The weight of this value relative to the other values of the same trait type.
+    /// A value with a weight of zero is never drawn.
+    #[n(1)]
+    pub weight: u32,
+    /// The SVG fragment drawn on the token image when this value is drawn.
+    #[n(2)]
+    pub layer: Option<String>,
+    /// The key of the uploaded image asset drawn on the token image when this value is drawn.
+    #[n(3)]
+    pub asset: Option<String>,
+}
+
+/// A trait value assigned to a token.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Attribute {
+    pub trait_type: String,
+    pub value: String,
+}
+
+impl GeneratorConfig {
+    /// Returns the keys of the assets drawn by the values of the configuration.
+    pub fn asset_keys(&self) -> BTreeSet<&str> {
+        self.trait_types
+            .iter()
+            .flat_map(|trait_type| &trait_type.values)
+            .filter_map(|value| value.asset.as_deref())
+            .collect()
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        let mut trait_type_names = BTreeSet::new();
+        for trait_type in &self.trait_types {
+            if trait_type.name.trim().is_empty() {
+                return Err("trait type name cannot be empty".to_string());
+            }
+            if !trait_type_names.insert(trait_type.name.as_str()) {
+                return Err(format!("duplicate trait type '{}'", trait_type.name));
+            }
+            if trait_type.total_weight() == 0 {
+                return Err(format!(
+                    "trait type '{}' has no value with a positive weight",
+                    trait_type.name
+                ));
+            }
+            let mut value_names = BTreeSet::new();
+            for value in &trait_type.values {
+                if !value_names.insert(value.name.as_str()) {
+                    return Err(format!(
+                        "duplicate value '{}' for trait type '{}'",
+                        value.name, trait_type.name
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl TraitType {
+    fn total_weight(&self) -> u64 {
+        self.values.iter().map(|value| value.weight as u64).sum()
+    }
+
+    /// Selects a value with a probability proportional to its weight,
+    /// given a uniformly distributed `draw`.
+    fn select(&self, draw: u64) -> Option<&TraitValue> {
+        let total_weight = self.total_weight();
+        if total_weight == 0 {
+            return None;
+        }
+        let mut target = draw % total_weight;
+        for value in &self.values {
+            let weight = value.weight as u64;
+            if target < weight {
+                return Some(value);
+            }
+            target -= weight;
+        }
+        panic!("BUG: the target {target} should be less than the total weight {total_weight}")
+    }
+}
+
+/// Draws the attributes of the token minted by the given event.
+///
+/// The `seed` is the randomness obtained from the management canister after the event was accepted,
+/// which makes the draws unpredictable before minting.
+/// Tokens minted before seeds were introduced have no seed.
+pub fn generate_attributes(
+    config: &GeneratorConfig,
+    event: &MintEvent,
+    seed: Option<&[u8; 32]>,
+) -> Vec<Attribute> {
+    config
+        .trait_types
+        .iter()
+        .enumerate()
+        .filter_map(|(trait_index, trait_type)| {
+            let draw = draw(event, seed, trait_index as u32);
+            trait_type.select(draw).map(|value| Attribute {
+                trait_type: trait_type.name.clone(),
+                value: value.name.clone(),
+            })
+        })
+        .collect()
+}
+
+fn draw(event: &MintEvent, seed: Option<&[u8; 32]>, trait_index: u32) -> u64 {
+    let mut preimage = Vec::with_capacity(DRAW_DOMAIN_SEPARATOR.len() + 32 + 32 + 32 + 32 + 4);
+    preimage.extend_from_slice(DRAW_DOMAIN_SEPARATOR);
+    if let Some(seed) = seed {
+        preimage.extend_from_slice(seed);
+    }
+    preimage.extend_from_slice(&event.token_id.to_be_bytes());
+    preimage.extend_from_slice(&event.block_number.to_be_bytes());
+    preimage.extend_from_slice(&event.transaction_hash.0);
+    preimage.extend_from_slice(&trait_index.to_be_bytes());
+    let hash = ic_crypto_sha3::Keccak256::hash(preimage);
+    let mut draw = [0u8; 8];
+    draw.copy_from_slice(&hash[..8]);
+    u64::from_be_bytes(draw)
+}
@@ -0,0 +1,131 @@
+use crate::generator::{generate_attributes, GeneratorConfig, TraitType, TraitValue};
+use crate::test_fixtures::mint_event;
+
+mod validate {
+    use super::*;
+
+    #[test]
+    fn should_accept_valid_config() {
+        assert_eq!(config().validate(), Ok(()));
+        assert_eq!(GeneratorConfig::default().validate(), Ok(()));
+    }
+
+    #[test]
+    fn should_reject_duplicate_trait_types() {
+        let mut config = config();
+        let trait_type = config.trait_types[0].clone();
+        config.trait_types.push(trait_type);
+
+        assert!(config
+            .validate()
+            .unwrap_err()
+            .contains("duplicate trait type"));
+    }
+
+    #[test]
+    fn should_reject_duplicate_values() {
+        let mut config = config();
+        let value = config.trait_types[0].values[0].clone();
+        config.trait_types[0].values.push(value);
+
+        assert!(config.validate().unwrap_err().contains("duplicate value"));
+    }
+
+    #[test]
+    fn should_reject_trait_type_without_positive_weight() {
+        let mut config = config();
+        for value in config.trait_types[1].values.iter_mut() {
+            value.weight = 0;
+        }
+
+        assert!(config
+            .validate()
+            .unwrap_err()
+            .contains("no value with a positive weight"));
+    }
+}
+
+mod generate {
+    use super::*;
+
+    #[test]
+    fn should_generate_one_attribute_per_trait_type() {
+        let attributes = generate_attributes(&config(), &mint_event(1, 1), None);
+
+        let trait_types: Vec<_> = attributes.iter().map(|a| a.trait_type.as_str()).collect();
+        assert_eq!(trait_types, vec!["Background", "Eyes"]);
+    }
+
+    #[test]
+    fn should_be_deterministic() {
+        for token_id in 0..100 {
+            assert_eq!(
+                generate_attributes(&config(), &mint_event(token_id, token_id), None),
+                generate_attributes(&config(), &mint_event(token_id, token_id), None)
+            );
+        }
+    }
+
+    #[test]
+    fn should_depend_on_seed() {
+        let config = config();
+        let seeded = |seed: u8| {
+            (0..100)
+                .map(|token_id| {
+                    generate_attributes(&config, &mint_event(token_id, token_id), Some(&[seed; 32]))
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(seeded(1), seeded(1));
+        assert_ne!(seeded(1), seeded(2));
+    }
+
+    #[test]
+    fn should_never_draw_values_with_zero_weight() {
+        let config = config();
+        for token_id in 0..1_000 {
+            let attributes = generate_attributes(&config, &mint_event(token_id, token_id), None);
+            assert_ne!(attributes[1].value, "Laser");
+        }
+    }
+
+    #[test]
+    fn should_draw_values_according_to_their_weight() {
+        let config = config();
+        let blue_count = (0..1_000)
+            .filter(|token_id| {
+                generate_attributes(&config, &mint_event(*token_id, *token_id), None)[0].value
+                    == "Blue"
+            })
+            .count();
+
+        // Blue has a weight of 90 out of 100.
+        assert!((850..=950).contains(&blue_count), "{blue_count}");
+    }
+}
+
+fn config() -> GeneratorConfig {
+    GeneratorConfig {
+        trait_types: vec![
+            TraitType {
+                name: "Background".to_string(),
+                values: vec![value("Blue", 90), value("Gold", 10)],
+            },
+            TraitType {
+                name: "Eyes".to_string(),
+                values: vec![value("Round", 1), value("Laser", 0), value("Sleepy", 1)],
+            },
+        ],
+        canvas: None,
+    }
+}
+
+fn value(name: &str, weight: u32) -> TraitValue {
+    TraitValue {
+        name: name.to_string(),
+        weight,
+        layer: None,
+        asset: None,
+    }
+}
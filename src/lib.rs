@@ -1,23 +1,34 @@
+pub mod abi;
 pub mod address;
+pub mod assets;
 mod cbor;
+pub mod certification;
 pub mod checked_amount;
+pub mod collections;
+pub mod consistency;
 pub mod deposit;
 pub mod endpoints;
 pub mod eth_logs;
 pub mod eth_rpc;
 pub mod eth_rpc_client;
 pub mod eth_rpc_error;
+pub mod generator;
 pub mod guard;
 pub mod lifecycle;
 pub mod logs;
 pub mod management;
 pub mod map;
 pub mod memo;
+pub mod metadata;
 pub mod numeric;
+pub mod reveal;
 mod serde_data;
 pub mod state;
 pub mod storage;
+pub mod svg;
 
+#[cfg(test)]
+mod test_fixtures;
 #[cfg(test)]
 mod tests;
 
@@ -26,7 +37,9 @@ use std::time::Duration;
 
 pub const MAIN_DERIVATION_PATH: Vec<ByteBuf> = vec![];
 pub const SCRAPPING_ETH_LOGS_INTERVAL: Duration = Duration::from_secs(3 * 60);
+pub const RETRY_SKIPPED_BLOCKS_INTERVAL: Duration = Duration::from_secs(60 * 60);
 pub const PROCESS_ETH_RETRIEVE_TRANSACTIONS_INTERVAL: Duration = Duration::from_secs(6 * 60);
 pub const PROCESS_REIMBURSEMENT: Duration = Duration::from_secs(3 * 60);
 pub const PROCESS_ETH_RETRIEVE_TRANSACTIONS_RETRY_INTERVAL: Duration = Duration::from_secs(3 * 60);
+pub const CONSISTENCY_AUDIT_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
 pub const MINT_RETRY_DELAY: Duration = Duration::from_secs(3 * 60);
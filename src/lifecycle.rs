@@ -1,4 +1,6 @@
 //! Module dealing with the lifecycle methods of the ckETH Minter.
+use crate::eth_rpc::MAX_PAYLOAD_SIZE;
+use crate::eth_rpc_client::providers::{validate_providers, RpcProvider};
 use crate::lifecycle::init::InitArg;
 use crate::lifecycle::upgrade::UpgradeArg;
 use candid::{CandidType, Deserialize};
@@ -18,23 +20,97 @@ pub enum MinterArg {
     UpgradeArg(UpgradeArg),
 }
 
-#[derive(
-    CandidType, Clone, Copy, Default, Deserialize, Debug, Eq, PartialEq, Hash, Encode, Decode,
-)]
-#[cbor(index_only)]
+#[derive(CandidType, Clone, Default, Deserialize, Debug, Eq, PartialEq, Hash)]
 pub enum EthereumNetwork {
-    #[n(1)]
     Mainnet,
-    #[n(11155111)]
     #[default]
     Sepolia,
+    Custom(ChainSpec),
+}
+
+/// The description of an EVM chain other than Ethereum Mainnet and Sepolia, e.g., an L2.
+#[derive(CandidType, Clone, Deserialize, Debug, Eq, PartialEq, Hash, Encode, Decode)]
+pub struct ChainSpec {
+    #[n(0)]
+    pub chain_id: u64,
+    /// The name of the chain displayed on the dashboard.
+    #[n(1)]
+    pub name: String,
+    /// The expected size in bytes of a block returned by `eth_getBlockByNumber`,
+    /// used to estimate the size of the HTTP responses.
+    #[n(2)]
+    pub expected_block_size: u64,
+    /// The maximum number of blocks queried by a single `eth_getLogs` request.
+    #[n(3)]
+    pub max_block_spread: u64,
+    /// The JSON-RPC providers, queried in this order.
+    #[cbor(n(4), with = "crate::cbor::rpc_providers")]
+    pub providers: Vec<RpcProvider>,
+}
+
+impl ChainSpec {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.chain_id == 0 {
+            return Err("chain ID cannot be zero".to_string());
+        }
+        if self.name.trim().is_empty() {
+            return Err("chain name cannot be empty".to_string());
+        }
+        if self.expected_block_size == 0 || self.expected_block_size > MAX_PAYLOAD_SIZE {
+            return Err(format!(
+                "expected block size must be between 1 and {MAX_PAYLOAD_SIZE} bytes"
+            ));
+        }
+        if self.max_block_spread == 0 {
+            return Err("max block spread cannot be zero".to_string());
+        }
+        if self.providers.is_empty() {
+            return Err("chain must have at least one provider".to_string());
+        }
+        validate_providers(&self.providers)
+    }
 }
 
 impl EthereumNetwork {
+    /// Returns the network with the values of the headers of its providers redacted.
+    pub fn redacted(self) -> Self {
+        match self {
+            EthereumNetwork::Custom(chain_spec) => EthereumNetwork::Custom(ChainSpec {
+                providers: chain_spec
+                    .providers
+                    .into_iter()
+                    .map(RpcProvider::redacted)
+                    .collect(),
+                ..chain_spec
+            }),
+            network => network,
+        }
+    }
+
     pub fn chain_id(&self) -> u64 {
         match self {
             EthereumNetwork::Mainnet => 1,
             EthereumNetwork::Sepolia => 11155111,
+            EthereumNetwork::Custom(chain_spec) => chain_spec.chain_id,
+        }
+    }
+
+    /// The expected size in bytes of a block returned by `eth_getBlockByNumber`.
+    pub fn expected_block_size(&self) -> u64 {
+        match self {
+            EthereumNetwork::Mainnet => 24 * 1024,
+            EthereumNetwork::Sepolia => 12 * 1024,
+            EthereumNetwork::Custom(chain_spec) => chain_spec.expected_block_size,
+        }
+    }
+
+    /// The maximum number of blocks queried by a single `eth_getLogs` request.
+    pub fn max_block_spread(&self) -> u64 {
+        match self {
+            // The maximum block spread is introduced by Cloudflare limits.
+            // https://developers.cloudflare.com/web3/ethereum-gateway/
+            EthereumNetwork::Mainnet | EthereumNetwork::Sepolia => 799,
+            EthereumNetwork::Custom(chain_spec) => chain_spec.max_block_spread,
         }
     }
 }
@@ -44,6 +120,36 @@ impl Display for EthereumNetwork {
         match self {
             EthereumNetwork::Mainnet => write!(f, "Ethereum Mainnet"),
             EthereumNetwork::Sepolia => write!(f, "Ethereum Testnet Sepolia"),
+            EthereumNetwork::Custom(chain_spec) => write!(f, "{}", chain_spec.name),
+        }
+    }
+}
+
+// Mainnet and Sepolia are encoded by their chain ID, as before custom chains were supported,
+// so that the events recorded by previous versions can still be decoded.
+impl<C> Encode<C> for EthereumNetwork {
+    fn encode<W: minicbor::encode::Write>(
+        &self,
+        e: &mut minicbor::Encoder<W>,
+        ctx: &mut C,
+    ) -> Result<(), minicbor::encode::Error<W::Error>> {
+        match self {
+            EthereumNetwork::Mainnet => e.u32(1)?.ok(),
+            EthereumNetwork::Sepolia => e.u32(11155111)?.ok(),
+            EthereumNetwork::Custom(chain_spec) => chain_spec.encode(e, ctx),
+        }
+    }
+}
+
+impl<'b, C> Decode<'b, C> for EthereumNetwork {
+    fn decode(d: &mut minicbor::Decoder<'b>, ctx: &mut C) -> Result<Self, minicbor::decode::Error> {
+        if d.datatype()? == minicbor::data::Type::Array {
+            return ChainSpec::decode(d, ctx).map(EthereumNetwork::Custom);
+        }
+        match d.u32()? {
+            1 => Ok(EthereumNetwork::Mainnet),
+            11155111 => Ok(EthereumNetwork::Sepolia),
+            n => Err(minicbor::decode::Error::unknown_variant(n)),
         }
     }
 }
@@ -1,8 +1,12 @@
 use crate::address::Address;
 use crate::endpoints::CandidBlockTag;
-use crate::eth_rpc::BlockTag;
+use crate::eth_logs::{ScrapingMode, TokenStandard};
+use crate::eth_rpc::BlockHeight;
+use crate::generator::GeneratorConfig;
 use crate::lifecycle::EthereumNetwork;
+use crate::metadata::CollectionMetadata;
 use crate::numeric::{BlockNumber, TransactionNonce, Wei};
+use crate::reveal::{Reveal, RevealConfig};
 use crate::state::{InvalidStateError, State};
 use candid::types::number::Nat;
 use candid::types::principal::Principal;
@@ -21,6 +25,26 @@ pub struct InitArg {
     pub ethereum_block_height: CandidBlockTag,
     #[cbor(n(4), with = "crate::cbor::nat")]
     pub last_scraped_block_number: Nat,
+    #[n(5)]
+    pub generator_config: Option<GeneratorConfig>,
+    #[n(6)]
+    pub collection_metadata: Option<CollectionMetadata>,
+    #[n(7)]
+    pub reveal_config: Option<RevealConfig>,
+    #[n(8)]
+    pub scraping_mode: Option<ScrapingMode>,
+    #[n(9)]
+    pub token_standard: Option<TokenStandard>,
+}
+
+impl InitArg {
+    /// Returns the arguments with the values of the headers of the providers redacted.
+    pub fn redacted(self) -> Self {
+        Self {
+            ethereum_network: self.ethereum_network.redacted(),
+            ..self
+        }
+    }
 }
 
 impl TryFrom<InitArg> for State {
@@ -33,6 +57,11 @@ impl TryFrom<InitArg> for State {
             ethereum_contract_address,
             ethereum_block_height,
             last_scraped_block_number,
+            generator_config,
+            collection_metadata,
+            reveal_config,
+            scraping_mode,
+            token_standard,
         }: InitArg,
     ) -> Result<Self, Self::Error> {
         use std::str::FromStr;
@@ -63,18 +92,42 @@ impl TryFrom<InitArg> for State {
                         "ERROR: last_scraped_block_number is at maximum value".to_string(),
                     )
                 })?;
+        let reveal = reveal_config
+            .map(Reveal::try_from)
+            .transpose()
+            .map_err(InvalidStateError::InvalidRevealConfig)?;
         let state = Self {
             ethereum_network,
+            rpc_providers: Default::default(),
+            consensus_config: Default::default(),
+            rpc_transport: Default::default(),
+            verify_receipts: false,
             minter_address,
             ethereum_contract_address,
-            ethereum_block_height: BlockTag::from(ethereum_block_height),
+            ethereum_block_height: BlockHeight::from(ethereum_block_height),
+            scraping_mode: scraping_mode.unwrap_or_default(),
+            token_standard: token_standard.unwrap_or_default(),
             first_scraped_block_number,
             last_scraped_block_number,
             last_observed_block_number: None,
             events_to_mint: Default::default(),
+            seeds: Default::default(),
+            rolled_back_seeds: Default::default(),
             minted_events: Default::default(),
             invalid_events: Default::default(),
+            invalid_token_ids: Default::default(),
+            owners: Default::default(),
+            tokens_by_owner: Default::default(),
+            burned_tokens: Default::default(),
+            supplies: Default::default(),
             skipped_blocks: Default::default(),
+            block_hashes: Default::default(),
+            token_changes: Default::default(),
+            generator_config: generator_config.unwrap_or_default(),
+            collections: Default::default(),
+            collection_metadata: collection_metadata.unwrap_or_default(),
+            assets: Default::default(),
+            reveal,
             active_tasks: Default::default(),
             http_request_counter: 0,
         };
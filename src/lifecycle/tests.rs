@@ -1,32 +1,17 @@
 mod init {
+    use crate::address::Address;
+    use crate::eth_logs::{ScrapingMode, TokenStandard};
     use crate::lifecycle::init::InitArg;
-    use crate::numeric::{TransactionNonce, Wei};
     use crate::state::{InvalidStateError, State};
     use assert_matches::assert_matches;
-    use candid::{Nat, Principal};
+    use candid::Nat;
     use num_bigint::BigUint;
 
     #[test]
     fn should_fail_when_init_args_invalid() {
         assert_matches!(
             State::try_from(InitArg {
-                ecdsa_key_name: "     ".to_string(),
-                ..valid_init_arg()
-            }),
-            Err(InvalidStateError::InvalidEcdsaKeyName(_))
-        );
-
-        assert_matches!(
-            State::try_from(InitArg {
-                minimum_withdrawal_amount: Nat::from(0),
-                ..valid_init_arg()
-            }),
-            Err(InvalidStateError::InvalidMinimumWithdrawalAmount(_))
-        );
-
-        assert_matches!(
-            State::try_from(InitArg {
-                ethereum_contract_address: Some("invalid".to_string()),
+                ethereum_contract_address: "invalid".to_string(),
                 ..valid_init_arg()
             }),
             Err(InvalidStateError::InvalidEthereumContractAddress(_))
@@ -34,9 +19,7 @@ mod init {
 
         assert_matches!(
             State::try_from(InitArg {
-                ethereum_contract_address: Some(
-                    "0x0000000000000000000000000000000000000000".to_string(),
-                ),
+                ethereum_contract_address: "0x0000000000000000000000000000000000000000".to_string(),
                 ..valid_init_arg()
             }),
             Err(InvalidStateError::InvalidEthereumContractAddress(_))
@@ -44,30 +27,29 @@ mod init {
 
         assert_matches!(
             State::try_from(InitArg {
-                ledger_id: Principal::anonymous(),
+                minter_address: Some("invalid".to_string()),
                 ..valid_init_arg()
             }),
-            Err(InvalidStateError::InvalidLedgerId(_))
+            Err(InvalidStateError::InvalidMinterAddress(_))
         );
 
         assert_matches!(
             State::try_from(InitArg {
-                next_transaction_nonce: Nat(BigUint::from_bytes_be(
+                last_scraped_block_number: Nat(BigUint::from_bytes_be(
                     &ethnum::u256::MAX.to_be_bytes(),
-                ) + 1_u8),
+                )),
                 ..valid_init_arg()
             }),
-            Err(InvalidStateError::InvalidTransactionNonce(_))
+            Err(InvalidStateError::InvalidLastScrapedBlockNumber(_))
         );
 
         assert_matches!(
             State::try_from(InitArg {
-                last_scraped_block_number: Nat(BigUint::from_bytes_be(
-                    &ethnum::u256::MAX.to_be_bytes(),
-                )),
+                scraping_mode: Some(ScrapingMode::AllTransfers),
+                token_standard: Some(TokenStandard::Erc1155),
                 ..valid_init_arg()
             }),
-            Err(InvalidStateError::InvalidLastScrapedBlockNumber(_))
+            Err(InvalidStateError::InvalidScrapingMode(_))
         );
     }
 
@@ -78,27 +60,214 @@ mod init {
         let state = State::try_from(init_arg.clone()).expect("valid init args");
 
         assert_eq!(state.ethereum_network, init_arg.ethereum_network);
-        assert_eq!(state.ecdsa_key_name, init_arg.ecdsa_key_name);
-        assert_eq!(state.ethereum_contract_address, None);
-        assert_eq!(state.ledger_id, init_arg.ledger_id);
-        assert_eq!(state.minimum_withdrawal_amount, Wei::TWO);
         assert_eq!(
-            state.eth_transactions.next_transaction_nonce(),
-            TransactionNonce::ZERO
+            state.ethereum_contract_address,
+            init_arg.ethereum_contract_address.parse().unwrap()
+        );
+        assert_eq!(state.minter_address, Address::ZERO);
+        assert_eq!(state.scraping_mode, ScrapingMode::Mints);
+        assert_eq!(state.token_standard, TokenStandard::Erc721);
+    }
+
+    #[test]
+    fn should_redact_headers_of_custom_chain_providers_in_init_event() {
+        use crate::eth_rpc_client::providers::{RpcHeader, RpcProvider};
+        use crate::lifecycle::{ChainSpec, EthereumNetwork};
+        use crate::state::event::{Event, EventType};
+
+        let init_arg = InitArg {
+            ethereum_network: EthereumNetwork::Custom(ChainSpec {
+                chain_id: 8453,
+                name: "Base".to_string(),
+                expected_block_size: 64 * 1024,
+                max_block_spread: 500,
+                providers: vec![RpcProvider {
+                    headers: Some(vec![RpcHeader {
+                        name: "Authorization".to_string(),
+                        value: "Bearer secret".to_string(),
+                    }]),
+                    ..RpcProvider::from("https://base-mainnet.g.alchemy.com/v2".to_string())
+                }],
+            }),
+            ..valid_init_arg()
+        };
+        let mut buf = vec![];
+        minicbor::encode(
+            Event {
+                timestamp: 0,
+                payload: EventType::Init(init_arg),
+            },
+            &mut buf,
+        )
+        .unwrap();
+
+        let init_arg = match minicbor::decode::<Event>(&buf).unwrap().payload {
+            EventType::Init(init_arg) => init_arg.redacted(),
+            payload => panic!("unexpected event {payload:?}"),
+        };
+
+        let EthereumNetwork::Custom(chain_spec) = init_arg.ethereum_network else {
+            panic!("expected a custom chain");
+        };
+        assert_eq!(
+            chain_spec.providers[0].headers,
+            Some(vec![RpcHeader {
+                name: "Authorization".to_string(),
+                value: "<redacted>".to_string(),
+            }])
         );
     }
 
     fn valid_init_arg() -> InitArg {
         InitArg {
             ethereum_network: Default::default(),
-            ecdsa_key_name: "test_key_1".to_string(),
-            ethereum_contract_address: None,
-            ledger_id: Principal::from_text("apia6-jaaaa-aaaar-qabma-cai")
-                .expect("BUG: invalid principal"),
+            minter_address: None,
+            ethereum_contract_address: "0xb44B5e756A894775FC32EDdf3314Bb1B1944dC34".to_string(),
             ethereum_block_height: Default::default(),
-            minimum_withdrawal_amount: Wei::TWO.into(),
-            next_transaction_nonce: TransactionNonce::ZERO.into(),
             last_scraped_block_number: Default::default(),
+            generator_config: None,
+            collection_metadata: None,
+            reveal_config: None,
+            scraping_mode: None,
+            token_standard: None,
         }
     }
 }
+
+mod ethereum_network {
+    use crate::eth_rpc_client::providers::{RpcHeader, RpcProvider};
+    use crate::lifecycle::{ChainSpec, EthereumNetwork};
+
+    #[test]
+    fn should_decode_networks_encoded_before_custom_chains() {
+        for network in [EthereumNetwork::Mainnet, EthereumNetwork::Sepolia] {
+            let mut buf = vec![];
+            minicbor::encode(network.chain_id() as u32, &mut buf).unwrap();
+            assert_eq!(minicbor::decode::<EthereumNetwork>(&buf).unwrap(), network);
+        }
+    }
+
+    #[test]
+    fn should_encode_and_decode_custom_chain() {
+        let network = EthereumNetwork::Custom(base());
+        let mut buf = vec![];
+        minicbor::encode(&network, &mut buf).unwrap();
+
+        assert_eq!(minicbor::decode::<EthereumNetwork>(&buf).unwrap(), network);
+        assert_eq!(network.chain_id(), 8453);
+        assert_eq!(network.max_block_spread(), 500);
+        assert_eq!(network.to_string(), "Base");
+    }
+
+    #[test]
+    fn should_encode_and_decode_providers_with_headers() {
+        let network = EthereumNetwork::Custom(ChainSpec {
+            providers: vec![RpcProvider {
+                url: "https://base-mainnet.g.alchemy.com/v2".to_string(),
+                headers: Some(vec![RpcHeader {
+                    name: "Authorization".to_string(),
+                    value: "Bearer key".to_string(),
+                }]),
+                max_response_size: Some(1024 * 1024),
+                max_block_spread: Some(10),
+            }],
+            ..base()
+        });
+        let mut buf = vec![];
+        minicbor::encode(&network, &mut buf).unwrap();
+
+        assert_eq!(minicbor::decode::<EthereumNetwork>(&buf).unwrap(), network);
+    }
+
+    #[test]
+    fn should_decode_providers_encoded_as_urls() {
+        let mut buf = vec![];
+        let mut encoder = minicbor::Encoder::new(&mut buf);
+        encoder
+            .array(5)
+            .unwrap()
+            .u64(8453)
+            .unwrap()
+            .str("Base")
+            .unwrap()
+            .u64(64 * 1024)
+            .unwrap()
+            .u64(500)
+            .unwrap()
+            .array(2)
+            .unwrap()
+            .str("https://mainnet.base.org")
+            .unwrap()
+            .str("https://base.publicnode.com")
+            .unwrap();
+
+        assert_eq!(
+            minicbor::decode::<EthereumNetwork>(&buf).unwrap(),
+            EthereumNetwork::Custom(base())
+        );
+    }
+
+    #[test]
+    fn should_reject_invalid_chain_spec() {
+        assert_eq!(base().validate(), Ok(()));
+        for (chain_spec, error) in [
+            (
+                ChainSpec {
+                    chain_id: 0,
+                    ..base()
+                },
+                "chain ID",
+            ),
+            (
+                ChainSpec {
+                    max_block_spread: 0,
+                    ..base()
+                },
+                "block spread",
+            ),
+            (
+                ChainSpec {
+                    providers: vec![],
+                    ..base()
+                },
+                "at least one provider",
+            ),
+            (
+                ChainSpec {
+                    providers: vec![provider("http://mainnet.base.org")],
+                    ..base()
+                },
+                "HTTPS",
+            ),
+            (
+                ChainSpec {
+                    providers: vec![
+                        provider("https://mainnet.base.org"),
+                        provider("https://mainnet.base.org"),
+                    ],
+                    ..base()
+                },
+                "duplicated",
+            ),
+        ] {
+            assert!(chain_spec.validate().unwrap_err().contains(error));
+        }
+    }
+
+    fn base() -> ChainSpec {
+        ChainSpec {
+            chain_id: 8453,
+            name: "Base".to_string(),
+            expected_block_size: 64 * 1024,
+            max_block_spread: 500,
+            providers: vec![
+                provider("https://mainnet.base.org"),
+                provider("https://base.publicnode.com"),
+            ],
+        }
+    }
+
+    fn provider(url: &str) -> RpcProvider {
+        RpcProvider::from(url.to_string())
+    }
+}
@@ -1,8 +1,17 @@
+use crate::certification::start_rebuild;
+use crate::collections::CollectionArg;
 use crate::endpoints::CandidBlockTag;
+use crate::eth_logs::ScrapingMode;
+use crate::eth_rpc_client::consensus::ConsensusConfig;
+use crate::eth_rpc_client::providers::RpcProvider;
+use crate::eth_rpc_client::transport::RpcTransportConfig;
+use crate::generator::GeneratorConfig;
 use crate::logs::INFO;
+use crate::metadata::CollectionMetadata;
+use crate::reveal::RevealConfig;
 use crate::state::audit::{process_event, replay_events, EventType};
-use crate::state::mutate_state;
 use crate::state::STATE;
+use crate::state::{mutate_state, read_state};
 use crate::storage::total_event_count;
 use candid::{CandidType, Deserialize};
 use ic_canister_log::log;
@@ -14,6 +23,35 @@ pub struct UpgradeArg {
     pub ethereum_contract_address: Option<String>,
     #[n(3)]
     pub ethereum_block_height: Option<CandidBlockTag>,
+    #[n(4)]
+    pub generator_config: Option<GeneratorConfig>,
+    #[n(5)]
+    pub collection_metadata: Option<CollectionMetadata>,
+    #[n(6)]
+    pub reveal_config: Option<RevealConfig>,
+    /// Transfers are only tracked from the next scraped block on.
+    #[n(7)]
+    pub scraping_mode: Option<ScrapingMode>,
+    /// Additional collections to watch.
+    #[n(8)]
+    pub add_collections: Option<Vec<CollectionArg>>,
+    /// The IDs of the additional collections to stop watching, whose tokens are no longer served.
+    /// Collections are removed before any collection is added.
+    #[n(9)]
+    pub remove_collections: Option<Vec<String>>,
+    /// Replaces the JSON-RPC providers, which are queried in the given order.
+    /// An empty list restores the default providers of the network.
+    #[n(10)]
+    pub rpc_providers: Option<Vec<RpcProvider>>,
+    /// Changes the consensus strategies of the methods that are set.
+    #[n(11)]
+    pub consensus_config: Option<ConsensusConfig>,
+    /// Changes how the JSON-RPC requests are sent to the providers.
+    #[n(12)]
+    pub rpc_transport: Option<RpcTransportConfig>,
+    /// Whether accepted events are checked against the receipts of their transactions before being minted.
+    #[n(13)]
+    pub verify_receipts: Option<bool>,
 }
 
 pub fn post_upgrade(upgrade_args: Option<UpgradeArg>) {
@@ -25,6 +63,7 @@ pub fn post_upgrade(upgrade_args: Option<UpgradeArg>) {
     if let Some(args) = upgrade_args {
         mutate_state(|s| process_event(s, EventType::Upgrade(args)))
     }
+    read_state(start_rebuild);
 
     let end = ic_cdk::api::instruction_counter();
 
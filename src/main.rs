@@ -1,21 +1,36 @@
-use candid::candid_method;
+use candid::{candid_method, Nat};
 use ic_canister_log::log;
 use ic_canisters_http_types::{HttpRequest, HttpResponse, HttpResponseBuilder};
 use ic_cdk_macros::{init, post_upgrade, pre_upgrade, query, update};
 
-use ic_cketh_minter::deposit::scrape_eth_logs;
+use ic_cketh_minter::assets::{self, Asset};
+use ic_cketh_minter::certification::start_rebuild;
+use ic_cketh_minter::consistency::audit_consistency;
+use ic_cketh_minter::deposit::{retry_skipped_blocks, scrape_eth_logs};
 use ic_cketh_minter::endpoints::events::{
     Event as CandidEvent, EventSource as CandidEventSource, GetEventsArg, GetEventsResult,
 };
-
-use ic_cketh_minter::eth_logs::{EventSource, MintEvent};
-use ic_cketh_minter::eth_rpc::into_nat;
+use ic_cketh_minter::endpoints::{
+    AssetError, CollectionInfo, CommitAssetBatchArg, ConsistencyAudit, CreateAssetBatchArg,
+    GenerationStatus, ListTokensArg, RevealStatus, Token, TokenAttribute, UploadAssetChunkArg,
+};
+use ic_cketh_minter::reveal::{commit_provenance, RevealError};
+
+use ic_cketh_minter::address::Address;
+use ic_cketh_minter::collections::{CollectionId, TokenKey};
+use ic_cketh_minter::eth_logs::{EventSource, MintEvent, TransferEvent};
+use ic_cketh_minter::eth_rpc::{into_nat, try_from_nat};
+use ic_cketh_minter::eth_rpc_client::providers::RpcProvider;
+use ic_cketh_minter::lifecycle::upgrade::UpgradeArg;
 use ic_cketh_minter::lifecycle::MinterArg;
 use ic_cketh_minter::logs::INFO;
+use ic_cketh_minter::metadata::base_url;
 
 use ic_cketh_minter::state::audit::{Event, EventType};
-use ic_cketh_minter::state::{read_state, State, STATE};
-use ic_cketh_minter::{storage, SCRAPPING_ETH_LOGS_INTERVAL};
+use ic_cketh_minter::state::{read_state, MintedEvent, State, STATE};
+use ic_cketh_minter::{
+    storage, CONSISTENCY_AUDIT_INTERVAL, RETRY_SKIPPED_BLOCKS_INTERVAL, SCRAPPING_ETH_LOGS_INTERVAL,
+};
 
 use std::time::Duration;
 
@@ -28,6 +43,17 @@ fn setup_timers() {
     ic_cdk_timers::set_timer_interval(SCRAPPING_ETH_LOGS_INTERVAL, || {
         ic_cdk::spawn(scrape_eth_logs())
     });
+    ic_cdk_timers::set_timer_interval(RETRY_SKIPPED_BLOCKS_INTERVAL, || {
+        ic_cdk::spawn(retry_skipped_blocks())
+    });
+    ic_cdk_timers::set_timer_interval(CONSISTENCY_AUDIT_INTERVAL, || {
+        ic_cdk::spawn(audit_consistency())
+    });
+    // Hidden collections commit to their provenance as soon as possible.
+    ic_cdk_timers::set_timer(
+        Duration::from_secs(0),
+        || ic_cdk::spawn(commit_provenance()),
+    );
 }
 
 #[init]
@@ -41,6 +67,7 @@ fn init(arg: MinterArg) {
                 *cell.borrow_mut() =
                     Some(State::try_from(init_arg).expect("BUG: failed to initialize minter"))
             });
+            read_state(start_rebuild);
         }
         MinterArg::UpgradeArg(_) => {
             ic_cdk::trap("cannot init canister state with upgrade args");
@@ -51,9 +78,13 @@ fn init(arg: MinterArg) {
 
 fn emit_preupgrade_events() {
     read_state(|s| {
-        storage::record_event(EventType::SyncedToBlock {
-            block_number: s.last_scraped_block_number,
-        });
+        for (collection, _, last_scraped_block_number) in s.scraped_contracts() {
+            storage::record_event(EventType::SyncedToBlock {
+                block_number: last_scraped_block_number,
+                collection,
+                block_hash: s.block_hashes.get(&last_scraped_block_number).copied(),
+            });
+        }
     });
 }
 
@@ -94,6 +125,186 @@ async fn get_canister_status() -> ic_cdk::api::management_canister::main::Canist
     .0
 }
 
+fn ensure_controller() -> Result<(), AssetError> {
+    if ic_cdk::api::is_controller(&ic_cdk::caller()) {
+        Ok(())
+    } else {
+        Err(AssetError::NotController)
+    }
+}
+
+#[update]
+#[candid_method(update)]
+fn create_asset_batch(arg: CreateAssetBatchArg) -> Result<u64, AssetError> {
+    ensure_controller()?;
+    assets::create_batch(arg, ic_cdk::api::time())
+}
+
+#[update]
+#[candid_method(update)]
+fn upload_asset_chunk(arg: UploadAssetChunkArg) -> Result<(), AssetError> {
+    ensure_controller()?;
+    assets::upload_chunk(arg)
+}
+
+#[update]
+#[candid_method(update)]
+fn commit_asset_batch(arg: CommitAssetBatchArg) -> Result<(), AssetError> {
+    ensure_controller()?;
+    assets::commit_batch(arg)
+}
+
+#[update]
+#[candid_method(update)]
+fn reveal() -> Result<(), RevealError> {
+    if !ic_cdk::api::is_controller(&ic_cdk::caller()) {
+        return Err(RevealError::NotController);
+    }
+    ic_cketh_minter::reveal::reveal()
+}
+
+#[query]
+#[candid_method(query)]
+fn get_reveal_status() -> RevealStatus {
+    read_state(|s| {
+        let reveal = s.reveal.as_ref();
+        let provenance = reveal.and_then(|reveal| reveal.provenance.as_ref());
+        RevealStatus {
+            hidden: s.is_hidden(),
+            provenance_hash: provenance.map(|provenance| hex::encode(provenance.hash)),
+            seed: reveal
+                .and_then(|reveal| reveal.revealed_seed())
+                .map(hex::encode),
+            reveal_block_number: reveal
+                .and_then(|reveal| reveal.reveal_block_number)
+                .map(Nat::from),
+        }
+    })
+}
+
+#[query]
+#[candid_method(query)]
+fn get_consistency_audit() -> Option<ConsistencyAudit> {
+    ic_cketh_minter::consistency::last_audit().map(ConsistencyAudit::from)
+}
+
+/// Parses the ID of an additional collection, where `None` is the collection configured at init.
+fn parse_collection(collection: Option<String>) -> Result<Option<CollectionId>, String> {
+    collection.map(|id| id.parse()).transpose()
+}
+
+/// Returns the given token, or `None` if the token ID or the collection ID is invalid.
+fn token_key(token_id: &Nat, collection: Option<String>) -> Option<TokenKey> {
+    Some(TokenKey {
+        collection: parse_collection(collection).ok()?,
+        token_id: try_from_nat(token_id)?,
+    })
+}
+
+#[query]
+#[candid_method(query)]
+fn get_token(token_id: Nat, collection: Option<String>) -> Option<Token> {
+    let token = token_key(&token_id, collection)?;
+    let base_url = base_url();
+    read_state(|s| {
+        s.minted_event_of(&token)
+            .map(|event| map_token(s, event, &base_url))
+    })
+}
+
+#[query]
+#[candid_method(query)]
+fn list_tokens(arg: ListTokensArg) -> Vec<Token> {
+    const MAX_TOKENS_PER_RESPONSE: u64 = 100;
+
+    let collection = match parse_collection(arg.collection) {
+        Ok(collection) => collection,
+        Err(_) => return vec![],
+    };
+    let base_url = base_url();
+    read_state(|s| {
+        s.minted_events
+            .values()
+            .filter(|event| event.mint_event.collection == collection)
+            .skip(arg.start as usize)
+            .take(arg.length.min(MAX_TOKENS_PER_RESPONSE) as usize)
+            .map(|event| map_token(s, event, &base_url))
+            .collect()
+    })
+}
+
+#[query]
+#[candid_method(query)]
+fn get_generation_status(token_id: Nat, collection: Option<String>) -> GenerationStatus {
+    match token_key(&token_id, collection) {
+        Some(token) => read_state(|s| s.generation_status(&token)),
+        None => GenerationStatus::NotFound,
+    }
+}
+
+#[query]
+#[candid_method(query)]
+fn owner_of(token_id: Nat, collection: Option<String>) -> Option<String> {
+    let token = token_key(&token_id, collection)?;
+    read_state(|s| s.owner_of(&token)).map(|owner| owner.to_string())
+}
+
+#[query]
+#[candid_method(query)]
+fn tokens_of(address: String, collection: Option<String>) -> Vec<Nat> {
+    let address: Address = address
+        .parse()
+        .unwrap_or_else(|e| ic_cdk::trap(&format!("invalid address {address}: {e}")));
+    let collection = parse_collection(collection)
+        .unwrap_or_else(|e| ic_cdk::trap(&format!("invalid collection: {e}")));
+    read_state(|s| {
+        s.tokens_of(&address, collection.as_ref())
+            .map(into_nat)
+            .collect()
+    })
+}
+
+#[query]
+#[candid_method(query)]
+fn get_collections() -> Vec<CollectionInfo> {
+    read_state(|s| {
+        s.collections
+            .iter()
+            .map(|(id, collection)| CollectionInfo {
+                id: id.to_string(),
+                ethereum_contract_address: collection.ethereum_contract_address.to_string(),
+                first_scraped_block_number: collection.first_scraped_block_number.into(),
+                last_scraped_block_number: collection.last_scraped_block_number.into(),
+            })
+            .collect()
+    })
+}
+
+fn map_token(s: &State, event: &MintedEvent, base_url: &str) -> Token {
+    let mint_event = &event.mint_event;
+    let token = mint_event.token_key();
+    Token {
+        token_id: into_nat(mint_event.token_id),
+        collection: mint_event.collection.as_ref().map(CollectionId::to_string),
+        owner: s
+            .owner_of(&token)
+            .unwrap_or(mint_event.to_address)
+            .to_string(),
+        supply: into_nat(s.supply_of(&token)),
+        transaction_hash: mint_event.transaction_hash.to_string(),
+        block_number: mint_event.block_number.into(),
+        attributes: event
+            .attributes
+            .iter()
+            .map(|attribute| TokenAttribute {
+                trait_type: attribute.trait_type.clone(),
+                value: attribute.value.clone(),
+            })
+            .collect(),
+        metadata_url: format!("{base_url}{}", token.path()),
+    }
+}
+
 #[query]
 #[candid_method(query)]
 fn get_events(arg: GetEventsArg) -> GetEventsResult {
@@ -103,21 +314,33 @@ fn get_events(arg: GetEventsArg) -> GetEventsResult {
         EventSource {
             transaction_hash,
             log_index,
+            batch_index,
         }: EventSource,
     ) -> CandidEventSource {
         CandidEventSource {
             transaction_hash: transaction_hash.to_string(),
             log_index: log_index.into(),
+            batch_index,
         }
     }
 
+    fn map_collection(collection: Option<CollectionId>) -> Option<String> {
+        collection.as_ref().map(CollectionId::to_string)
+    }
+
     fn map_event(Event { timestamp, payload }: Event) -> CandidEvent {
         use ic_cketh_minter::endpoints::events::EventPayload as EP;
         CandidEvent {
             timestamp,
             payload: match payload {
-                EventType::Init(args) => EP::Init(args),
-                EventType::Upgrade(args) => EP::Upgrade(args),
+                // The headers of the providers may contain API keys.
+                EventType::Init(args) => EP::Init(args.redacted()),
+                EventType::Upgrade(args) => EP::Upgrade(UpgradeArg {
+                    rpc_providers: args.rpc_providers.map(|providers| {
+                        providers.into_iter().map(RpcProvider::redacted).collect()
+                    }),
+                    ..args
+                }),
                 EventType::AcceptedMint(MintEvent {
                     transaction_hash,
                     block_number,
@@ -125,6 +348,10 @@ fn get_events(arg: GetEventsArg) -> GetEventsResult {
                     from_address,
                     to_address,
                     token_id,
+                    batch_index,
+                    value,
+                    collection,
+                    block_hash,
                 }) => EP::AcceptedTransfer {
                     transaction_hash: transaction_hash.to_string(),
                     block_number: block_number.into(),
@@ -132,22 +359,155 @@ fn get_events(arg: GetEventsArg) -> GetEventsResult {
                     from_address: from_address.to_string(),
                     to_address: to_address.to_string(),
                     token_id: into_nat(token_id),
+                    batch_index,
+                    value: value.map(into_nat),
+                    collection: map_collection(collection),
+                    block_hash: block_hash.map(|hash| hash.to_string()),
+                },
+                EventType::AcceptedEdition(MintEvent {
+                    transaction_hash,
+                    block_number,
+                    log_index,
+                    from_address,
+                    to_address,
+                    token_id,
+                    batch_index,
+                    value,
+                    collection,
+                    block_hash,
+                }) => EP::AcceptedEdition {
+                    transaction_hash: transaction_hash.to_string(),
+                    block_number: block_number.into(),
+                    log_index: log_index.into(),
+                    from_address: from_address.to_string(),
+                    to_address: to_address.to_string(),
+                    token_id: into_nat(token_id),
+                    batch_index,
+                    value: value.map(into_nat),
+                    collection: map_collection(collection),
+                    block_hash: block_hash.map(|hash| hash.to_string()),
                 },
                 EventType::InvalidTransfer {
                     event_source,
                     reason,
+                    token_id,
+                    collection,
+                    block_number: _,
                 } => EP::InvalidTransfer {
                     event_source: map_event_source(event_source),
                     reason,
+                    token_id: token_id.map(into_nat),
+                    collection: map_collection(collection),
+                },
+                EventType::TransferredNft(TransferEvent {
+                    transaction_hash,
+                    block_number,
+                    log_index,
+                    from_address,
+                    to_address,
+                    token_id,
+                    batch_index,
+                    value,
+                    collection,
+                    block_hash,
+                }) => EP::TransferredNft {
+                    transaction_hash: transaction_hash.to_string(),
+                    block_number: block_number.into(),
+                    log_index: log_index.into(),
+                    from_address: from_address.to_string(),
+                    to_address: to_address.to_string(),
+                    token_id: into_nat(token_id),
+                    batch_index,
+                    value: value.map(into_nat),
+                    collection: map_collection(collection),
+                    block_hash: block_hash.map(|hash| hash.to_string()),
+                },
+                EventType::BurnedNft(TransferEvent {
+                    transaction_hash,
+                    block_number,
+                    log_index,
+                    from_address,
+                    to_address: _,
+                    token_id,
+                    batch_index,
+                    value,
+                    collection,
+                    block_hash,
+                }) => EP::BurnedNft {
+                    transaction_hash: transaction_hash.to_string(),
+                    block_number: block_number.into(),
+                    log_index: log_index.into(),
+                    from_address: from_address.to_string(),
+                    token_id: into_nat(token_id),
+                    batch_index,
+                    value: value.map(into_nat),
+                    collection: map_collection(collection),
+                    block_hash: block_hash.map(|hash| hash.to_string()),
                 },
                 EventType::MintedNft { event_source } => EP::MintedNft {
                     event_source: map_event_source(event_source),
                 },
-                EventType::SyncedToBlock { block_number } => EP::SyncedToBlock {
+                EventType::SyncedToBlock {
+                    block_number,
+                    collection,
+                    block_hash,
+                } => EP::SyncedToBlock {
+                    block_number: block_number.into(),
+                    collection: map_collection(collection),
+                    block_hash: block_hash.map(|hash| hash.to_string()),
+                },
+                EventType::SkippedBlock {
+                    block_number,
+                    collection,
+                } => EP::SkippedBlock {
+                    block_number: block_number.into(),
+                    collection: map_collection(collection),
+                },
+                EventType::CommittedAsset(Asset {
+                    key,
+                    content_type,
+                    sha256,
+                    size,
+                    content_index: _,
+                }) => EP::CommittedAsset {
+                    key,
+                    content_type,
+                    sha256: hex::encode(sha256),
+                    size,
+                },
+                EventType::ReceivedRandomness {
+                    seed,
+                    event_sources,
+                } => EP::ReceivedRandomness {
+                    seed: hex::encode(seed),
+                    event_sources: event_sources.into_iter().map(map_event_source).collect(),
+                },
+                // The seed stays secret until the reveal.
+                EventType::CommittedProvenance {
+                    seed: _,
+                    provenance_hash,
+                } => EP::CommittedProvenance {
+                    provenance_hash: hex::encode(provenance_hash),
+                },
+                EventType::Revealed { seed } => EP::Revealed {
+                    seed: hex::encode(seed),
+                },
+                EventType::ChainReorganized { block_number } => EP::ChainReorganized {
                     block_number: block_number.into(),
                 },
-                EventType::SkippedBlock(block_number) => EP::SkippedBlock {
+                EventType::ScrapedSkippedBlock {
+                    block_number,
+                    collection,
+                } => EP::ScrapedSkippedBlock {
                     block_number: block_number.into(),
+                    collection: map_collection(collection),
+                },
+                EventType::RejectedMint {
+                    event_source,
+                    reason,
+                } => EP::RejectedMint {
+                    event_source: map_event_source(event_source),
+                    reason,
                 },
             },
         }
@@ -214,6 +574,19 @@ fn http_request(req: HttpRequest) -> HttpResponse {
                     "Total count of Ethereum blocks that were skipped for deposits.",
                 )?;
 
+                s.collections.iter().try_fold(
+                    w.gauge_vec(
+                        "cketh_minter_collection_last_processed_block",
+                        "The last Ethereum block checked for each additional collection.",
+                    )?,
+                    |gauge, (id, collection)| {
+                        gauge.value(
+                            &[("collection", id.as_str())],
+                            collection.last_scraped_block_number.as_f64(),
+                        )
+                    },
+                )?;
+
                 w.gauge_vec(
                     "cketh_minter_accepted_deposits",
                     "The number of deposits the ckETH minter processed, by status.",
@@ -221,6 +594,12 @@ fn http_request(req: HttpRequest) -> HttpResponse {
                 .value(&[("status", "accepted")], s.minted_events.len() as f64)?
                 .value(&[("status", "rejected")], s.invalid_events.len() as f64)?;
 
+                w.encode_gauge(
+                    "cketh_minter_burned_tokens",
+                    s.burned_tokens.len() as f64,
+                    "The number of tokens burned on Ethereum.",
+                )?;
+
                 w.encode_gauge(
                     "cketh_event_count",
                     storage::total_event_count() as f64,
@@ -228,6 +607,8 @@ fn http_request(req: HttpRequest) -> HttpResponse {
                 )?;
 
                 ic_cketh_minter::eth_rpc::encode_metrics(w)?;
+                ic_cketh_minter::eth_rpc_client::health::encode_metrics(w)?;
+                ic_cketh_minter::consistency::encode_metrics(w)?;
 
                 Ok(())
             })
@@ -315,11 +696,46 @@ fn http_request(req: HttpRequest) -> HttpResponse {
             .header("Content-Type", "application/json; charset=utf-8")
             .with_body_and_content_length(log.serialize_logs(MAX_BODY_SIZE))
             .build()
+    } else if req.path() == ic_cketh_minter::metadata::COLLECTION_PATH {
+        serve_collection(req.path())
+    } else if let Some((collection, token_path)) =
+        ic_cketh_minter::metadata::split_token_path(req.path())
+    {
+        serve_token(req.path(), collection, token_path)
     } else {
         HttpResponseBuilder::not_found().build()
     }
 }
 
+fn serve_token(path: &str, collection: Option<CollectionId>, token_path: &str) -> HttpResponse {
+    use ic_cketh_minter::metadata::{parse_token_path, token_asset};
+
+    let asset = parse_token_path(collection, token_path)
+        .and_then(|token_path| read_state(|s| token_asset(s, token_path, &base_url())));
+    match asset {
+        Some(asset) => serve_certified_asset(path, asset),
+        None => HttpResponseBuilder::not_found()
+            .with_body_and_content_length("token not found")
+            .build(),
+    }
+}
+
+fn serve_collection(path: &str) -> HttpResponse {
+    use ic_cketh_minter::metadata::collection_asset;
+
+    serve_certified_asset(path, read_state(collection_asset))
+}
+
+fn serve_certified_asset(path: &str, asset: ic_cketh_minter::metadata::TokenAsset) -> HttpResponse {
+    use ic_cketh_minter::certification::certificate_header;
+
+    let mut response = HttpResponseBuilder::ok().header("Content-Type", asset.content_type);
+    if let Some((name, value)) = certificate_header(path) {
+        response = response.header(name, value);
+    }
+    response.with_body_and_content_length(asset.body).build()
+}
+
 #[cfg(feature = "debug_checks")]
 #[query]
 fn check_audit_log() {
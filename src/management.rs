@@ -143,3 +143,24 @@ pub async fn sign_with_ecdsa(
         )
     }))
 }
+
+/// Fetches 32 bytes of randomness from the management canister.
+pub async fn raw_rand() -> Result<[u8; 32], CallError> {
+    let res: Result<(Vec<u8>,), _> =
+        ic_cdk::api::call::call(Principal::management_canister(), "raw_rand", ()).await;
+    match res {
+        Ok((random_bytes,)) => {
+            let random_bytes_length = random_bytes.len();
+            Ok(<[u8; 32]>::try_from(random_bytes).unwrap_or_else(|_| {
+                panic!(
+                    "BUG: invalid randomness from management canister. Expected 32 bytes but got {} bytes",
+                    random_bytes_length
+                )
+            }))
+        }
+        Err((code, msg)) => Err(CallError {
+            method: "raw_rand".to_string(),
+            reason: Reason::from_reject(code, msg),
+        }),
+    }
+}
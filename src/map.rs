@@ -3,6 +3,7 @@ mod tests;
 
 use std::borrow::Borrow;
 use std::collections::BTreeMap;
+use std::ops::RangeBounds;
 
 /// A map with two keys: a primary key `Key` and an alternative key `AltKey`.
 /// The stored value `V` is indexed by both keys and can be efficiently retrieved from either key.
@@ -58,6 +59,10 @@ impl<Key: Ord, AltKey: Ord, V> MultiKeyMap<Key, AltKey, V> {
         self.by_key.is_empty()
     }
 
+    pub fn len(&self) -> usize {
+        self.by_key.len()
+    }
+
     pub fn try_insert(
         &mut self,
         key: Key,
@@ -150,6 +155,25 @@ impl<Key: Ord, AltKey: Ord, V> MultiKeyMap<Key, AltKey, V> {
         self.by_key.keys()
     }
 
+    /// Iterates over the primary keys within the given range, in ascending order.
+    pub fn range_keys<R: RangeBounds<Key>>(&self, range: R) -> impl Iterator<Item = &Key> {
+        self.by_key.range(range).map(|(key, _alt_key)| key)
+    }
+
+    /// Iterates over all stored values in the map.
+    /// Elements are returned in ascending order of the primary key.
+    pub fn values(&self) -> impl DoubleEndedIterator<Item = &V> {
+        self.by_key
+            .values()
+            .map(|alt_key| &self.by_alt_key[alt_key])
+    }
+
+    /// Iterates mutably over all stored values in the map.
+    /// Elements are returned in ascending order of the alternative key.
+    pub fn values_mut(&mut self) -> impl Iterator<Item = &mut V> {
+        self.by_alt_key.values_mut()
+    }
+
     pub fn remove_entry<Q: ?Sized>(&mut self, key: &Q) -> Option<(Key, AltKey, V)>
     where
         Key: Borrow<Q>,
@@ -80,6 +80,24 @@ fn should_iterate_in_order_of_primary_key() {
     assert_eq!(iter.next(), None);
 }
 
+#[test]
+fn should_iterate_values_in_order_of_primary_key() {
+    let mut map = MultiKeyMap::<PrimaryKey, AltKey, u32>::default();
+    map.try_insert(PrimaryKey::new(2), AltKey::new('a'), 2)
+        .unwrap();
+    map.try_insert(PrimaryKey::new(1), AltKey::new('b'), 1)
+        .unwrap();
+    map.try_insert(PrimaryKey::new(4), AltKey::new('c'), 4)
+        .unwrap();
+
+    assert_eq!(map.len(), 3);
+    assert_eq!(map.values().collect::<Vec<_>>(), vec![&1, &2, &4]);
+    assert_eq!(map.values().rev().collect::<Vec<_>>(), vec![&4, &2, &1]);
+
+    map.values_mut().for_each(|value| *value *= 10);
+    assert_eq!(map.values().collect::<Vec<_>>(), vec![&10, &20, &40]);
+}
+
 #[test]
 fn should_remove_entry() {
     let mut map = MultiKeyMap::<PrimaryKey, AltKey, u32>::default();
@@ -3,13 +3,11 @@ mod decode_memo {
     use crate::eth_rpc::Hash;
     use crate::memo::{Address, MintEvent};
     use crate::memo::{BurnMemo, MintMemo};
-    use crate::numeric::{BlockNumber, LedgerBurnIndex, LogIndex, Wei};
-    use crate::state::transactions::ReimbursementRequest;
-    use candid::Principal;
+    use crate::numeric::{BlockNumber, LogIndex};
+    use ethnum::u256;
     use icrc_ledger_types::icrc1::transfer::Memo;
     use proptest::array::{uniform20, uniform32};
     use proptest::prelude::*;
-    use std::str::FromStr;
 
     fn arb_hash() -> impl Strategy<Value = Hash> {
         uniform32(any::<u8>()).prop_map(Hash)
@@ -72,7 +70,11 @@ mod decode_memo {
             log_index: LogIndex::from(39_u8),
             from_address,
             to_address,
-            token_id: TokenId::from_str_hex("0x1c09"),
+            token_id: u256::from(0x1c09_u16),
+            batch_index: None,
+            value: None,
+            collection: None,
+            block_hash: None,
         };
         let memo: Memo = event.into();
 
@@ -93,14 +95,10 @@ mod decode_memo {
             "0x705f826861c802b407843e99af986cfde8749b669e5e0a5a150f4350bcaa9bc3"
                 .parse()
                 .unwrap();
-        let reimbursment_request = ReimbursementRequest {
-            withdrawal_id: LedgerBurnIndex::from(1234_u64),
-            reimbursed_amount: Wei::from(100_u64),
-            to: Principal::anonymous(),
-            to_subaccount: None,
-            transaction_hash: Some(transaction_hash),
-        };
-        let memo: Memo = reimbursment_request.into();
+        let memo = Memo::from(MintMemo::Reimburse {
+            withdrawal_id: 1234,
+            tx_hash: transaction_hash,
+        });
 
         assert_eq!(
             memo.0,
@@ -0,0 +1,298 @@
+//! Token metadata served to marketplaces and wallets.
+//!
+//! The `tokenURI` of the NFT contract points to the minter, which serves for each minted token
+//! a JSON document following the ERC-721 metadata JSON schema,
+//! see <https://eips.ethereum.org/EIPS/eip-721> and <https://docs.opensea.io/docs/metadata-standards>.
+
+#[cfg(test)]
+mod tests;
+
+use crate::address::Address;
+use crate::collections::{CollectionId, TokenKey};
+use crate::state::State;
+use crate::svg::{render_placeholder, render_svg};
+use candid::{CandidType, Deserialize};
+use ethnum::u256;
+use minicbor::{Decode, Encode};
+use serde::Serialize;
+use std::str::FromStr;
+
+/// The description served in the metadata of burned tokens.
+pub const BURNED_TOKEN_DESCRIPTION: &str = "This token was burned.";
+
+/// The path at which the collection metadata is served,
+/// to be set as the `contractURI` of the NFT contract.
+pub const COLLECTION_PATH: &str = "/collection.json";
+
+/// The maximum royalty fee, i.e., 100%.
+pub const MAX_SELLER_FEE_BASIS_POINTS: u16 = 10_000;
+
+/// Metadata shared by all the tokens of the collection.
+#[derive(CandidType, Deserialize, Clone, Debug, Default, Encode, Decode, PartialEq, Eq)]
+pub struct CollectionMetadata {
+    /// The name of the collection.
+    /// Token names are derived from it, e.g. "Name #42".
+    #[n(0)]
+    pub name: String,
+    /// A human-readable description shared by all the tokens.
+    #[n(1)]
+    pub description: String,
+    /// A link to the website of the collection.
+    #[n(2)]
+    pub external_url: Option<String>,
+    /// A link to the image of the collection.
+    #[n(3)]
+    pub image: Option<String>,
+    /// A link to the banner image displayed at the top of the collection page.
+    #[n(4)]
+    pub banner_image: Option<String>,
+    /// The Ethereum address receiving the royalties on secondary sales.
+    #[n(5)]
+    pub fee_recipient: Option<String>,
+    /// The royalties on secondary sales, in basis points of the sale price.
+    #[n(6)]
+    pub seller_fee_basis_points: Option<u16>,
+}
+
+impl CollectionMetadata {
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(fee_recipient) = &self.fee_recipient {
+            Address::from_str(fee_recipient)
+                .map_err(|e| format!("invalid fee recipient '{fee_recipient}': {e}"))?;
+        }
+        if let Some(basis_points) = self.seller_fee_basis_points {
+            if basis_points > MAX_SELLER_FEE_BASIS_POINTS {
+                return Err(format!(
+                    "seller fee of {basis_points} basis points exceeds the maximum of {MAX_SELLER_FEE_BASIS_POINTS}"
+                ));
+            }
+            if self.fee_recipient.is_none() {
+                return Err("seller fee requires a fee recipient".to_string());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The collection-level metadata read by marketplaces from the `contractURI` of the NFT contract,
+/// see <https://docs.opensea.io/docs/contract-level-metadata>.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ContractMetadata {
+    pub name: String,
+    pub description: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub banner_image: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub external_link: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seller_fee_basis_points: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fee_recipient: Option<String>,
+}
+
+impl ContractMetadata {
+    pub fn to_json(&self) -> Vec<u8> {
+        serde_json::to_vec(self).expect("BUG: failed to serialize contract metadata")
+    }
+}
+
+impl From<&CollectionMetadata> for ContractMetadata {
+    fn from(collection: &CollectionMetadata) -> Self {
+        Self {
+            name: collection.name.clone(),
+            description: collection.description.clone(),
+            image: collection.image.clone(),
+            banner_image: collection.banner_image.clone(),
+            external_link: collection.external_url.clone(),
+            seller_fee_basis_points: collection.seller_fee_basis_points,
+            fee_recipient: collection.fee_recipient.clone(),
+        }
+    }
+}
+
+/// The metadata of a single token.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct TokenMetadata {
+    pub name: String,
+    pub description: String,
+    pub image: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub external_url: Option<String>,
+    pub attributes: Vec<MetadataAttribute>,
+    /// Set on the tombstone metadata of burned tokens.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub burned: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct MetadataAttribute {
+    pub trait_type: String,
+    pub value: String,
+}
+
+impl TokenMetadata {
+    pub fn to_json(&self) -> Vec<u8> {
+        serde_json::to_vec(self).expect("BUG: failed to serialize token metadata")
+    }
+}
+
+/// The token resources served over HTTP.
+/// The paths of the tokens of additional collections are prefixed by the collection ID.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TokenPath {
+    /// `/token/{id}` or `/token/{id}.json`.
+    Metadata(TokenKey),
+    /// `/token/{id}/image.svg`.
+    Image(TokenKey),
+}
+
+impl TokenPath {
+    /// All the paths at which the given token is served.
+    pub fn all(token: &TokenKey) -> [(String, TokenPath); 3] {
+        let path = token.path();
+        [
+            (path.clone(), TokenPath::Metadata(token.clone())),
+            (format!("{path}.json"), TokenPath::Metadata(token.clone())),
+            (format!("{path}/image.svg"), TokenPath::Image(token.clone())),
+        ]
+    }
+}
+
+/// The body of an HTTP response serving a token or collection resource.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TokenAsset {
+    pub content_type: &'static str,
+    pub body: Vec<u8>,
+}
+
+/// Returns the resource served at the given path,
+/// or `None` if the token was neither minted nor burned.
+pub fn token_asset(state: &State, path: TokenPath, base_url: &str) -> Option<TokenAsset> {
+    match path {
+        TokenPath::Metadata(token) => {
+            token_metadata(state, &token, base_url).map(|metadata| TokenAsset {
+                content_type: "application/json",
+                body: metadata.to_json(),
+            })
+        }
+        TokenPath::Image(token) => {
+            let config = state.generator_config_of(token.collection.as_ref())?;
+            if state.is_burned(&token) {
+                return Some(TokenAsset {
+                    content_type: "image/svg+xml",
+                    body: render_placeholder(config, None).into_bytes(),
+                });
+            }
+            let attributes = state.attributes_of(&token)?;
+            // The delayed reveal only applies to the collection configured at init.
+            let image = match &state.reveal {
+                Some(reveal) if reveal.is_hidden() && token.collection.is_none() => {
+                    render_placeholder(config, reveal.placeholder_layer.as_deref())
+                }
+                _ => render_svg(config, &state.assets, attributes),
+            };
+            Some(TokenAsset {
+                content_type: "image/svg+xml",
+                body: image.into_bytes(),
+            })
+        }
+    }
+}
+
+/// Returns the collection metadata served at [`COLLECTION_PATH`].
+pub fn collection_asset(state: &State) -> TokenAsset {
+    TokenAsset {
+        content_type: "application/json",
+        body: ContractMetadata::from(&state.collection_metadata).to_json(),
+    }
+}
+
+/// The URL at which this canister serves HTTP requests.
+pub fn base_url() -> String {
+    format!("https://{}.icp0.io", ic_cdk::id())
+}
+
+/// Splits the path of an HTTP request for a token resource into the collection
+/// and the part that follows `/token/`, e.g. `/token/42` or `/{collection}/token/42`.
+pub fn split_token_path(path: &str) -> Option<(Option<CollectionId>, &str)> {
+    if let Some(token_path) = path.strip_prefix("/token/") {
+        return Some((None, token_path));
+    }
+    let (collection, token_path) = path.strip_prefix('/')?.split_once("/token/")?;
+    Some((Some(collection.parse().ok()?), token_path))
+}
+
+/// Parses the part of an HTTP request path that follows `/token/`,
+/// for a token of the given collection.
+pub fn parse_token_path(collection: Option<CollectionId>, path: &str) -> Option<TokenPath> {
+    if let Some(token_id) = path.strip_suffix("/image.svg") {
+        let token_id = parse_token_id(token_id)?;
+        return Some(TokenPath::Image(TokenKey {
+            collection,
+            token_id,
+        }));
+    }
+    let token_id = parse_token_id(path.strip_suffix(".json").unwrap_or(path))?;
+    Some(TokenPath::Metadata(TokenKey {
+        collection,
+        token_id,
+    }))
+}
+
+/// Parses a token ID in its canonical decimal representation (no sign, no leading zeroes),
+/// so that each token is served at a single path.
+pub fn parse_token_id(token_id: &str) -> Option<u256> {
+    if token_id.is_empty() || !token_id.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    if token_id.len() > 1 && token_id.starts_with('0') {
+        return None;
+    }
+    u256::from_str_radix(token_id, 10).ok()
+}
+
+/// Returns the metadata of the given token, or `None` if the token was neither minted nor burned.
+/// Tokens are named after the metadata of their collection.
+/// Burned tokens are served a tombstone without any attribute and with an empty image.
+///
+/// `base_url` is the URL at which the minter serves HTTP requests,
+/// e.g. `https://<canister id>.icp0.io`.
+pub fn token_metadata(state: &State, token: &TokenKey, base_url: &str) -> Option<TokenMetadata> {
+    let collection = state.collection_metadata_of(token.collection.as_ref())?;
+    let image = format!("{base_url}{}/image.svg", token.path());
+    if state.is_burned(token) {
+        return Some(TokenMetadata {
+            name: token_name(collection, token.token_id),
+            description: BURNED_TOKEN_DESCRIPTION.to_string(),
+            image,
+            external_url: collection.external_url.clone(),
+            attributes: vec![],
+            burned: true,
+        });
+    }
+    let attributes = state.attributes_of(token)?;
+    Some(TokenMetadata {
+        name: token_name(collection, token.token_id),
+        description: collection.description.clone(),
+        image,
+        external_url: collection.external_url.clone(),
+        attributes: attributes
+            .iter()
+            .map(|attribute| MetadataAttribute {
+                trait_type: attribute.trait_type.clone(),
+                value: attribute.value.clone(),
+            })
+            .collect(),
+        burned: false,
+    })
+}
+
+fn token_name(collection: &CollectionMetadata, token_id: u256) -> String {
+    if collection.name.is_empty() {
+        format!("#{token_id}")
+    } else {
+        format!("{} #{token_id}", collection.name)
+    }
+}
@@ -0,0 +1,309 @@
+mod parse_token_path {
+    use crate::collections::{CollectionId, TokenKey};
+    use crate::metadata::{parse_token_id, parse_token_path, split_token_path, TokenPath};
+    use ethnum::u256;
+
+    #[test]
+    fn should_parse_metadata_paths() {
+        assert_eq!(
+            parse_token_path(None, "42"),
+            Some(TokenPath::Metadata(u256::from(42_u8).into()))
+        );
+        assert_eq!(
+            parse_token_path(None, "42.json"),
+            Some(TokenPath::Metadata(u256::from(42_u8).into()))
+        );
+        assert_eq!(
+            parse_token_path(None, "0"),
+            Some(TokenPath::Metadata(u256::ZERO.into()))
+        );
+    }
+
+    #[test]
+    fn should_parse_image_paths() {
+        assert_eq!(
+            parse_token_path(None, "42/image.svg"),
+            Some(TokenPath::Image(u256::from(42_u8).into()))
+        );
+        assert_eq!(parse_token_path(None, "42.json/image.svg"), None);
+        assert_eq!(parse_token_path(None, "/image.svg"), None);
+    }
+
+    #[test]
+    fn should_split_collection_prefix() {
+        let dragons: CollectionId = "dragons".parse().unwrap();
+
+        assert_eq!(split_token_path("/token/42"), Some((None, "42")));
+        assert_eq!(
+            split_token_path("/dragons/token/42/image.svg"),
+            Some((Some(dragons.clone()), "42/image.svg"))
+        );
+        assert_eq!(
+            parse_token_path(Some(dragons.clone()), "42.json"),
+            Some(TokenPath::Metadata(TokenKey {
+                collection: Some(dragons),
+                token_id: u256::from(42_u8),
+            }))
+        );
+        for invalid in [
+            "/Dragons/token/42",
+            "/a/b/token/42",
+            "//token/42",
+            "/dragons/42",
+        ] {
+            assert_eq!(split_token_path(invalid), None, "{invalid}");
+        }
+    }
+
+    #[test]
+    fn should_parse_max_token_id() {
+        assert_eq!(parse_token_id(&u256::MAX.to_string()), Some(u256::MAX));
+    }
+
+    #[test]
+    fn should_reject_invalid_token_ids() {
+        for invalid in [
+            "",
+            ".json",
+            "042",
+            "-1",
+            "+1",
+            "0x2a",
+            "1 ",
+            "1.5",
+            "42.json.json",
+            "115792089237316195423570985008687907853269984665640564039457584007913129639936",
+        ] {
+            assert_eq!(parse_token_path(None, invalid), None, "{invalid}");
+        }
+    }
+}
+
+mod token_metadata {
+    use crate::address::Address;
+    use crate::collections::{CollectionArg, CollectionId, TokenKey};
+    use crate::eth_logs::MintEvent;
+    use crate::lifecycle::init::InitArg;
+    use crate::lifecycle::upgrade::UpgradeArg;
+    use crate::metadata::{
+        token_asset, token_metadata, CollectionMetadata, TokenPath, BURNED_TOKEN_DESCRIPTION,
+    };
+    use crate::state::audit::{apply_state_transition, EventType};
+    use crate::state::State;
+    use crate::test_fixtures::{holder, init_arg, mint_event, transfer_event};
+    use candid::Nat;
+    use ethnum::u256;
+
+    const BASE_URL: &str = "https://2ibo7-dia.icp0.io";
+
+    #[test]
+    fn should_not_return_metadata_of_unminted_token() {
+        let mut state = state();
+        let event = mint_event(1, 1);
+        apply_state_transition(&mut state, &EventType::AcceptedMint(event));
+
+        assert_eq!(token_metadata(&state, &token(1), BASE_URL), None);
+        assert_eq!(token_metadata(&state, &token(2), BASE_URL), None);
+    }
+
+    #[test]
+    fn should_return_metadata_of_minted_token() {
+        let mut state = state();
+        let event = mint_event(1, 1);
+        apply_state_transition(&mut state, &EventType::AcceptedMint(event.clone()));
+        apply_state_transition(
+            &mut state,
+            &EventType::MintedNft {
+                event_source: event.source(),
+            },
+        );
+
+        let metadata = token_metadata(&state, &token(1), BASE_URL).unwrap();
+
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&metadata.to_json()).unwrap(),
+            serde_json::json!({
+                "name": "Iluvatar #1",
+                "description": "The music of the Ainur",
+                "image": "https://2ibo7-dia.icp0.io/token/1/image.svg",
+                "attributes": []
+            })
+        );
+    }
+
+    #[test]
+    fn should_return_tombstone_of_burned_token() {
+        let mut state = state();
+        let event = mint_event(1, 1);
+        apply_state_transition(&mut state, &EventType::AcceptedMint(event.clone()));
+        apply_state_transition(
+            &mut state,
+            &EventType::MintedNft {
+                event_source: event.source(),
+            },
+        );
+        apply_state_transition(
+            &mut state,
+            &EventType::BurnedNft(transfer_event(30, holder(), Address::ZERO, 1)),
+        );
+
+        let metadata = token_metadata(&state, &token(1), BASE_URL).unwrap();
+
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&metadata.to_json()).unwrap(),
+            serde_json::json!({
+                "name": "Iluvatar #1",
+                "description": BURNED_TOKEN_DESCRIPTION,
+                "image": "https://2ibo7-dia.icp0.io/token/1/image.svg",
+                "attributes": [],
+                "burned": true
+            })
+        );
+        assert_eq!(
+            token_asset(&state, TokenPath::Image(token(1)), BASE_URL)
+                .unwrap()
+                .body,
+            br#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 1024 1024" width="1024" height="1024"></svg>"#
+        );
+    }
+
+    #[test]
+    fn should_name_tokens_after_the_metadata_of_their_collection() {
+        let mut state = state();
+        apply_state_transition(
+            &mut state,
+            &EventType::Upgrade(UpgradeArg {
+                add_collections: Some(vec![CollectionArg {
+                    id: "other".to_string(),
+                    ethereum_contract_address: "0x7a250d5630B4cF539739dF2C5dAcb4c659F2488D"
+                        .to_string(),
+                    last_scraped_block_number: Nat::from(100_u8),
+                    generator_config: None,
+                    collection_metadata: Some(CollectionMetadata {
+                        name: "Yavanna".to_string(),
+                        description: "The giver of fruits".to_string(),
+                        ..Default::default()
+                    }),
+                }]),
+                ..Default::default()
+            }),
+        );
+        let collection: CollectionId = "other".parse().unwrap();
+        for event in [
+            mint_event(1, 1),
+            MintEvent {
+                collection: Some(collection.clone()),
+                ..mint_event(2, 1)
+            },
+        ] {
+            apply_state_transition(&mut state, &EventType::AcceptedMint(event.clone()));
+            apply_state_transition(
+                &mut state,
+                &EventType::MintedNft {
+                    event_source: event.source(),
+                },
+            );
+        }
+
+        let metadata = token_metadata(&state, &token(1), BASE_URL).unwrap();
+        assert_eq!(metadata.name, "Iluvatar #1");
+        assert_eq!(metadata.description, "The music of the Ainur");
+
+        let token = TokenKey {
+            collection: Some(collection),
+            token_id: u256::ONE,
+        };
+        let metadata = token_metadata(&state, &token, BASE_URL).unwrap();
+        assert_eq!(metadata.name, "Yavanna #1");
+        assert_eq!(metadata.description, "The giver of fruits");
+        assert_eq!(
+            metadata.image,
+            "https://2ibo7-dia.icp0.io/other/token/1/image.svg"
+        );
+    }
+
+    fn state() -> State {
+        State::try_from(InitArg {
+            collection_metadata: Some(CollectionMetadata {
+                name: "Iluvatar".to_string(),
+                description: "The music of the Ainur".to_string(),
+                ..Default::default()
+            }),
+            ..init_arg()
+        })
+        .expect("init args should be valid")
+    }
+
+    fn token(token_id: u8) -> TokenKey {
+        TokenKey::from(u256::from(token_id))
+    }
+}
+
+mod collection_metadata {
+    use crate::metadata::{CollectionMetadata, ContractMetadata, MAX_SELLER_FEE_BASIS_POINTS};
+
+    #[test]
+    fn should_serve_contract_metadata() {
+        let metadata = ContractMetadata::from(&collection());
+
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&metadata.to_json()).unwrap(),
+            serde_json::json!({
+                "name": "Iluvatar",
+                "description": "The music of the Ainur",
+                "image": "https://iluvatar.example/collection.png",
+                "external_link": "https://iluvatar.example",
+                "seller_fee_basis_points": 250,
+                "fee_recipient": "0xdd2851Cdd40aE6536831558DD46db62fAc7A844d"
+            })
+        );
+    }
+
+    #[test]
+    fn should_accept_valid_collection_metadata() {
+        assert_eq!(collection().validate(), Ok(()));
+        assert_eq!(CollectionMetadata::default().validate(), Ok(()));
+    }
+
+    #[test]
+    fn should_reject_invalid_royalties() {
+        let too_high = CollectionMetadata {
+            seller_fee_basis_points: Some(MAX_SELLER_FEE_BASIS_POINTS + 1),
+            ..collection()
+        };
+        assert!(too_high
+            .validate()
+            .unwrap_err()
+            .contains("exceeds the maximum"));
+
+        let invalid_recipient = CollectionMetadata {
+            fee_recipient: Some("0xdd2851".to_string()),
+            ..collection()
+        };
+        assert!(invalid_recipient
+            .validate()
+            .unwrap_err()
+            .contains("invalid fee recipient"));
+
+        let without_recipient = CollectionMetadata {
+            fee_recipient: None,
+            ..collection()
+        };
+        assert!(without_recipient
+            .validate()
+            .unwrap_err()
+            .contains("requires a fee recipient"));
+    }
+
+    fn collection() -> CollectionMetadata {
+        CollectionMetadata {
+            name: "Iluvatar".to_string(),
+            description: "The music of the Ainur".to_string(),
+            external_url: Some("https://iluvatar.example".to_string()),
+            image: Some("https://iluvatar.example/collection.png".to_string()),
+            banner_image: None,
+            fee_recipient: Some("0xdd2851Cdd40aE6536831558DD46db62fAc7A844d".to_string()),
+            seller_fee_basis_points: Some(250),
+        }
+    }
+}
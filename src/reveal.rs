@@ -0,0 +1,218 @@
+//! Delayed reveal of the token traits.
+//!
+//! A collection configured with a [`RevealConfig`] starts hidden: every token serves placeholder
+//! metadata, and no trait is generated. Right after init, the minter draws a secret seed with
+//! `raw_rand` and publishes the provenance hash, a commitment to the generator configuration,
+//! to the assets it draws and to the seed. The traits of all tokens are derived from that seed once
+//! the collection is revealed, either by a controller or when the minter scraped the configured Ethereum block.
+//! The seed is then published, so that anyone can check that the assignments were fixed in advance.
+//! Since the published seed makes the draws predictable, tokens minted after the reveal
+//! also mix in the randomness drawn for their batch, see [`post_reveal_seed`].
+
+#[cfg(test)]
+mod tests;
+
+use crate::assets::Asset;
+use crate::generator::GeneratorConfig;
+use crate::guard::TimerGuard;
+use crate::logs::INFO;
+use crate::management::raw_rand;
+use crate::numeric::BlockNumber;
+use crate::state::{audit::process_event, event::EventType, mutate_state, read_state, TaskType};
+use candid::{CandidType, Deserialize, Nat};
+use ic_canister_log::log;
+use minicbor::{Decode, Encode};
+use std::collections::BTreeMap;
+
+/// Domain separator of the provenance hash.
+const PROVENANCE_DOMAIN_SEPARATOR: &[u8] = b"iluvatar-provenance";
+
+/// Domain separator of the seeds of the tokens minted after the reveal.
+const POST_REVEAL_DOMAIN_SEPARATOR: &[u8] = b"iluvatar-post-reveal";
+
+#[derive(CandidType, Deserialize, Clone, Debug, Default, Encode, Decode, PartialEq, Eq)]
+pub struct RevealConfig {
+    /// The SVG fragment drawn on the image of every token until the reveal.
+    #[n(0)]
+    pub placeholder_layer: Option<String>,
+    /// The collection is revealed once the minter scraped the logs up to this Ethereum block.
+    /// If not set, the collection is only revealed by a controller.
+    #[cbor(n(1), with = "crate::cbor::nat::option")]
+    pub reveal_block_number: Option<Nat>,
+}
+
+/// The reveal state of a hidden collection.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Reveal {
+    pub placeholder_layer: Option<String>,
+    pub reveal_block_number: Option<BlockNumber>,
+    /// The committed seed, if the minter already received it.
+    pub provenance: Option<Provenance>,
+    pub revealed: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Provenance {
+    /// The secret seed from which the traits are derived. Only published after the reveal.
+    pub seed: [u8; 32],
+    /// The commitment to the generator configuration and to the seed.
+    pub hash: [u8; 32],
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum RevealError {
+    NotController,
+    NotHidden,
+    ProvenanceNotCommitted,
+}
+
+impl TryFrom<RevealConfig> for Reveal {
+    type Error = String;
+
+    fn try_from(config: RevealConfig) -> Result<Self, Self::Error> {
+        if let Some(layer) = &config.placeholder_layer {
+            if layer.len() > crate::svg::MAX_LAYER_SIZE {
+                return Err(format!(
+                    "placeholder layer exceeds the maximum of {} bytes",
+                    crate::svg::MAX_LAYER_SIZE
+                ));
+            }
+        }
+        let reveal_block_number = config
+            .reveal_block_number
+            .map(BlockNumber::try_from)
+            .transpose()
+            .map_err(|e| format!("invalid reveal block number: {e}"))?;
+        Ok(Self {
+            placeholder_layer: config.placeholder_layer,
+            reveal_block_number,
+            provenance: None,
+            revealed: false,
+        })
+    }
+}
+
+impl Reveal {
+    pub fn is_hidden(&self) -> bool {
+        !self.revealed
+    }
+
+    /// Returns the seed from which the traits are derived, once revealed.
+    pub fn revealed_seed(&self) -> Option<[u8; 32]> {
+        if self.revealed {
+            self.provenance.as_ref().map(|provenance| provenance.seed)
+        } else {
+            None
+        }
+    }
+}
+
+/// Computes the provenance hash, i.e., the SHA-256 hash of the domain separator `iluvatar-provenance`,
+/// followed by the CBOR encoding of the generator configuration,
+/// by the SHA-256 hashes of the contents of the assets it draws in ascending order of their keys,
+/// and by the seed.
+pub fn provenance_hash(
+    config: &GeneratorConfig,
+    assets: &BTreeMap<String, Asset>,
+    seed: &[u8; 32],
+) -> [u8; 32] {
+    let mut preimage = PROVENANCE_DOMAIN_SEPARATOR.to_vec();
+    minicbor::encode(config, &mut preimage).expect("BUG: failed to encode generator config");
+    for key in config.asset_keys() {
+        let asset = assets
+            .get(key)
+            .unwrap_or_else(|| panic!("BUG: generator config draws unknown asset '{key}'"));
+        preimage.extend_from_slice(&asset.sha256);
+    }
+    preimage.extend_from_slice(seed);
+    ic_crypto_sha2::Sha256::hash(&preimage)
+}
+
+/// Computes the seed of a token minted after the reveal, i.e., the SHA-256 hash of the domain separator
+/// `iluvatar-post-reveal`, followed by the revealed seed and by the randomness drawn for its batch.
+pub fn post_reveal_seed(revealed_seed: &[u8; 32], batch_seed: &[u8; 32]) -> [u8; 32] {
+    let mut preimage = POST_REVEAL_DOMAIN_SEPARATOR.to_vec();
+    preimage.extend_from_slice(revealed_seed);
+    preimage.extend_from_slice(batch_seed);
+    ic_crypto_sha2::Sha256::hash(&preimage)
+}
+
+/// Draws the secret seed of a hidden collection and publishes the provenance hash.
+pub async fn commit_provenance() {
+    let _guard = match TimerGuard::new(TaskType::CommitProvenance) {
+        Ok(guard) => guard,
+        Err(_) => return,
+    };
+    let is_pending = read_state(|s| {
+        s.reveal
+            .as_ref()
+            .is_some_and(|reveal| reveal.provenance.is_none())
+    });
+    if !is_pending {
+        return;
+    }
+    match raw_rand().await {
+        Ok(seed) => mutate_state(|s| {
+            let provenance_hash = provenance_hash(&s.generator_config, &s.assets, &seed);
+            log!(
+                INFO,
+                "[commit_provenance]: committed to provenance hash {}",
+                hex::encode(provenance_hash)
+            );
+            process_event(
+                s,
+                EventType::CommittedProvenance {
+                    seed,
+                    provenance_hash,
+                },
+            )
+        }),
+        Err(err) => {
+            log!(
+                INFO,
+                "[commit_provenance]: failed to get randomness: {err}, rescheduling"
+            );
+            ic_cdk_timers::set_timer(crate::MINT_RETRY_DELAY, || {
+                ic_cdk::spawn(commit_provenance())
+            });
+        }
+    }
+}
+
+/// Reveals the traits of a hidden collection.
+pub fn reveal() -> Result<(), RevealError> {
+    mutate_state(|s| {
+        let reveal = s.reveal.as_ref().ok_or(RevealError::NotHidden)?;
+        if !reveal.is_hidden() {
+            return Err(RevealError::NotHidden);
+        }
+        let seed = reveal
+            .provenance
+            .as_ref()
+            .ok_or(RevealError::ProvenanceNotCommitted)?
+            .seed;
+        log!(
+            INFO,
+            "[reveal]: revealing the collection with seed {}",
+            hex::encode(seed)
+        );
+        process_event(s, EventType::Revealed { seed });
+        Ok(())
+    })
+}
+
+/// Reveals the traits of a hidden collection if the minter scraped the reveal block.
+pub fn reveal_if_block_reached() {
+    let is_reached = read_state(|s| {
+        s.reveal.as_ref().is_some_and(|reveal| {
+            reveal.is_hidden()
+                && reveal.provenance.is_some()
+                && reveal
+                    .reveal_block_number
+                    .is_some_and(|block_number| block_number <= s.last_scraped_block_number)
+        })
+    });
+    if is_reached {
+        reveal().expect("BUG: failed to reveal a hidden collection");
+    }
+}
@@ -0,0 +1,213 @@
+use crate::assets::Asset;
+use crate::generator::{generate_attributes, GeneratorConfig, TraitType, TraitValue};
+use crate::lifecycle::init::InitArg;
+use crate::lifecycle::upgrade::UpgradeArg;
+use crate::reveal::{post_reveal_seed, provenance_hash, RevealConfig};
+use crate::state::audit::{apply_state_transition, EventType};
+use crate::state::State;
+use crate::test_fixtures::{init_arg, mint_event};
+use ethnum::u256;
+use std::collections::BTreeMap;
+
+const SEED: [u8; 32] = [42; 32];
+
+mod provenance {
+    use super::*;
+
+    #[test]
+    fn should_commit_to_config_and_seed() {
+        let hash = provenance_hash(&config(), &BTreeMap::new(), &SEED);
+
+        assert_eq!(hash, provenance_hash(&config(), &BTreeMap::new(), &SEED));
+        assert_ne!(
+            hash,
+            provenance_hash(&config(), &BTreeMap::new(), &[43; 32])
+        );
+        assert_ne!(
+            hash,
+            provenance_hash(&GeneratorConfig::default(), &BTreeMap::new(), &SEED)
+        );
+    }
+
+    #[test]
+    fn should_commit_to_drawn_assets() {
+        let mut config = config();
+        config.trait_types[0].values[1].asset = Some("gold.png".to_string());
+        let assets = |sha256| {
+            BTreeMap::from([(
+                "gold.png".to_string(),
+                Asset {
+                    key: "gold.png".to_string(),
+                    content_type: "image/png".to_string(),
+                    sha256,
+                    size: 4,
+                    content_index: 0,
+                },
+            )])
+        };
+        let mut unrelated_assets = assets([1; 32]);
+        unrelated_assets.insert(
+            "silver.png".to_string(),
+            assets([3; 32])["gold.png"].clone(),
+        );
+
+        let hash = provenance_hash(&config, &assets([1; 32]), &SEED);
+
+        assert_ne!(hash, provenance_hash(&config, &assets([2; 32]), &SEED));
+        assert_eq!(hash, provenance_hash(&config, &unrelated_assets, &SEED));
+    }
+}
+
+mod reveal {
+    use super::*;
+
+    #[test]
+    fn should_not_generate_traits_while_hidden() {
+        let mut state = hidden_state();
+        mint(&mut state, 1);
+        commit_provenance(&mut state);
+        mint(&mut state, 2);
+
+        assert!(state.is_hidden());
+        assert_eq!(state.attributes_of(&u256::from(1_u8).into()), Some(&[][..]));
+        assert_eq!(state.attributes_of(&u256::from(2_u8).into()), Some(&[][..]));
+    }
+
+    #[test]
+    fn should_generate_traits_from_committed_seed_on_reveal() {
+        let mut state = hidden_state();
+        mint(&mut state, 1);
+        commit_provenance(&mut state);
+        apply_state_transition(&mut state, &EventType::Revealed { seed: SEED });
+
+        assert!(!state.is_hidden());
+        assert_eq!(
+            state.attributes_of(&u256::from(1_u8).into()).unwrap(),
+            generate_attributes(&config(), &mint_event(1, 1), Some(&SEED))
+        );
+    }
+
+    #[test]
+    fn should_mix_batch_randomness_into_traits_of_tokens_minted_after_reveal() {
+        const BATCH_SEED: [u8; 32] = [7; 32];
+        let mut state = hidden_state();
+        commit_provenance(&mut state);
+        apply_state_transition(&mut state, &EventType::Revealed { seed: SEED });
+        let event = mint_event(2, 2);
+        apply_state_transition(&mut state, &EventType::AcceptedMint(event.clone()));
+
+        assert_eq!(state.events_without_seed(), vec![event.source()]);
+        assert!(!state.is_ready_to_mint(&event.source()));
+
+        apply_state_transition(
+            &mut state,
+            &EventType::ReceivedRandomness {
+                seed: BATCH_SEED,
+                event_sources: vec![event.source()],
+            },
+        );
+        apply_state_transition(
+            &mut state,
+            &EventType::MintedNft {
+                event_source: event.source(),
+            },
+        );
+
+        let seed = post_reveal_seed(&SEED, &BATCH_SEED);
+        assert_ne!(seed, SEED);
+        let minted_event = state.minted_event_of(&u256::from(2_u8).into()).unwrap();
+        assert_eq!(minted_event.seed, Some(seed));
+        assert_eq!(
+            minted_event.attributes,
+            generate_attributes(&config(), &event, Some(&seed))
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "seed that was not committed")]
+    fn should_not_reveal_with_other_seed() {
+        let mut state = hidden_state();
+        commit_provenance(&mut state);
+        apply_state_transition(&mut state, &EventType::Revealed { seed: [43; 32] });
+    }
+
+    #[test]
+    #[should_panic(expected = "applying upgrade event should succeed")]
+    fn should_not_change_config_after_commitment() {
+        let mut state = hidden_state();
+        commit_provenance(&mut state);
+        apply_state_transition(
+            &mut state,
+            &EventType::Upgrade(UpgradeArg {
+                generator_config: Some(GeneratorConfig::default()),
+                ..Default::default()
+            }),
+        );
+    }
+
+    #[test]
+    fn should_not_require_batch_randomness_for_hidden_collections() {
+        let mut state = hidden_state();
+        let event = mint_event(1, 1);
+        apply_state_transition(&mut state, &EventType::AcceptedMint(event.clone()));
+
+        assert!(state.events_without_seed().is_empty());
+        assert!(state.is_ready_to_mint(&event.source()));
+    }
+}
+
+fn hidden_state() -> State {
+    State::try_from(InitArg {
+        generator_config: Some(config()),
+        reveal_config: Some(RevealConfig {
+            placeholder_layer: Some("<rect/>".to_string()),
+            reveal_block_number: None,
+        }),
+        ..init_arg()
+    })
+    .expect("init args should be valid")
+}
+
+fn commit_provenance(state: &mut State) {
+    apply_state_transition(
+        state,
+        &EventType::CommittedProvenance {
+            seed: SEED,
+            provenance_hash: provenance_hash(&config(), &BTreeMap::new(), &SEED),
+        },
+    );
+}
+
+fn mint(state: &mut State, token_id: u64) {
+    let event = mint_event(token_id, token_id);
+    apply_state_transition(state, &EventType::AcceptedMint(event.clone()));
+    apply_state_transition(
+        state,
+        &EventType::MintedNft {
+            event_source: event.source(),
+        },
+    );
+}
+
+fn config() -> GeneratorConfig {
+    GeneratorConfig {
+        trait_types: vec![TraitType {
+            name: "Background".to_string(),
+            values: vec![
+                TraitValue {
+                    name: "Blue".to_string(),
+                    weight: 1,
+                    layer: None,
+                    asset: None,
+                },
+                TraitValue {
+                    name: "Gold".to_string(),
+                    weight: 1,
+                    layer: None,
+                    asset: None,
+                },
+            ],
+        }],
+        canvas: None,
+    }
+}
@@ -1,11 +1,22 @@
 use crate::address::Address;
-use crate::eth_logs::{EventSource, MintEvent};
-use crate::eth_rpc::BlockTag;
-
+use crate::assets::Asset;
+use crate::collections::{Collection, CollectionArg, CollectionId, TokenKey};
+use crate::endpoints::GenerationStatus;
+use crate::eth_logs::{EventSource, MintEvent, ScrapingMode, TokenStandard, TransferEvent};
+use crate::eth_rpc::{BlockHeight, Hash};
+use crate::eth_rpc_client::consensus::ConsensusConfig;
+use crate::eth_rpc_client::providers::{validate_providers, RpcProvider};
+use crate::eth_rpc_client::transport::RpcTransportConfig;
+use crate::eth_rpc_client::EthRpcClient;
+use crate::generator::{generate_attributes, Attribute, GeneratorConfig};
 use crate::lifecycle::upgrade::UpgradeArg;
 use crate::lifecycle::EthereumNetwork;
+use crate::map::MultiKeyMap;
+use crate::metadata::CollectionMetadata;
 use crate::numeric::BlockNumber;
+use crate::reveal::{post_reveal_seed, provenance_hash, Provenance, Reveal};
 
+use ethnum::u256;
 use std::cell::RefCell;
 use std::collections::{btree_map, BTreeMap, BTreeSet, HashSet};
 use strum_macros::EnumIter;
@@ -20,9 +31,16 @@ thread_local! {
     pub static STATE: RefCell<Option<State>> = RefCell::default();
 }
 
+/// The maximum number of block hashes kept to detect chain reorganizations.
+pub const MAX_BLOCK_HASHES: usize = 128;
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct MintedEvent {
     pub mint_event: MintEvent,
+    /// The randomness used to generate the traits of the minted token.
+    pub seed: Option<[u8; 32]>,
+    /// The traits generated for the minted token.
+    pub attributes: Vec<Attribute>,
 }
 
 impl MintedEvent {
@@ -31,19 +49,116 @@ impl MintedEvent {
     }
 }
 
+/// A change to the tokens made by an event included in a block,
+/// which is reverted if the block is no longer part of the canonical chain.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TokenChange {
+    /// The event minting the token was accepted.
+    Accepted {
+        source: EventSource,
+        token: TokenKey,
+    },
+    /// The token was minted, with the randomness received for its event.
+    Minted {
+        token: TokenKey,
+        batch_seed: Option<[u8; 32]>,
+    },
+    /// The mint of a burned token was replaced by a new mint.
+    ReplacedMint {
+        token: TokenKey,
+        minted_event: MintedEvent,
+    },
+    /// Editions of the token were put into circulation.
+    AddedSupply { token: TokenKey, amount: u256 },
+    /// Editions of the token were taken out of circulation.
+    SubtractedSupply { token: TokenKey, amount: u256 },
+    /// The token changed owner.
+    Owner {
+        token: TokenKey,
+        previous: Option<Address>,
+    },
+    /// The token was burned, or minted again after it was burned.
+    Burn {
+        token: TokenKey,
+        previous: Option<TransferEvent>,
+    },
+    /// The event was recorded as invalid.
+    Invalid {
+        source: EventSource,
+        token: Option<TokenKey>,
+        previous: Option<EventSource>,
+    },
+}
+
+impl TokenChange {
+    fn token(&self) -> Option<&TokenKey> {
+        match self {
+            TokenChange::Accepted { token, .. }
+            | TokenChange::Minted { token, .. }
+            | TokenChange::ReplacedMint { token, .. }
+            | TokenChange::AddedSupply { token, .. }
+            | TokenChange::SubtractedSupply { token, .. }
+            | TokenChange::Owner { token, .. }
+            | TokenChange::Burn { token, .. } => Some(token),
+            TokenChange::Invalid { token, .. } => token.as_ref(),
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub struct State {
     pub ethereum_network: EthereumNetwork,
+    /// The JSON-RPC providers configured by an upgrade, queried in this order.
+    /// The default providers of the network are queried if empty.
+    pub rpc_providers: Vec<RpcProvider>,
+    pub consensus_config: ConsensusConfig,
+    pub rpc_transport: RpcTransportConfig,
+    /// Whether accepted events are checked against the receipts of their transactions before being minted.
+    pub verify_receipts: bool,
     pub minter_address: Address,
     pub ethereum_contract_address: Address,
-    pub ethereum_block_height: BlockTag,
+    pub ethereum_block_height: BlockHeight,
+    pub scraping_mode: ScrapingMode,
+    pub token_standard: TokenStandard,
     pub first_scraped_block_number: BlockNumber,
     pub last_scraped_block_number: BlockNumber,
     pub last_observed_block_number: Option<BlockNumber>,
-    pub events_to_mint: BTreeMap<EventSource, MintEvent>,
-    pub minted_events: BTreeMap<EventSource, MintedEvent>,
+    /// The accepted events, by source and by the token they mint.
+    pub events_to_mint: MultiKeyMap<EventSource, TokenKey, MintEvent>,
+    /// The randomness received for events to mint.
+    pub seeds: BTreeMap<EventSource, [u8; 32]>,
+    /// The randomness received for events rolled back by a chain reorganization, with the block that included them.
+    /// It is used again if the events are included again, so that a reorganization cannot draw new traits.
+    pub rolled_back_seeds: BTreeMap<EventSource, (BlockNumber, [u8; 32])>,
+    /// The minted tokens, by token and by the source of the event that minted them.
+    pub minted_events: MultiKeyMap<TokenKey, EventSource, MintedEvent>,
     pub invalid_events: BTreeMap<EventSource, String>,
+    /// The source of the invalid events, by transferred token.
+    pub invalid_token_ids: BTreeMap<TokenKey, EventSource>,
+    /// The current owner of each token.
+    /// Only mints are tracked unless the minter scrapes all transfers.
+    pub owners: BTreeMap<TokenKey, Address>,
+    /// The tokens held by each owner.
+    pub tokens_by_owner: BTreeMap<Address, BTreeSet<TokenKey>>,
+    /// The burned tokens, with the transfer that burned them.
+    pub burned_tokens: BTreeMap<TokenKey, TransferEvent>,
+    /// The number of editions of each token in circulation, i.e., minted and not burned.
+    pub supplies: BTreeMap<TokenKey, u256>,
     pub skipped_blocks: BTreeSet<BlockNumber>,
+    /// The hashes of the last blocks processed by the minter, i.e.,
+    /// of the blocks including accepted events and of the blocks the minter synced to.
+    pub block_hashes: BTreeMap<BlockNumber, Hash>,
+    /// The changes to the tokens made by the events of the blocks with a recorded hash,
+    /// in the order they were applied, with the block including each event.
+    pub token_changes: Vec<(BlockNumber, TokenChange)>,
+    pub generator_config: GeneratorConfig,
+    /// The additional collections watched by the minter, by ID.
+    pub collections: BTreeMap<CollectionId, Collection>,
+    pub collection_metadata: CollectionMetadata,
+    /// The assets committed by the controllers, by key.
+    pub assets: BTreeMap<String, Asset>,
+    /// The reveal state of the collection, if it was launched hidden.
+    pub reveal: Option<Reveal>,
 
     /// Locks preventing concurrent execution timer tasks
     pub active_tasks: HashSet<TaskType>,
@@ -62,41 +177,312 @@ pub enum InvalidStateError {
     InvalidMinimumWithdrawalAmount(String),
     InvalidLastScrapedBlockNumber(String),
     InvalidMinterAddress(String),
+    InvalidGeneratorConfig(String),
+    InvalidRevealConfig(String),
+    InvalidCollectionMetadata(String),
+    InvalidScrapingMode(String),
+    InvalidCollection(String),
+    InvalidEthereumNetwork(String),
+    InvalidRpcProviders(String),
+    InvalidConsensusConfig(String),
 }
 
 impl State {
     pub fn validate_config(&self) -> Result<(), InvalidStateError> {
+        if let EthereumNetwork::Custom(chain_spec) = &self.ethereum_network {
+            chain_spec
+                .validate()
+                .map_err(InvalidStateError::InvalidEthereumNetwork)?;
+        }
+        validate_providers(&self.rpc_providers).map_err(InvalidStateError::InvalidRpcProviders)?;
+        self.consensus_config
+            .validate(EthRpcClient::from_state(self).provider_count())
+            .map_err(InvalidStateError::InvalidConsensusConfig)?;
         if self.ethereum_contract_address == Address::ZERO {
             return Err(InvalidStateError::InvalidEthereumContractAddress(
                 "ethereum_contract_address cannot be the zero address".to_string(),
             ));
         }
+        self.generator_config
+            .validate()
+            .map_err(InvalidStateError::InvalidGeneratorConfig)?;
+        crate::svg::validate_layers(&self.generator_config, &self.assets)
+            .map_err(InvalidStateError::InvalidGeneratorConfig)?;
+        self.collection_metadata
+            .validate()
+            .map_err(InvalidStateError::InvalidCollectionMetadata)?;
+        if self.token_standard == TokenStandard::Erc1155
+            && self.scraping_mode == ScrapingMode::AllTransfers
+        {
+            return Err(InvalidStateError::InvalidScrapingMode(
+                "ERC-1155 tokens have no single owner to track".to_string(),
+            ));
+        }
+        for (id, collection) in &self.collections {
+            let invalid_collection =
+                |e: String| InvalidStateError::InvalidCollection(format!("collection '{id}': {e}"));
+            collection
+                .generator_config
+                .validate()
+                .map_err(invalid_collection)?;
+            crate::svg::validate_layers(&collection.generator_config, &self.assets)
+                .map_err(invalid_collection)?;
+            collection.metadata.validate().map_err(invalid_collection)?;
+        }
         Ok(())
     }
 
+    /// Checks that the given event mints a token that was neither minted nor accepted before,
+    /// unless the token was burned since.
+    pub fn validate_mint(&self, event: &MintEvent) -> Result<(), String> {
+        if let Some(reason) = self.invalid_events.get(&event.source()) {
+            return Err(format!(
+                "event {} was already rejected: {reason}",
+                event.source()
+            ));
+        }
+        let token = event.token_key();
+        if let Some(minted_event) = self.minted_events.get(&token) {
+            // Burned tokens can be minted again.
+            if !self.is_burned(&token) {
+                return Err(format!(
+                    "token {token} was already minted by {}",
+                    minted_event.source()
+                ));
+            }
+        }
+        if let Some(event_to_mint) = self.events_to_mint.get_alt(&token) {
+            return Err(format!(
+                "token {token} is already being minted by {}",
+                event_to_mint.source()
+            ));
+        }
+        Ok(())
+    }
+
+    /// Checks whether the given event mints more editions of an ERC-1155 token
+    /// that was already accepted, which only increases its supply.
+    pub fn is_new_edition(&self, event: &MintEvent) -> bool {
+        let token = event.token_key();
+        self.token_standard == TokenStandard::Erc1155
+            && (self.minted_events.contains(&token) || self.events_to_mint.contains_alt(&token))
+    }
+
     fn record_event_to_mint(&mut self, event: &MintEvent) {
         let event_source = event.source();
         assert!(
-            !self.events_to_mint.contains_key(&event_source),
+            !self.events_to_mint.contains(&event_source),
             "there must be no two different events with the same source"
         );
-        assert!(!self.minted_events.contains_key(&event_source));
+        assert!(!self.minted_events.contains_alt(&event_source));
         assert!(!self.invalid_events.contains_key(&event_source));
+        // Event logs recorded before tokens were indexed may accept several events minting the same token.
+        // The later events are recorded as invalid when replayed.
+        let block_number = event.block_number;
+        if let Err(reason) = self.validate_mint(event) {
+            let _ = self.record_invalid_deposit(
+                event_source,
+                Some(event.token_key()),
+                reason,
+                Some(block_number),
+            );
+            return;
+        }
+
+        // Minting a burned token again replaces the token that was burned.
+        let token = event.token_key();
+        if let Some((token, _, minted_event)) = self.minted_events.remove_entry(&token) {
+            self.record_token_change(
+                block_number,
+                TokenChange::ReplacedMint {
+                    token,
+                    minted_event,
+                },
+            );
+        }
+        if self.is_burned(&token) {
+            self.change_burn(block_number, token.clone(), None);
+        }
+        self.events_to_mint
+            .try_insert(event_source, token.clone(), event.clone())
+            .expect("BUG: event to mint was already recorded");
+        if let Some((_, seed)) = self.rolled_back_seeds.remove(&event_source) {
+            self.seeds.insert(event_source, seed);
+        }
+        self.record_token_change(
+            block_number,
+            TokenChange::Accepted {
+                source: event_source,
+                token: token.clone(),
+            },
+        );
+        self.add_supply(block_number, token.clone(), event.amount());
+        // The token may already have an owner if its mint was in a skipped block
+        // that was scraped again after later transfers.
+        if self.token_standard == TokenStandard::Erc721 && !self.owners.contains_key(&token) {
+            self.change_owner(block_number, token, Some(event.to_address));
+        }
+    }
+
+    fn record_edition(&mut self, event: &MintEvent) {
+        assert!(
+            self.is_new_edition(event),
+            "only editions of accepted ERC-1155 tokens can be minted again"
+        );
+        if self.is_burned(&event.token_key()) {
+            self.change_burn(event.block_number, event.token_key(), None);
+        }
+        self.add_supply(event.block_number, event.token_key(), event.amount());
+    }
+
+    fn add_supply(&mut self, block_number: BlockNumber, token: TokenKey, amount: u256) {
+        let supply = self.supplies.entry(token.clone()).or_default();
+        *supply = supply.saturating_add(amount);
+        self.record_token_change(block_number, TokenChange::AddedSupply { token, amount });
+    }
+
+    /// Takes editions of a token out of circulation, returning the remaining supply if it is known.
+    /// The supply of a token is forgotten once all its editions are out of circulation.
+    fn subtract_supply(
+        &mut self,
+        block_number: BlockNumber,
+        token: &TokenKey,
+        amount: u256,
+    ) -> Option<u256> {
+        let btree_map::Entry::Occupied(mut supply) = self.supplies.entry(token.clone()) else {
+            return None;
+        };
+        let amount = amount.min(*supply.get());
+        *supply.get_mut() -= amount;
+        let remaining = *supply.get();
+        if remaining == u256::ZERO {
+            supply.remove();
+        }
+        self.record_token_change(
+            block_number,
+            TokenChange::SubtractedSupply {
+                token: token.clone(),
+                amount,
+            },
+        );
+        Some(remaining)
+    }
+
+    /// Returns the number of editions of the given token in circulation.
+    pub fn supply_of(&self, token: &TokenKey) -> u256 {
+        self.supplies.get(token).copied().unwrap_or_default()
+    }
+
+    /// Records the new owner of a transferred token.
+    ///
+    /// The previous owner is not checked against the sender of the transfer,
+    /// since transfers that happened before the minter scraped all transfers were not recorded.
+    fn record_transfer(&mut self, event: &TransferEvent) {
+        self.change_owner(
+            event.block_number,
+            event.token_key(),
+            Some(event.to_address),
+        );
+    }
+
+    /// Records burned editions of a token.
+    ///
+    /// A token is burned, and no longer has any owner, once its whole supply was burned.
+    /// Tokens minted before the first scraped block have no known supply and are burned at once.
+    fn record_burn(&mut self, event: &TransferEvent) {
+        let token = event.token_key();
+        let remaining = self.subtract_supply(event.block_number, &token, event.amount());
+        if remaining.is_some_and(|supply| supply != u256::ZERO) {
+            return;
+        }
+        self.change_owner(event.block_number, token.clone(), None);
+        self.change_burn(event.block_number, token, Some(event.clone()));
+    }
 
-        self.events_to_mint.insert(event_source, event.clone());
+    /// Changes the owner of a token in the given block, remembering the previous owner.
+    fn change_owner(&mut self, block_number: BlockNumber, token: TokenKey, owner: Option<Address>) {
+        let previous = self.owner_of(&token);
+        match owner {
+            Some(owner) => self.record_owner(token.clone(), owner),
+            None => self.remove_owner(&token),
+        }
+        self.record_token_change(block_number, TokenChange::Owner { token, previous });
+    }
+
+    /// Burns a token, or clears the burn of a token minted again, in the given block,
+    /// remembering the previous burn.
+    fn change_burn(
+        &mut self,
+        block_number: BlockNumber,
+        token: TokenKey,
+        burn: Option<TransferEvent>,
+    ) {
+        let previous = match burn {
+            Some(burn) => self.burned_tokens.insert(token.clone(), burn),
+            None => self.burned_tokens.remove(&token),
+        };
+        self.record_token_change(block_number, TokenChange::Burn { token, previous });
+    }
+
+    fn record_owner(&mut self, token: TokenKey, owner: Address) {
+        self.remove_owner(&token);
+        self.owners.insert(token.clone(), owner);
+        self.tokens_by_owner.entry(owner).or_default().insert(token);
+    }
+
+    fn remove_owner(&mut self, token: &TokenKey) {
+        if let Some(previous_owner) = self.owners.remove(token) {
+            if let btree_map::Entry::Occupied(mut entry) =
+                self.tokens_by_owner.entry(previous_owner)
+            {
+                entry.get_mut().remove(token);
+                if entry.get().is_empty() {
+                    entry.remove();
+                }
+            }
+        }
+    }
+
+    pub fn is_burned(&self, token: &TokenKey) -> bool {
+        self.burned_tokens.contains_key(token)
+    }
+
+    pub fn owner_of(&self, token: &TokenKey) -> Option<Address> {
+        self.owners.get(token).copied()
+    }
+
+    /// Returns the IDs of the tokens of the given collection held by the given owner, in ascending order.
+    pub fn tokens_of<'a>(
+        &'a self,
+        owner: &Address,
+        collection: Option<&'a CollectionId>,
+    ) -> impl Iterator<Item = u256> + 'a {
+        self.tokens_by_owner
+            .get(owner)
+            .into_iter()
+            .flatten()
+            .filter(move |token| token.collection.as_ref() == collection)
+            .map(|token| token.token_id)
     }
 
     pub fn has_events_to_mint(&self) -> bool {
         !self.events_to_mint.is_empty()
     }
 
-    fn record_invalid_deposit(&mut self, source: EventSource, error: String) -> bool {
+    /// Records an invalid event, together with the block including it if it is known.
+    fn record_invalid_deposit(
+        &mut self,
+        source: EventSource,
+        token: Option<TokenKey>,
+        error: String,
+        block_number: Option<BlockNumber>,
+    ) -> bool {
         assert!(
-            !self.events_to_mint.contains_key(&source),
+            !self.events_to_mint.contains(&source),
             "attempted to mark an accepted event as invalid"
         );
         assert!(
-            !self.minted_events.contains_key(&source),
+            !self.minted_events.contains_alt(&source),
             "attempted to mark a minted event {source:?} as invalid"
         );
 
@@ -104,27 +490,267 @@ impl State {
             btree_map::Entry::Occupied(_) => false,
             btree_map::Entry::Vacant(entry) => {
                 entry.insert(error);
+                let previous = token
+                    .clone()
+                    .and_then(|token| self.invalid_token_ids.insert(token, source));
+                if let Some(block_number) = block_number {
+                    self.record_token_change(
+                        block_number,
+                        TokenChange::Invalid {
+                            source,
+                            token,
+                            previous,
+                        },
+                    );
+                }
                 true
             }
         }
     }
 
-    fn record_successful_mint(&mut self, source: EventSource) {
+    /// Stops minting an accepted event that the receipt of its transaction contradicts.
+    /// Editions of the token accepted from other events remain in circulation.
+    fn record_rejected_mint(&mut self, source: EventSource, reason: String) {
+        let (_, token, event) = match self.events_to_mint.remove_entry(&source) {
+            Some(entry) => entry,
+            None => panic!("attempted to reject an unknown event {source:?}"),
+        };
+        self.seeds.remove(&source);
+        let block_number = event.block_number;
+        if self.subtract_supply(block_number, &token, event.amount()) == Some(u256::ZERO) {
+            self.change_owner(block_number, token.clone(), None);
+        }
         assert!(
-            !self.invalid_events.contains_key(&source),
-            "attempted to mint an event previously marked as invalid {source:?}"
+            self.record_invalid_deposit(source, Some(token), reason, Some(block_number)),
+            "attempted to reject an event twice {source:?}"
         );
-        let mint_event = match self.events_to_mint.remove(&source) {
-            Some(event) => event,
+    }
+
+    fn record_successful_mint(&mut self, source: EventSource) {
+        // Duplicate mints of legacy event logs are recorded as invalid, see `record_event_to_mint`.
+        if self.invalid_events.contains_key(&source) {
+            return;
+        }
+        let (_, token, mint_event) = match self.events_to_mint.remove_entry(&source) {
+            Some(entry) => entry,
             None => panic!("attempted to mint ckETH for an unknown event {source:?}"),
         };
 
-        assert_eq!(
+        // The traits of hidden collections are derived from the provenance seed once revealed,
+        // mixed with the randomness of the batch for tokens minted after the reveal.
+        // Tokens minted after the reveal before batch randomness was required have no batch seed.
+        let batch_seed = self.seeds.remove(&source);
+        let reveal = self.reveal.as_ref().filter(|_| token.collection.is_none());
+        let seed = match reveal {
+            Some(reveal) => match (reveal.revealed_seed(), batch_seed) {
+                (Some(revealed_seed), Some(batch_seed)) => {
+                    Some(post_reveal_seed(&revealed_seed, &batch_seed))
+                }
+                (revealed_seed, _) => revealed_seed,
+            },
+            None => batch_seed,
+        };
+        let attributes = match (reveal, seed) {
+            (Some(reveal), _) if reveal.is_hidden() => vec![],
+            (_, seed) => generate_attributes(
+                self.generator_config_of(token.collection.as_ref())
+                    .expect("BUG: attempted to mint a token of an unknown collection"),
+                &mint_event,
+                seed.as_ref(),
+            ),
+        };
+        let block_number = mint_event.block_number;
+        let minted_event = MintedEvent {
+            mint_event,
+            seed,
+            attributes,
+        };
+        assert!(
             self.minted_events
-                .insert(source, MintedEvent { mint_event }),
-            None,
-            "attempted to mint ckETH twice for the same event {source:?}"
+                .try_insert(token.clone(), source, minted_event)
+                .is_ok(),
+            "attempted to mint token {token} twice for the event {source:?}"
         );
+        self.record_token_change(block_number, TokenChange::Minted { token, batch_seed });
+    }
+
+    fn record_randomness(&mut self, seed: [u8; 32], sources: &[EventSource]) {
+        // Duplicate mints of legacy event logs are recorded as invalid, see `record_event_to_mint`.
+        for source in sources
+            .iter()
+            .filter(|source| !self.invalid_events.contains_key(source))
+        {
+            assert!(
+                self.events_to_mint.contains(source),
+                "attempted to record randomness for an event that is not to be minted {source:?}"
+            );
+            assert_eq!(
+                self.seeds.insert(*source, seed),
+                None,
+                "attempted to record randomness twice for the same event {source:?}"
+            );
+        }
+    }
+
+    /// Returns the events to mint that did not receive randomness yet.
+    /// A collection configured at init that is still hidden uses the provenance seed instead.
+    pub fn events_without_seed(&self) -> Vec<EventSource> {
+        self.events_to_mint
+            .keys()
+            .filter(|source| !self.is_ready_to_mint(source))
+            .copied()
+            .collect()
+    }
+
+    pub fn is_ready_to_mint(&self, source: &EventSource) -> bool {
+        let is_hidden_collection = self.is_hidden()
+            && self
+                .events_to_mint
+                .get_entry(source)
+                .is_some_and(|(token, _)| token.collection.is_none());
+        is_hidden_collection || self.seeds.contains_key(source)
+    }
+
+    /// Returns true if the collection was launched hidden and is not revealed yet.
+    pub fn is_hidden(&self) -> bool {
+        self.reveal.as_ref().is_some_and(Reveal::is_hidden)
+    }
+
+    fn record_provenance(&mut self, seed: [u8; 32], hash: [u8; 32]) {
+        assert_eq!(
+            provenance_hash(&self.generator_config, &self.assets, &seed),
+            hash,
+            "BUG: provenance hash does not match the generator config, the assets and the seed"
+        );
+        let reveal = self
+            .reveal
+            .as_mut()
+            .expect("BUG: attempted to commit the provenance of a collection that is not hidden");
+        assert_eq!(
+            reveal.provenance, None,
+            "BUG: attempted to commit the provenance twice"
+        );
+        reveal.provenance = Some(Provenance { seed, hash });
+    }
+
+    fn record_reveal(&mut self, seed: [u8; 32]) {
+        let reveal = self
+            .reveal
+            .as_mut()
+            .expect("BUG: attempted to reveal a collection that is not hidden");
+        assert!(!reveal.revealed, "BUG: attempted to reveal twice");
+        assert_eq!(
+            reveal.provenance.as_ref().map(|provenance| provenance.seed),
+            Some(seed),
+            "BUG: attempted to reveal with a seed that was not committed"
+        );
+        reveal.revealed = true;
+        for minted_event in self.minted_events.values_mut() {
+            if minted_event.mint_event.collection.is_some() {
+                continue;
+            }
+            minted_event.seed = Some(seed);
+            minted_event.attributes = generate_attributes(
+                &self.generator_config,
+                &minted_event.mint_event,
+                Some(&seed),
+            );
+        }
+    }
+
+    /// Returns the event that minted the given token, if any.
+    pub fn minted_event_of(&self, token: &TokenKey) -> Option<&MintedEvent> {
+        self.minted_events.get(token)
+    }
+
+    /// Returns the traits generated for the given token, if it was minted.
+    pub fn attributes_of(&self, token: &TokenKey) -> Option<&[Attribute]> {
+        self.minted_event_of(token)
+            .map(|event| event.attributes.as_slice())
+    }
+
+    /// Returns the metadata of the given collection, or `None` if the collection is not watched.
+    /// The collection configured at init has no collection ID.
+    pub fn collection_metadata_of(
+        &self,
+        collection: Option<&CollectionId>,
+    ) -> Option<&CollectionMetadata> {
+        match collection {
+            Some(id) => self
+                .collections
+                .get(id)
+                .map(|collection| &collection.metadata),
+            None => Some(&self.collection_metadata),
+        }
+    }
+
+    /// Returns the traits of the tokens of the given collection that were minted.
+    pub fn minted_attributes<'a>(
+        &'a self,
+        collection: Option<&'a CollectionId>,
+    ) -> impl Iterator<Item = (&'a TokenKey, &'a [Attribute])> + 'a {
+        self.minted_events
+            .iter()
+            .filter(move |(token, _, _)| token.collection.as_ref() == collection)
+            .map(|(token, _, minted_event)| (token, minted_event.attributes.as_slice()))
+    }
+
+    /// Returns the minted tokens whose image embeds the asset with the given key.
+    pub fn tokens_drawing_asset<'a>(&'a self, key: &'a str) -> impl Iterator<Item = &'a TokenKey> {
+        self.minted_events
+            .iter()
+            .filter(move |(token, _, minted_event)| {
+                self.generator_config_of(token.collection.as_ref())
+                    .is_some_and(|config| {
+                        crate::svg::draws_asset(config, &minted_event.attributes, key)
+                    })
+            })
+            .map(|(token, _, _)| token)
+    }
+
+    /// Returns whether the image of a minted token embeds the asset with the given key,
+    /// or whether the provenance of the hidden collection commits to the asset.
+    pub fn is_asset_drawn(&self, key: &str) -> bool {
+        let is_committed = self
+            .reveal
+            .as_ref()
+            .is_some_and(|reveal| reveal.is_hidden() && reveal.provenance.is_some())
+            && self.generator_config.asset_keys().contains(key);
+        is_committed || self.tokens_drawing_asset(key).next().is_some()
+    }
+
+    /// Returns the minted and burned tokens whose mint or burn was included in a block after the given one.
+    pub fn tokens_changed_after(&self, block_number: BlockNumber) -> Vec<TokenKey> {
+        let minted = self
+            .minted_events
+            .iter()
+            .filter(|(_, _, minted_event)| minted_event.mint_event.block_number > block_number)
+            .map(|(token, _, _)| token.clone());
+        let burned = self
+            .burned_tokens
+            .iter()
+            .filter(|(_, event)| event.block_number > block_number)
+            .map(|(token, _)| token.clone());
+        minted.chain(burned).collect()
+    }
+
+    pub fn generation_status(&self, token: &TokenKey) -> GenerationStatus {
+        if self.minted_event_of(token).is_some() {
+            return GenerationStatus::Generated;
+        }
+        if self.events_to_mint.contains_alt(token) {
+            return GenerationStatus::Pending;
+        }
+        match self.invalid_token_ids.get(token) {
+            Some(source) => GenerationStatus::Invalid {
+                reason: self.invalid_events[source].clone(),
+            },
+            None => GenerationStatus::NotFound,
+        }
+    }
+
+    fn record_committed_asset(&mut self, asset: Asset) {
+        self.assets.insert(asset.key.clone(), asset);
     }
 
     pub fn next_request_id(&mut self) -> u64 {
@@ -135,19 +761,336 @@ impl State {
         current_request_id
     }
 
-    pub fn record_skipped_block(&mut self, block_number: BlockNumber) {
+    pub fn record_skipped_block(
+        &mut self,
+        collection: Option<&CollectionId>,
+        block_number: BlockNumber,
+    ) {
+        let skipped_blocks = match collection {
+            Some(id) => &mut self.collection_mut(id).skipped_blocks,
+            None => &mut self.skipped_blocks,
+        };
         assert!(
-            self.skipped_blocks.insert(block_number),
+            skipped_blocks.insert(block_number),
             "BUG: block {} was already skipped",
             block_number
         );
     }
 
-    pub const fn ethereum_network(&self) -> EthereumNetwork {
-        self.ethereum_network
+    pub fn record_scraped_skipped_block(
+        &mut self,
+        collection: Option<&CollectionId>,
+        block_number: BlockNumber,
+    ) {
+        let skipped_blocks = match collection {
+            Some(id) => &mut self.collection_mut(id).skipped_blocks,
+            None => &mut self.skipped_blocks,
+        };
+        assert!(
+            skipped_blocks.remove(&block_number),
+            "BUG: block {} was not skipped",
+            block_number
+        );
+    }
+
+    /// Returns the transfers of a skipped block, in chain order, without the ones sent by another address
+    /// than the owner of the token, so that the owners recorded since the block was skipped are not replaced
+    /// by earlier transfers. The owners are tracked across the transfers of the block,
+    /// so that a token transferred several times in the block ends up with its last recipient.
+    pub fn skipped_block_transfers(&self, transfers: Vec<TransferEvent>) -> Vec<TransferEvent> {
+        let mut owners: BTreeMap<TokenKey, Option<Address>> = BTreeMap::new();
+        transfers
+            .into_iter()
+            .filter(|transfer| {
+                let token = transfer.token_key();
+                let owner = match owners.get(&token) {
+                    Some(owner) => *owner,
+                    None => self.owner_of(&token),
+                };
+                let is_applied = transfer.from_address == Address::ZERO
+                    || transfer.is_burn()
+                    || !matches!(owner, Some(owner) if owner != transfer.from_address);
+                if is_applied {
+                    owners.insert(token, (!transfer.is_burn()).then_some(transfer.to_address));
+                }
+                is_applied
+            })
+            .collect()
+    }
+
+    /// Returns the blocks skipped for the contract of each watched collection.
+    pub fn skipped_blocks_of_contracts(&self) -> Vec<(Option<CollectionId>, Address, BlockNumber)> {
+        let init_collection = self
+            .skipped_blocks
+            .iter()
+            .map(|block_number| (None, self.ethereum_contract_address, *block_number));
+        let collections = self.collections.iter().flat_map(|(id, collection)| {
+            collection.skipped_blocks.iter().map(|block_number| {
+                (
+                    Some(id.clone()),
+                    collection.ethereum_contract_address,
+                    *block_number,
+                )
+            })
+        });
+        init_collection.chain(collections).collect()
+    }
+
+    /// Records that the logs of the contract of the given collection were scraped up to the given block.
+    pub fn set_last_scraped_block_number(
+        &mut self,
+        collection: Option<&CollectionId>,
+        block_number: BlockNumber,
+    ) {
+        match collection {
+            Some(id) => self.collection_mut(id).last_scraped_block_number = block_number,
+            None => self.last_scraped_block_number = block_number,
+        }
     }
 
-    pub const fn ethereum_block_height(&self) -> BlockTag {
+    /// Records the hash of a processed block, forgetting about the oldest hashes beyond [`MAX_BLOCK_HASHES`]
+    /// and about the changes to the tokens in blocks older than the oldest recorded hash,
+    /// which can no longer be rolled back.
+    pub fn record_block_hash(&mut self, block_number: BlockNumber, block_hash: Hash) {
+        self.block_hashes.insert(block_number, block_hash);
+        while self.block_hashes.len() > MAX_BLOCK_HASHES {
+            self.block_hashes.pop_first();
+        }
+        if let Some(oldest_block_number) = self.block_hashes.keys().next() {
+            self.token_changes
+                .retain(|(block_number, _)| block_number >= oldest_block_number);
+            self.rolled_back_seeds
+                .retain(|_, (block_number, _)| *block_number >= *oldest_block_number);
+        }
+    }
+
+    fn record_token_change(&mut self, block_number: BlockNumber, change: TokenChange) {
+        self.token_changes.push((block_number, change));
+    }
+
+    fn revert_token_change(&mut self, block_number: BlockNumber, change: TokenChange) {
+        match change {
+            // Rejected events are no longer to mint.
+            TokenChange::Accepted { source, .. } => {
+                if self.events_to_mint.remove_entry(&source).is_some() {
+                    if let Some(seed) = self.seeds.remove(&source) {
+                        self.rolled_back_seeds.insert(source, (block_number, seed));
+                    }
+                }
+            }
+            TokenChange::Minted { token, batch_seed } => {
+                let (token, source, minted_event) = self
+                    .minted_events
+                    .remove_entry(&token)
+                    .expect("BUG: rolled back token was not minted");
+                self.events_to_mint
+                    .try_insert(source, token, minted_event.mint_event)
+                    .expect("BUG: rolled back event was still to mint");
+                if let Some(seed) = batch_seed {
+                    self.seeds.insert(source, seed);
+                }
+            }
+            TokenChange::ReplacedMint {
+                token,
+                minted_event,
+            } => {
+                self.minted_events
+                    .try_insert(token, minted_event.source(), minted_event)
+                    .expect("BUG: replaced token was minted again");
+            }
+            TokenChange::AddedSupply { token, amount } => {
+                if let btree_map::Entry::Occupied(mut supply) = self.supplies.entry(token) {
+                    *supply.get_mut() = supply.get().saturating_sub(amount);
+                    if *supply.get() == u256::ZERO {
+                        supply.remove();
+                    }
+                }
+            }
+            TokenChange::SubtractedSupply { token, amount } => {
+                let supply = self.supplies.entry(token).or_default();
+                *supply = supply.saturating_add(amount);
+            }
+            TokenChange::Owner { token, previous } => match previous {
+                Some(owner) => self.record_owner(token, owner),
+                None => self.remove_owner(&token),
+            },
+            TokenChange::Burn { token, previous } => match previous {
+                Some(burn) => {
+                    self.burned_tokens.insert(token, burn);
+                }
+                None => {
+                    self.burned_tokens.remove(&token);
+                }
+            },
+            TokenChange::Invalid {
+                source,
+                token,
+                previous,
+            } => {
+                self.invalid_events.remove(&source);
+                if let Some(token) = token {
+                    match previous {
+                        Some(previous) => self.invalid_token_ids.insert(token, previous),
+                        None => self.invalid_token_ids.remove(&token),
+                    };
+                }
+            }
+        }
+    }
+
+    /// Rolls back the events included in the blocks after the given one,
+    /// which are no longer part of the canonical chain,
+    /// so that the logs of every contract are scraped again from the next block.
+    ///
+    /// The changes to the tokens made by these events are reverted in the reverse order they were applied.
+    /// The randomness received for the rolled back events is kept in case they are included again.
+    /// Invalid events recorded without their block are not rolled back.
+    fn roll_back_to(&mut self, block_number: BlockNumber) {
+        let (reverted, kept): (Vec<_>, Vec<_>) = std::mem::take(&mut self.token_changes)
+            .into_iter()
+            .partition(|(block, _)| *block > block_number);
+        self.token_changes = kept;
+        for (block, change) in reverted.into_iter().rev() {
+            self.revert_token_change(block, change);
+        }
+
+        self.block_hashes.retain(|block, _| *block <= block_number);
+        self.skipped_blocks.retain(|block| *block <= block_number);
+        self.last_scraped_block_number = self.last_scraped_block_number.min(block_number);
+        for collection in self.collections.values_mut() {
+            collection
+                .skipped_blocks
+                .retain(|block| *block <= block_number);
+            collection.last_scraped_block_number =
+                collection.last_scraped_block_number.min(block_number);
+        }
+    }
+
+    /// Returns the contract of each watched collection, with the last block scraped for it.
+    pub fn scraped_contracts(&self) -> Vec<(Option<CollectionId>, Address, BlockNumber)> {
+        std::iter::once((
+            None,
+            self.ethereum_contract_address,
+            self.last_scraped_block_number,
+        ))
+        .chain(self.collections.iter().map(|(id, collection)| {
+            (
+                Some(id.clone()),
+                collection.ethereum_contract_address,
+                collection.last_scraped_block_number,
+            )
+        }))
+        .collect()
+    }
+
+    /// Returns the generator configuration of the given collection, if it is watched.
+    pub fn generator_config_of(
+        &self,
+        collection: Option<&CollectionId>,
+    ) -> Option<&GeneratorConfig> {
+        match collection {
+            Some(id) => self
+                .collections
+                .get(id)
+                .map(|collection| &collection.generator_config),
+            None => Some(&self.generator_config),
+        }
+    }
+
+    /// Returns the generator configurations of all the watched collections.
+    pub fn generator_configs(&self) -> impl Iterator<Item = &GeneratorConfig> {
+        std::iter::once(&self.generator_config).chain(
+            self.collections
+                .values()
+                .map(|collection| &collection.generator_config),
+        )
+    }
+
+    fn collection_mut(&mut self, id: &CollectionId) -> &mut Collection {
+        self.collections
+            .get_mut(id)
+            .unwrap_or_else(|| panic!("BUG: unknown collection '{id}'"))
+    }
+
+    fn add_collection(&mut self, arg: CollectionArg) -> Result<(), InvalidStateError> {
+        let (id, collection) = <(CollectionId, Collection)>::try_from(arg)
+            .map_err(InvalidStateError::InvalidCollection)?;
+        if self.collections.contains_key(&id) {
+            return Err(InvalidStateError::InvalidCollection(format!(
+                "collection '{id}' already exists"
+            )));
+        }
+        if self
+            .scraped_contracts()
+            .iter()
+            .any(|(_, address, _)| *address == collection.ethereum_contract_address)
+        {
+            return Err(InvalidStateError::InvalidCollection(format!(
+                "contract {} of collection '{id}' is already watched",
+                collection.ethereum_contract_address
+            )));
+        }
+        self.collections.insert(id, collection);
+        Ok(())
+    }
+
+    /// Stops watching the given collection and forgets about its tokens.
+    fn remove_collection(&mut self, id: &str) -> Result<(), InvalidStateError> {
+        let id: CollectionId = id.parse().map_err(InvalidStateError::InvalidCollection)?;
+        if self.collections.remove(&id).is_none() {
+            return Err(InvalidStateError::InvalidCollection(format!(
+                "unknown collection '{id}'"
+            )));
+        }
+        let collection = Some(id);
+        let in_collection = |token: &TokenKey| token.collection == collection;
+
+        let sources: Vec<EventSource> = self
+            .events_to_mint
+            .iter()
+            .filter(|(_, token, _)| in_collection(token))
+            .map(|(source, _, _)| *source)
+            .collect();
+        for source in sources {
+            self.events_to_mint.remove_entry(&source);
+            self.seeds.remove(&source);
+        }
+        self.minted_events.drain(in_collection);
+        let invalid_events = &mut self.invalid_events;
+        self.invalid_token_ids.retain(|token, source| {
+            if in_collection(token) {
+                invalid_events.remove(source);
+                return false;
+            }
+            true
+        });
+        let owned_tokens: Vec<TokenKey> = self
+            .owners
+            .keys()
+            .filter(|token| in_collection(token))
+            .cloned()
+            .collect();
+        for token in owned_tokens {
+            self.remove_owner(&token);
+        }
+        self.burned_tokens.retain(|token, _| !in_collection(token));
+        self.supplies.retain(|token, _| !in_collection(token));
+        self.token_changes
+            .retain(|(_, change)| !change.token().is_some_and(in_collection));
+        Ok(())
+    }
+
+    pub const fn ethereum_network(&self) -> &EthereumNetwork {
+        &self.ethereum_network
+    }
+
+    /// The maximum number of blocks queried by a single `eth_getLogs` request,
+    /// which is accepted by the network and by every provider queried by the minter.
+    pub fn max_block_spread(&self) -> u64 {
+        EthRpcClient::from_state(self).max_block_spread()
+    }
+
+    pub const fn ethereum_block_height(&self) -> BlockHeight {
         self.ethereum_block_height
     }
 
@@ -157,6 +1100,16 @@ impl State {
         let UpgradeArg {
             ethereum_contract_address,
             ethereum_block_height,
+            generator_config,
+            collection_metadata,
+            reveal_config,
+            scraping_mode,
+            add_collections,
+            remove_collections,
+            rpc_providers,
+            consensus_config,
+            rpc_transport,
+            verify_receipts,
         } = upgrade_args;
         if let Some(address) = ethereum_contract_address {
             let ethereum_contract_address = Address::from_str(&address).map_err(|e| {
@@ -167,6 +1120,63 @@ impl State {
         if let Some(block_height) = ethereum_block_height {
             self.ethereum_block_height = block_height.into();
         }
+        if let Some(mode) = scraping_mode {
+            self.scraping_mode = mode;
+        }
+        if let Some(config) = generator_config {
+            let is_committed = self
+                .reveal
+                .as_ref()
+                .is_some_and(|reveal| reveal.is_hidden() && reveal.provenance.is_some());
+            if is_committed && config != self.generator_config {
+                return Err(InvalidStateError::InvalidGeneratorConfig(
+                    "cannot change the generator config of a hidden collection after committing to its provenance".to_string(),
+                ));
+            }
+            crate::svg::check_unchanged_images(
+                &self.generator_config,
+                &config,
+                self.minted_attributes(None),
+            )
+            .map_err(InvalidStateError::InvalidGeneratorConfig)?;
+            self.generator_config = config;
+        }
+        if let Some(config) = reveal_config {
+            match self.reveal.as_mut() {
+                Some(reveal) if reveal.is_hidden() => {
+                    let updated =
+                        Reveal::try_from(config).map_err(InvalidStateError::InvalidRevealConfig)?;
+                    reveal.placeholder_layer = updated.placeholder_layer;
+                    reveal.reveal_block_number = updated.reveal_block_number;
+                }
+                _ => {
+                    return Err(InvalidStateError::InvalidRevealConfig(
+                        "only hidden collections can change their reveal config".to_string(),
+                    ))
+                }
+            }
+        }
+        if let Some(metadata) = collection_metadata {
+            self.collection_metadata = metadata;
+        }
+        for id in remove_collections.unwrap_or_default() {
+            self.remove_collection(&id)?;
+        }
+        for arg in add_collections.unwrap_or_default() {
+            self.add_collection(arg)?;
+        }
+        if let Some(providers) = rpc_providers {
+            self.rpc_providers = providers;
+        }
+        if let Some(config) = consensus_config {
+            self.consensus_config.update(config);
+        }
+        if let Some(transport) = rpc_transport {
+            self.rpc_transport = transport;
+        }
+        if let Some(verify_receipts) = verify_receipts {
+            self.verify_receipts = verify_receipts;
+        }
         self.validate_config()
     }
 
@@ -182,6 +1192,10 @@ impl State {
         use ic_utils_ensure::ensure_eq;
 
         ensure_eq!(self.ethereum_network, other.ethereum_network);
+        ensure_eq!(self.rpc_providers, other.rpc_providers);
+        ensure_eq!(self.consensus_config, other.consensus_config);
+        ensure_eq!(self.rpc_transport, other.rpc_transport);
+        ensure_eq!(self.verify_receipts, other.verify_receipts);
         ensure_eq!(
             self.ethereum_contract_address,
             other.ethereum_contract_address
@@ -196,8 +1210,23 @@ impl State {
         );
         ensure_eq!(self.ethereum_block_height, other.ethereum_block_height);
         ensure_eq!(self.events_to_mint, other.events_to_mint);
+        ensure_eq!(self.seeds, other.seeds);
+        ensure_eq!(self.rolled_back_seeds, other.rolled_back_seeds);
         ensure_eq!(self.minted_events, other.minted_events);
         ensure_eq!(self.invalid_events, other.invalid_events);
+        ensure_eq!(self.invalid_token_ids, other.invalid_token_ids);
+        ensure_eq!(self.scraping_mode, other.scraping_mode);
+        ensure_eq!(self.owners, other.owners);
+        ensure_eq!(self.tokens_by_owner, other.tokens_by_owner);
+        ensure_eq!(self.burned_tokens, other.burned_tokens);
+        ensure_eq!(self.token_standard, other.token_standard);
+        ensure_eq!(self.supplies, other.supplies);
+        ensure_eq!(self.token_changes, other.token_changes);
+        ensure_eq!(self.generator_config, other.generator_config);
+        ensure_eq!(self.collections, other.collections);
+        ensure_eq!(self.collection_metadata, other.collection_metadata);
+        ensure_eq!(self.assets, other.assets);
+        ensure_eq!(self.reveal, other.reveal);
         Ok(())
     }
 }
@@ -226,4 +1255,6 @@ pub enum TaskType {
     RetrieveEth,
     ScrapEthLogs,
     Reimbursement,
+    CommitProvenance,
+    ConsistencyAudit,
 }
@@ -1,5 +1,7 @@
 pub use super::event::{Event, EventType};
 use super::State;
+use crate::certification::{certify_token, certify_tokens, start_rebuild};
+use crate::collections::TokenKey;
 use crate::storage::{record_event, with_event_iter};
 
 /// Updates the state to reflect the given state transition.
@@ -17,28 +19,129 @@ pub fn apply_state_transition(state: &mut State, payload: &EventType) {
         }
         EventType::AcceptedMint(eth_event) => {
             state.record_event_to_mint(eth_event);
+            if let Some(block_hash) = eth_event.block_hash {
+                state.record_block_hash(eth_event.block_number, block_hash);
+            }
         }
         EventType::InvalidTransfer {
             event_source,
             reason,
+            token_id,
+            collection,
+            block_number,
         } => {
-            let _ = state.record_invalid_deposit(*event_source, reason.clone());
+            let token = token_id.map(|token_id| TokenKey {
+                collection: collection.clone(),
+                token_id,
+            });
+            let _ =
+                state.record_invalid_deposit(*event_source, token, reason.clone(), *block_number);
         }
         EventType::MintedNft { event_source } => {
             state.record_successful_mint(*event_source);
         }
-        EventType::SyncedToBlock { block_number } => {
-            state.last_scraped_block_number = *block_number;
+        EventType::SyncedToBlock {
+            block_number,
+            collection,
+            block_hash,
+        } => {
+            state.set_last_scraped_block_number(collection.as_ref(), *block_number);
+            if let Some(block_hash) = block_hash {
+                state.record_block_hash(*block_number, *block_hash);
+            }
+        }
+        EventType::SkippedBlock {
+            block_number,
+            collection,
+        } => {
+            state.record_skipped_block(collection.as_ref(), *block_number);
+        }
+        EventType::CommittedAsset(asset) => {
+            state.record_committed_asset(asset.clone());
         }
-        EventType::SkippedBlock(block_number) => {
-            state.record_skipped_block(*block_number);
+        EventType::ReceivedRandomness {
+            seed,
+            event_sources,
+        } => {
+            state.record_randomness(*seed, event_sources);
+        }
+        EventType::CommittedProvenance {
+            seed,
+            provenance_hash,
+        } => {
+            state.record_provenance(*seed, *provenance_hash);
+        }
+        EventType::Revealed { seed } => {
+            state.record_reveal(*seed);
+        }
+        EventType::TransferredNft(transfer_event) => {
+            state.record_transfer(transfer_event);
+            if let Some(block_hash) = transfer_event.block_hash {
+                state.record_block_hash(transfer_event.block_number, block_hash);
+            }
+        }
+        EventType::BurnedNft(transfer_event) => {
+            state.record_burn(transfer_event);
+            if let Some(block_hash) = transfer_event.block_hash {
+                state.record_block_hash(transfer_event.block_number, block_hash);
+            }
+        }
+        EventType::AcceptedEdition(mint_event) => {
+            state.record_edition(mint_event);
+            if let Some(block_hash) = mint_event.block_hash {
+                state.record_block_hash(mint_event.block_number, block_hash);
+            }
+        }
+        EventType::ChainReorganized { block_number } => {
+            state.roll_back_to(*block_number);
+        }
+        EventType::ScrapedSkippedBlock {
+            block_number,
+            collection,
+        } => {
+            state.record_scraped_skipped_block(collection.as_ref(), *block_number);
+        }
+        EventType::RejectedMint {
+            event_source,
+            reason,
+        } => {
+            state.record_rejected_mint(*event_source, reason.clone());
         }
     }
 }
 
 /// Records the given event payload in the event log and updates the state to reflect the change.
 pub fn process_event(state: &mut State, payload: EventType) {
+    // Rolled back and burned tokens minted again are no longer served once the event is applied.
+    let unserved_tokens = match &payload {
+        EventType::ChainReorganized { block_number } => state.tokens_changed_after(*block_number),
+        EventType::AcceptedMint(event) if state.is_burned(&event.token_key()) => {
+            vec![event.token_key()]
+        }
+        _ => vec![],
+    };
     apply_state_transition(state, &payload);
+    // Only the responses of the tokens affected by the event are certified again.
+    // Upgrades are certified once the upgrade completes, see `lifecycle::post_upgrade`.
+    match &payload {
+        EventType::MintedNft { event_source } => {
+            if let Some(minted_event) = state.minted_events.get_alt(event_source) {
+                certify_token(state, &minted_event.mint_event.token_key());
+            }
+        }
+        EventType::BurnedNft(transfer_event) => certify_token(state, &transfer_event.token_key()),
+        EventType::AcceptedEdition(mint_event) => certify_token(state, &mint_event.token_key()),
+        EventType::CommittedAsset(asset) => {
+            let tokens: Vec<TokenKey> = state.tokens_drawing_asset(&asset.key).cloned().collect();
+            certify_tokens(state, &tokens);
+        }
+        EventType::ChainReorganized { .. } | EventType::AcceptedMint(_) => {
+            certify_tokens(state, &unserved_tokens)
+        }
+        // The reveal changes the image of every minted token of the collection.
+        EventType::Revealed { .. } => start_rebuild(state),
+        _ => {}
+    }
     record_event(payload);
 }
 
@@ -1,8 +1,12 @@
-use crate::eth_logs::{EventSource, MintEvent};
+use crate::assets::Asset;
+use crate::collections::CollectionId;
+use crate::eth_logs::{EventSource, MintEvent, TransferEvent};
+use crate::eth_rpc::Hash;
 
 use crate::lifecycle::{init::InitArg, upgrade::UpgradeArg};
 use crate::numeric::BlockNumber;
 
+use ethnum::u256;
 use minicbor::{Decode, Encode};
 
 /// The event describing the ckETH minter state transition.
@@ -27,6 +31,16 @@ pub enum EventType {
         /// The reason why minter considers the deposit invalid.
         #[n(1)]
         reason: String,
+        /// The transferred token, if it could be parsed from the log entry.
+        #[cbor(n(2), with = "crate::cbor::u256::option")]
+        token_id: Option<u256>,
+        /// The additional collection of the contract that emitted the event, if any.
+        #[n(3)]
+        collection: Option<CollectionId>,
+        /// The block including the event, if known.
+        /// Invalid events recorded without their block are not rolled back on chain reorganizations.
+        #[n(4)]
+        block_number: Option<BlockNumber>,
     },
     /// The minter minted ckETH in response to a deposit.
     #[n(5)]
@@ -41,10 +55,88 @@ pub enum EventType {
         /// The last processed block number (inclusive).
         #[n(0)]
         block_number: BlockNumber,
+        /// The additional collection whose contract was processed, if any.
+        #[n(1)]
+        collection: Option<CollectionId>,
+        /// The hash of the last processed block, if known.
+        #[n(2)]
+        block_hash: Option<Hash>,
     },
     /// The minter could not scrap the logs for that block.
     #[n(13)]
-    SkippedBlock(#[n(0)] BlockNumber),
+    SkippedBlock {
+        #[n(0)]
+        block_number: BlockNumber,
+        /// The additional collection whose contract logs were skipped, if any.
+        #[n(1)]
+        collection: Option<CollectionId>,
+    },
+    /// A controller committed an asset.
+    #[n(14)]
+    CommittedAsset(#[n(0)] Asset),
+    /// The minter received randomness from the management canister
+    /// to generate the traits of the specified events.
+    #[n(15)]
+    ReceivedRandomness {
+        /// The 32 bytes returned by `raw_rand`.
+        #[cbor(n(0), with = "minicbor::bytes")]
+        seed: [u8; 32],
+        /// The events to mint that use this seed.
+        #[n(1)]
+        event_sources: Vec<EventSource>,
+    },
+    /// The minter committed to the seed of a hidden collection.
+    #[n(16)]
+    CommittedProvenance {
+        /// The secret seed returned by `raw_rand`.
+        #[cbor(n(0), with = "minicbor::bytes")]
+        seed: [u8; 32],
+        /// The commitment to the generator configuration and to the seed.
+        #[cbor(n(1), with = "minicbor::bytes")]
+        provenance_hash: [u8; 32],
+    },
+    /// The hidden collection was revealed with the committed seed.
+    #[n(17)]
+    Revealed {
+        #[cbor(n(0), with = "minicbor::bytes")]
+        seed: [u8; 32],
+    },
+    /// The minter discovered a transfer of a token between two owners.
+    #[n(18)]
+    TransferredNft(#[n(0)] TransferEvent),
+    /// The minter discovered a transfer of a token to the zero address, which destroys it.
+    #[n(19)]
+    BurnedNft(#[n(0)] TransferEvent),
+    /// The minter discovered a mint of more editions of an ERC-1155 token it already accepted.
+    #[n(20)]
+    AcceptedEdition(#[n(0)] MintEvent),
+    /// The canonical chain no longer includes the blocks processed by the minter after the specified height.
+    /// The events of these blocks are rolled back, and the logs are processed again from the next block.
+    #[n(21)]
+    ChainReorganized {
+        /// The last processed block that is still in the canonical chain.
+        #[n(0)]
+        block_number: BlockNumber,
+    },
+    /// The minter scraped the logs of a block that it previously skipped.
+    #[n(22)]
+    ScrapedSkippedBlock {
+        #[n(0)]
+        block_number: BlockNumber,
+        /// The additional collection whose contract logs were scraped, if any.
+        #[n(1)]
+        collection: Option<CollectionId>,
+    },
+    /// The receipt of the transaction of an accepted event contradicts the event,
+    /// which is no longer minted.
+    #[n(23)]
+    RejectedMint {
+        #[n(0)]
+        event_source: EventSource,
+        /// The reason why the receipt contradicts the event.
+        #[n(1)]
+        reason: String,
+    },
 }
 
 #[derive(Encode, Decode, Debug, PartialEq, Eq)]
@@ -1,34 +1,27 @@
 use crate::address::Address;
 use crate::checked_amount::CheckedAmountOf;
+use crate::collections::CollectionId;
 use crate::endpoints::CandidBlockTag;
-use crate::eth_logs::{EventSource, MintEvent};
-use crate::eth_rpc::{BlockTag, Hash};
-use crate::eth_rpc_client::responses::{TransactionReceipt, TransactionStatus};
+use crate::eth_logs::{EventSource, MintEvent, ScrapingMode, TokenStandard, TransferEvent};
+use crate::eth_rpc::{BlockHeight, BlockTag, Hash};
 use crate::lifecycle::init::InitArg;
 use crate::lifecycle::upgrade::UpgradeArg;
 use crate::lifecycle::EthereumNetwork;
-use crate::numeric::{
-    wei_from_milli_ether, BlockNumber, GasAmount, LedgerBurnIndex, LedgerMintIndex, LogIndex,
-    TokenId, TransactionNonce, Wei, WeiPerGas,
-};
+use crate::numeric::{BlockNumber, LogIndex};
 use crate::state::event::{Event, EventType};
 use crate::state::State;
-use crate::tx::{
-    AccessList, AccessListItem, Eip1559Signature, Eip1559TransactionRequest,
-    SignedEip1559TransactionRequest, StorageKey,
-};
-use candid::{Nat, Principal};
+use candid::Nat;
 use ethnum::u256;
 use proptest::array::{uniform20, uniform32};
 use proptest::collection::vec as pvec;
 use proptest::prelude::*;
 
 mod next_request_id {
-    use crate::state::tests::a_state;
+    use crate::test_fixtures::state;
 
     #[test]
     fn should_retrieve_and_increment_counter() {
-        let mut state = a_state();
+        let mut state = state();
 
         assert_eq!(state.next_request_id(), 0);
         assert_eq!(state.next_request_id(), 1);
@@ -38,7 +31,7 @@ mod next_request_id {
 
     #[test]
     fn should_wrap_to_0_when_overflow() {
-        let mut state = a_state();
+        let mut state = state();
         state.http_request_counter = u64::MAX;
 
         assert_eq!(state.next_request_id(), u64::MAX);
@@ -46,59 +39,1223 @@ mod next_request_id {
     }
 }
 
-fn a_state() -> State {
-    State::try_from(InitArg {
-        ethereum_network: Default::default(),
-        ecdsa_key_name: "test_key_1".to_string(),
-        ethereum_contract_address: None,
-        ledger_id: Principal::from_text("apia6-jaaaa-aaaar-qabma-cai")
-            .expect("BUG: invalid principal"),
-        ethereum_block_height: Default::default(),
-        minimum_withdrawal_amount: wei_from_milli_ether(10).into(),
-        next_transaction_nonce: Default::default(),
-        last_scraped_block_number: Default::default(),
-    })
-    .expect("init args should be valid")
+mod token_index {
+    use crate::address::Address;
+    use crate::endpoints::GenerationStatus;
+    use crate::eth_logs::{MintEvent, MintEventError, ScrapingMode, TokenStandard, TransferEvent};
+    use crate::numeric::LogIndex;
+    use crate::state::audit::{apply_state_transition, EventType};
+    use crate::state::{InvalidStateError, State};
+    use crate::test_fixtures::{mint_event, state, transfer_event};
+    use assert_matches::assert_matches;
+    use ethnum::u256;
+
+    #[test]
+    fn should_index_events_by_token_id() {
+        let mut state = state();
+        let first = mint_event(1, 7);
+        let second = mint_event(2, 3);
+        apply_state_transition(&mut state, &EventType::AcceptedMint(first.clone()));
+        apply_state_transition(&mut state, &EventType::AcceptedMint(second.clone()));
+        apply_state_transition(
+            &mut state,
+            &EventType::MintedNft {
+                event_source: first.source(),
+            },
+        );
+
+        assert_eq!(
+            state
+                .minted_event_of(&u256::from(7_u8).into())
+                .map(|e| e.source()),
+            Some(first.source())
+        );
+        assert_eq!(state.minted_event_of(&u256::from(3_u8).into()), None);
+        assert_eq!(
+            state.generation_status(&u256::from(3_u8).into()),
+            GenerationStatus::Pending
+        );
+
+        apply_state_transition(
+            &mut state,
+            &EventType::MintedNft {
+                event_source: second.source(),
+            },
+        );
+        let token_ids: Vec<_> = state
+            .minted_events
+            .values()
+            .map(|event| event.mint_event.token_id)
+            .collect();
+        assert_eq!(token_ids, vec![u256::from(3_u8), u256::from(7_u8)]);
+    }
+
+    #[test]
+    fn should_reject_second_mint_of_same_token() {
+        let mut state = state();
+        let first = mint_event(1, 42);
+        apply_state_transition(&mut state, &EventType::AcceptedMint(first.clone()));
+
+        let duplicate = mint_event(2, 42);
+        assert!(state
+            .validate_mint(&duplicate)
+            .unwrap_err()
+            .contains("already being minted"));
+
+        apply_state_transition(
+            &mut state,
+            &EventType::MintedNft {
+                event_source: first.source(),
+            },
+        );
+        let reason = state.validate_mint(&duplicate).unwrap_err();
+        assert!(reason.contains("already minted"));
+
+        apply_state_transition(
+            &mut state,
+            &EventType::InvalidTransfer {
+                event_source: duplicate.source(),
+                reason,
+                token_id: Some(duplicate.token_id),
+                collection: None,
+                block_number: Some(duplicate.block_number),
+            },
+        );
+        assert_eq!(
+            state.generation_status(&u256::from(42_u8).into()),
+            GenerationStatus::Generated
+        );
+    }
+
+    #[test]
+    fn should_record_duplicate_mint_of_legacy_event_log_as_invalid() {
+        let mut state = state();
+        let first = mint_event(1, 42);
+        let duplicate = mint_event(2, 42);
+        apply_state_transition(&mut state, &EventType::AcceptedMint(first.clone()));
+        apply_state_transition(&mut state, &EventType::AcceptedMint(duplicate.clone()));
+        apply_state_transition(
+            &mut state,
+            &EventType::ReceivedRandomness {
+                seed: [1; 32],
+                event_sources: vec![first.source(), duplicate.source()],
+            },
+        );
+        for event in [&first, &duplicate] {
+            apply_state_transition(
+                &mut state,
+                &EventType::MintedNft {
+                    event_source: event.source(),
+                },
+            );
+        }
+
+        assert!(state.invalid_events[&duplicate.source()].contains("already being minted"));
+        assert!(state.events_to_mint.is_empty());
+        assert_eq!(
+            state
+                .minted_event_of(&u256::from(42_u8).into())
+                .map(|e| e.source()),
+            Some(first.source())
+        );
+        assert_eq!(state.supply_of(&u256::from(42_u8).into()), u256::ONE);
+    }
+
+    #[test]
+    fn should_track_owners_on_mint_and_transfer() {
+        let mut state = state();
+        let minter = mint_event(1, 7).to_address;
+        let buyer: Address = "0xb44B5e756A894775FC32EDdf3314Bb1B1944dC34"
+            .parse()
+            .unwrap();
+        apply_state_transition(&mut state, &EventType::AcceptedMint(mint_event(1, 7)));
+        apply_state_transition(&mut state, &EventType::AcceptedMint(mint_event(2, 3)));
+
+        assert_eq!(state.owner_of(&u256::from(7_u8).into()), Some(minter));
+        assert_eq!(
+            state.tokens_of(&minter, None).collect::<Vec<_>>(),
+            vec![u256::from(3_u8), u256::from(7_u8)]
+        );
+
+        apply_state_transition(
+            &mut state,
+            &EventType::TransferredNft(transfer_event(3, minter, buyer, 7)),
+        );
+        assert_eq!(state.owner_of(&u256::from(7_u8).into()), Some(buyer));
+        assert_eq!(
+            state.tokens_of(&minter, None).collect::<Vec<_>>(),
+            vec![u256::from(3_u8)]
+        );
+        assert_eq!(
+            state.tokens_of(&buyer, None).collect::<Vec<_>>(),
+            vec![u256::from(7_u8)]
+        );
+
+        apply_state_transition(
+            &mut state,
+            &EventType::TransferredNft(transfer_event(4, minter, buyer, 3)),
+        );
+        assert_eq!(state.tokens_of(&minter, None).count(), 0);
+        assert!(!state.tokens_by_owner.contains_key(&minter));
+        assert_eq!(state.owner_of(&u256::from(42_u8).into()), None);
+    }
+
+    #[test]
+    fn should_remove_owner_of_burned_token() {
+        let mut state = state();
+        let holder = mint_event(1, 7).to_address;
+        apply_state_transition(&mut state, &EventType::AcceptedMint(mint_event(1, 7)));
+        let burn = transfer_event(2, holder, Address::ZERO, 7);
+        assert!(burn.is_burn());
+
+        apply_state_transition(&mut state, &EventType::BurnedNft(burn.clone()));
+
+        assert!(state.is_burned(&u256::from(7_u8).into()));
+        assert_eq!(
+            state.burned_tokens.get(&u256::from(7_u8).into()),
+            Some(&burn)
+        );
+        assert_eq!(state.owner_of(&u256::from(7_u8).into()), None);
+        assert_eq!(state.tokens_of(&holder, None).count(), 0);
+        assert!(!state.tokens_by_owner.contains_key(&Address::ZERO));
+    }
+
+    #[test]
+    fn should_mint_burned_token_again() {
+        let mut state = state();
+        let first = mint_event(1, 7);
+        apply_state_transition(&mut state, &EventType::AcceptedMint(first.clone()));
+        apply_state_transition(
+            &mut state,
+            &EventType::MintedNft {
+                event_source: first.source(),
+            },
+        );
+        apply_state_transition(
+            &mut state,
+            &EventType::BurnedNft(transfer_event(2, first.to_address, Address::ZERO, 7)),
+        );
+
+        let second = mint_event(3, 7);
+        assert_eq!(state.validate_mint(&second), Ok(()));
+        apply_state_transition(&mut state, &EventType::AcceptedMint(second.clone()));
+
+        let token = u256::from(7_u8).into();
+        assert!(!state.is_burned(&token));
+        assert_eq!(state.minted_event_of(&token), None);
+        assert_eq!(state.generation_status(&token), GenerationStatus::Pending);
+        assert_eq!(state.owner_of(&token), Some(second.to_address));
+
+        apply_state_transition(
+            &mut state,
+            &EventType::MintedNft {
+                event_source: second.source(),
+            },
+        );
+        assert_eq!(
+            state.minted_event_of(&token).map(|e| e.source()),
+            Some(second.source())
+        );
+        assert_eq!(state.supply_of(&token), u256::ONE);
+    }
+
+    #[test]
+    fn should_count_editions_of_erc1155_tokens() {
+        let mut state = erc1155_state();
+        let first = MintEvent {
+            value: Some(u256::from(10_u8)),
+            ..mint_event(1, 7)
+        };
+        let second = MintEvent {
+            value: Some(u256::from(5_u8)),
+            ..mint_event(2, 7)
+        };
+        assert!(!state.is_new_edition(&first));
+        apply_state_transition(&mut state, &EventType::AcceptedMint(first.clone()));
+
+        assert!(state.is_new_edition(&second));
+        apply_state_transition(&mut state, &EventType::AcceptedEdition(second));
+        assert_eq!(state.supply_of(&u256::from(7_u8).into()), u256::from(15_u8));
+        assert_eq!(state.events_to_mint.len(), 1);
+        assert_eq!(state.owner_of(&u256::from(7_u8).into()), None);
+
+        let burn = TransferEvent {
+            value: Some(u256::from(14_u8)),
+            ..transfer_event(3, first.to_address, Address::ZERO, 7)
+        };
+        apply_state_transition(&mut state, &EventType::BurnedNft(burn.clone()));
+        assert_eq!(state.supply_of(&u256::from(7_u8).into()), u256::ONE);
+        assert!(!state.is_burned(&u256::from(7_u8).into()));
+
+        let burn = TransferEvent {
+            value: Some(u256::ONE),
+            log_index: LogIndex::from(4_u8),
+            ..burn
+        };
+        apply_state_transition(&mut state, &EventType::BurnedNft(burn));
+        assert_eq!(state.supply_of(&u256::from(7_u8).into()), u256::ZERO);
+        assert!(state.is_burned(&u256::from(7_u8).into()));
+    }
+
+    #[test]
+    #[should_panic(expected = "only editions of accepted ERC-1155 tokens")]
+    fn should_not_accept_editions_of_erc721_tokens() {
+        let mut state = state();
+        apply_state_transition(&mut state, &EventType::AcceptedMint(mint_event(1, 7)));
+        apply_state_transition(&mut state, &EventType::AcceptedEdition(mint_event(2, 7)));
+    }
+
+    #[test]
+    fn should_not_track_owners_of_erc1155_tokens() {
+        let mut state = erc1155_state();
+        state.scraping_mode = ScrapingMode::AllTransfers;
+
+        assert_matches!(
+            state.validate_config(),
+            Err(InvalidStateError::InvalidScrapingMode(_))
+        );
+    }
+
+    #[test]
+    fn should_only_convert_transfers_from_zero_address_to_mints() {
+        let holder = mint_event(1, 7).to_address;
+        let mint = transfer_event(1, Address::ZERO, holder, 7);
+        assert_eq!(MintEvent::try_from(mint), Ok(mint_event(1, 7)));
+
+        let transfer = transfer_event(2, holder, Address::ZERO, 7);
+        assert_eq!(
+            MintEvent::try_from(transfer),
+            Err(MintEventError::NoMintEvent)
+        );
+    }
+
+    #[test]
+    fn should_reject_mint_contradicted_by_receipt() {
+        let mut state = state();
+        let event = mint_event(1, 42);
+        let token = event.token_key();
+        apply_state_transition(&mut state, &EventType::AcceptedMint(event.clone()));
+
+        apply_state_transition(
+            &mut state,
+            &EventType::RejectedMint {
+                event_source: event.source(),
+                reason: "transaction failed".to_string(),
+            },
+        );
+
+        assert!(!state.has_events_to_mint());
+        assert_eq!(state.owner_of(&token), None);
+        assert_eq!(state.supply_of(&token), u256::ZERO);
+        assert_eq!(
+            state.generation_status(&token),
+            GenerationStatus::Invalid {
+                reason: "transaction failed".to_string()
+            }
+        );
+        assert!(state.validate_mint(&event).is_err());
+    }
+
+    #[test]
+    fn should_keep_other_editions_of_rejected_mint() {
+        let mut state = erc1155_state();
+        let first = MintEvent {
+            value: Some(u256::from(3_u8)),
+            ..mint_event(1, 42)
+        };
+        let edition = MintEvent {
+            value: Some(u256::from(2_u8)),
+            ..mint_event(2, 42)
+        };
+        apply_state_transition(&mut state, &EventType::AcceptedMint(first.clone()));
+        apply_state_transition(&mut state, &EventType::AcceptedEdition(edition.clone()));
+
+        apply_state_transition(
+            &mut state,
+            &EventType::RejectedMint {
+                event_source: first.source(),
+                reason: "no log at index 1".to_string(),
+            },
+        );
+
+        assert_eq!(state.supply_of(&first.token_key()), u256::from(2_u8));
+        assert!(state.invalid_events.contains_key(&first.source()));
+    }
+
+    fn erc1155_state() -> State {
+        State {
+            token_standard: TokenStandard::Erc1155,
+            ..state()
+        }
+    }
+}
+
+mod collections {
+    use crate::collections::{CollectionArg, CollectionId, TokenKey};
+    use crate::eth_logs::MintEvent;
+    use crate::lifecycle::upgrade::UpgradeArg;
+    use crate::numeric::BlockNumber;
+    use crate::state::audit::{apply_state_transition, EventType};
+    use crate::state::{InvalidStateError, State};
+    use crate::test_fixtures::{self, state};
+    use assert_matches::assert_matches;
+    use candid::Nat;
+    use ethnum::u256;
+
+    const OTHER_CONTRACT: &str = "0x7a250d5630B4cF539739dF2C5dAcb4c659F2488D";
+
+    #[test]
+    fn should_key_tokens_by_collection() {
+        let mut state = state_with_collection();
+        let other = collection_id();
+        apply_state_transition(&mut state, &EventType::AcceptedMint(mint_event(1, 7, None)));
+        apply_state_transition(
+            &mut state,
+            &EventType::AcceptedMint(mint_event(2, 7, Some(other.clone()))),
+        );
+
+        let holder = mint_event(1, 7, None).to_address;
+        let other_token = TokenKey {
+            collection: Some(other.clone()),
+            token_id: u256::from(7_u8),
+        };
+        assert_eq!(state.events_to_mint.len(), 2);
+        assert_eq!(state.owner_of(&u256::from(7_u8).into()), Some(holder));
+        assert_eq!(state.owner_of(&other_token), Some(holder));
+        assert_eq!(
+            state.tokens_of(&holder, Some(&other)).collect::<Vec<_>>(),
+            vec![u256::from(7_u8)]
+        );
+        assert_eq!(other_token.path(), "/other/token/7");
+    }
+
+    #[test]
+    fn should_scrape_contract_of_each_collection() {
+        let state = state_with_collection();
+
+        assert_eq!(
+            state.scraped_contracts(),
+            vec![
+                (None, state.ethereum_contract_address, BlockNumber::ZERO),
+                (
+                    Some(collection_id()),
+                    OTHER_CONTRACT.parse().unwrap(),
+                    BlockNumber::from(100_u8)
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_reject_duplicate_collections() {
+        let mut state = state_with_collection();
+
+        assert_matches!(
+            state.upgrade(UpgradeArg {
+                add_collections: Some(vec![collection_arg("other", OTHER_CONTRACT)]),
+                ..Default::default()
+            }),
+            Err(InvalidStateError::InvalidCollection(reason)) if reason.contains("already exists")
+        );
+        assert_matches!(
+            state.upgrade(UpgradeArg {
+                add_collections: Some(vec![collection_arg(
+                    "copy",
+                    "0xb44B5e756A894775FC32EDdf3314Bb1B1944dC34"
+                )]),
+                ..Default::default()
+            }),
+            Err(InvalidStateError::InvalidCollection(reason)) if reason.contains("already watched")
+        );
+    }
+
+    #[test]
+    fn should_forget_tokens_of_removed_collection() {
+        let mut state = state_with_collection();
+        let event = mint_event(2, 7, Some(collection_id()));
+        apply_state_transition(&mut state, &EventType::AcceptedMint(mint_event(1, 7, None)));
+        apply_state_transition(&mut state, &EventType::AcceptedMint(event.clone()));
+        apply_state_transition(
+            &mut state,
+            &EventType::MintedNft {
+                event_source: event.source(),
+            },
+        );
+
+        apply_state_transition(
+            &mut state,
+            &EventType::Upgrade(UpgradeArg {
+                remove_collections: Some(vec!["other".to_string()]),
+                ..Default::default()
+            }),
+        );
+
+        assert!(state.collections.is_empty());
+        assert!(state.minted_events.is_empty());
+        assert_eq!(state.events_to_mint.len(), 1);
+        assert_eq!(state.owner_of(&event.token_key()), None);
+        assert_eq!(
+            state.tokens_of(&event.to_address, None).collect::<Vec<_>>(),
+            vec![u256::from(7_u8)]
+        );
+        assert_matches!(
+            state.upgrade(UpgradeArg {
+                remove_collections: Some(vec!["other".to_string()]),
+                ..Default::default()
+            }),
+            Err(InvalidStateError::InvalidCollection(reason)) if reason.contains("unknown")
+        );
+    }
+
+    fn state_with_collection() -> State {
+        let mut state = state();
+        apply_state_transition(
+            &mut state,
+            &EventType::Upgrade(UpgradeArg {
+                add_collections: Some(vec![collection_arg("other", OTHER_CONTRACT)]),
+                ..Default::default()
+            }),
+        );
+        state
+    }
+
+    fn collection_id() -> CollectionId {
+        "other".parse().unwrap()
+    }
+
+    fn collection_arg(id: &str, contract: &str) -> CollectionArg {
+        CollectionArg {
+            id: id.to_string(),
+            ethereum_contract_address: contract.to_string(),
+            last_scraped_block_number: Nat::from(100_u8),
+            generator_config: None,
+            collection_metadata: None,
+        }
+    }
+
+    fn mint_event(log_index: u64, token_id: u64, collection: Option<CollectionId>) -> MintEvent {
+        MintEvent {
+            collection,
+            ..test_fixtures::mint_event(log_index, token_id)
+        }
+    }
+}
+
+mod chain_reorganization {
+    use crate::address::Address;
+    use crate::collections::TokenKey;
+    use crate::eth_logs::TokenStandard;
+    use crate::eth_logs::{MintEvent, TransferEvent};
+    use crate::eth_rpc::Hash;
+    use crate::numeric::{BlockNumber, LogIndex};
+    use crate::state::audit::{apply_state_transition, EventType};
+    use crate::state::{State, MAX_BLOCK_HASHES};
+    use crate::test_fixtures::{self, holder, state};
+    use ethnum::u256;
+
+    #[test]
+    fn should_record_block_hashes_of_accepted_events() {
+        let mut state = state();
+        let event = mint_event(10, 1);
+        apply_state_transition(&mut state, &EventType::AcceptedMint(event.clone()));
+        apply_state_transition(
+            &mut state,
+            &EventType::SyncedToBlock {
+                block_number: BlockNumber::from(12_u8),
+                collection: None,
+                block_hash: Some(Hash([12; 32])),
+            },
+        );
+
+        assert_eq!(
+            state.block_hashes.into_iter().collect::<Vec<_>>(),
+            vec![
+                (BlockNumber::from(10_u8), Hash([10; 32])),
+                (BlockNumber::from(12_u8), Hash([12; 32]))
+            ]
+        );
+    }
+
+    #[test]
+    fn should_keep_latest_block_hashes() {
+        let mut state = state();
+        for block_number in 0..=MAX_BLOCK_HASHES as u64 {
+            state.record_block_hash(BlockNumber::from(block_number), Hash([0; 32]));
+        }
+
+        assert_eq!(state.block_hashes.len(), MAX_BLOCK_HASHES);
+        assert_eq!(
+            state.block_hashes.keys().next(),
+            Some(&BlockNumber::from(1_u8))
+        );
+    }
+
+    #[test]
+    fn should_roll_back_events_of_reorganized_blocks() {
+        let mut state = state();
+        let kept = mint_event(10, 1);
+        let minted = mint_event(20, 2);
+        let accepted = mint_event(30, 3);
+        for event in [&kept, &minted, &accepted] {
+            apply_state_transition(&mut state, &EventType::AcceptedMint(event.clone()));
+        }
+        apply_state_transition(
+            &mut state,
+            &EventType::ReceivedRandomness {
+                seed: [1; 32],
+                event_sources: vec![minted.source(), accepted.source()],
+            },
+        );
+        apply_state_transition(
+            &mut state,
+            &EventType::MintedNft {
+                event_source: minted.source(),
+            },
+        );
+        state.last_scraped_block_number = BlockNumber::from(40_u8);
+        state.skipped_blocks.insert(BlockNumber::from(35_u8));
+
+        apply_state_transition(
+            &mut state,
+            &EventType::ChainReorganized {
+                block_number: BlockNumber::from(15_u8),
+            },
+        );
+
+        assert_eq!(state.last_scraped_block_number, BlockNumber::from(15_u8));
+        assert!(state.skipped_blocks.is_empty());
+        assert!(state.minted_events.is_empty());
+        assert!(state.seeds.is_empty());
+        assert_eq!(
+            state.events_to_mint.keys().collect::<Vec<_>>(),
+            vec![&kept.source()]
+        );
+        assert_eq!(
+            state.tokens_of(&kept.to_address, None).collect::<Vec<_>>(),
+            vec![u256::from(1_u8)]
+        );
+        assert_eq!(
+            state.block_hashes.keys().collect::<Vec<_>>(),
+            vec![&BlockNumber::from(10_u8)]
+        );
+
+        // The events of the canonical chain are scraped again, with the randomness they received.
+        apply_state_transition(&mut state, &EventType::AcceptedMint(minted.clone()));
+        assert_eq!(state.seeds.get(&minted.source()), Some(&[1; 32]));
+        assert!(state.is_ready_to_mint(&minted.source()));
+        assert_eq!(
+            state.rolled_back_seeds.keys().collect::<Vec<_>>(),
+            vec![&accepted.source()]
+        );
+    }
+
+    #[test]
+    fn should_list_tokens_changed_in_reorganized_blocks() {
+        let mut state = state();
+        for event in [mint_event(10, 1), mint_event(20, 2)] {
+            apply_state_transition(&mut state, &EventType::AcceptedMint(event.clone()));
+            apply_state_transition(
+                &mut state,
+                &EventType::ReceivedRandomness {
+                    seed: [1; 32],
+                    event_sources: vec![event.source()],
+                },
+            );
+            apply_state_transition(
+                &mut state,
+                &EventType::MintedNft {
+                    event_source: event.source(),
+                },
+            );
+        }
+        apply_state_transition(&mut state, &EventType::AcceptedMint(mint_event(30, 3)));
+        let burned = mint_event(10, 1);
+        apply_state_transition(
+            &mut state,
+            &EventType::BurnedNft(TransferEvent {
+                transaction_hash: Hash([4; 32]),
+                block_number: BlockNumber::from(25_u8),
+                log_index: LogIndex::from(0_u8),
+                from_address: burned.to_address,
+                to_address: Address::ZERO,
+                token_id: burned.token_id,
+                batch_index: None,
+                value: None,
+                collection: None,
+                block_hash: None,
+            }),
+        );
+
+        assert_eq!(
+            state.tokens_changed_after(BlockNumber::from(15_u8)),
+            vec![
+                TokenKey::from(u256::from(2_u8)),
+                TokenKey::from(u256::from(1_u8))
+            ]
+        );
+        assert_eq!(state.tokens_changed_after(BlockNumber::from(25_u8)), vec![]);
+    }
+
+    #[test]
+    fn should_reject_events_rejected_before_reorganization() {
+        let mut state = state();
+        let event = mint_event(20, 2);
+        apply_state_transition(
+            &mut state,
+            &EventType::InvalidTransfer {
+                event_source: event.source(),
+                reason: "token 2 was already minted".to_string(),
+                token_id: Some(event.token_id),
+                collection: None,
+                block_number: Some(event.block_number),
+            },
+        );
+
+        assert!(state
+            .validate_mint(&event)
+            .unwrap_err()
+            .contains("already rejected"));
+    }
+
+    #[test]
+    fn should_roll_back_transfers_and_burns_of_reorganized_blocks() {
+        let mut state = state();
+        let (transferred, burned) = (mint_event(10, 1), mint_event(10, 2));
+        for event in [&transferred, &burned] {
+            apply_state_transition(&mut state, &EventType::AcceptedMint(event.clone()));
+        }
+        let buyer: Address = "0x7e41257f7b5c3dd3313ef02b1f4c864fe95bec2b"
+            .parse()
+            .unwrap();
+        apply_state_transition(
+            &mut state,
+            &EventType::TransferredNft(transfer_event(20, holder(), buyer, 1)),
+        );
+        apply_state_transition(
+            &mut state,
+            &EventType::BurnedNft(transfer_event(20, holder(), Address::ZERO, 2)),
+        );
+        assert!(state.is_burned(&burned.token_key()));
+
+        apply_state_transition(
+            &mut state,
+            &EventType::ChainReorganized {
+                block_number: BlockNumber::from(15_u8),
+            },
+        );
+
+        assert_eq!(state.owner_of(&transferred.token_key()), Some(holder()));
+        assert_eq!(state.owner_of(&burned.token_key()), Some(holder()));
+        assert!(!state.is_burned(&burned.token_key()));
+        assert_eq!(state.supply_of(&burned.token_key()), u256::ONE);
+        assert_eq!(state.tokens_of(&buyer, None).count(), 0);
+    }
+
+    #[test]
+    fn should_restore_burned_token_minted_again_in_reorganized_blocks() {
+        let mut state = state();
+        let first = mint_event(10, 1);
+        apply_state_transition(&mut state, &EventType::AcceptedMint(first.clone()));
+        apply_state_transition(
+            &mut state,
+            &EventType::ReceivedRandomness {
+                seed: [1; 32],
+                event_sources: vec![first.source()],
+            },
+        );
+        apply_state_transition(
+            &mut state,
+            &EventType::MintedNft {
+                event_source: first.source(),
+            },
+        );
+        let burn = transfer_event(11, holder(), Address::ZERO, 1);
+        apply_state_transition(&mut state, &EventType::BurnedNft(burn.clone()));
+        let again = MintEvent {
+            transaction_hash: Hash([2; 32]),
+            ..mint_event(20, 1)
+        };
+        apply_state_transition(&mut state, &EventType::AcceptedMint(again));
+
+        apply_state_transition(
+            &mut state,
+            &EventType::ChainReorganized {
+                block_number: BlockNumber::from(15_u8),
+            },
+        );
+
+        let token = first.token_key();
+        assert!(state.events_to_mint.is_empty());
+        assert_eq!(
+            state.minted_event_of(&token).map(|event| event.source()),
+            Some(first.source())
+        );
+        assert_eq!(state.burned_tokens.get(&token), Some(&burn));
+        assert_eq!(state.owner_of(&token), None);
+        assert_eq!(state.supply_of(&token), u256::ZERO);
+    }
+
+    #[test]
+    fn should_roll_back_editions_of_reorganized_blocks() {
+        let mut state = State {
+            token_standard: TokenStandard::Erc1155,
+            ..state()
+        };
+        let event = MintEvent {
+            value: Some(u256::from(2_u8)),
+            ..mint_event(10, 1)
+        };
+        apply_state_transition(&mut state, &EventType::AcceptedMint(event.clone()));
+        apply_state_transition(
+            &mut state,
+            &EventType::AcceptedEdition(MintEvent {
+                transaction_hash: Hash([2; 32]),
+                value: Some(u256::from(3_u8)),
+                ..mint_event(20, 1)
+            }),
+        );
+        assert_eq!(state.supply_of(&event.token_key()), u256::from(5_u8));
+
+        apply_state_transition(
+            &mut state,
+            &EventType::ChainReorganized {
+                block_number: BlockNumber::from(15_u8),
+            },
+        );
+
+        assert_eq!(state.supply_of(&event.token_key()), u256::from(2_u8));
+    }
+
+    #[test]
+    fn should_accept_events_rejected_in_reorganized_blocks() {
+        let mut state = state();
+        let event = mint_event(20, 2);
+        apply_state_transition(
+            &mut state,
+            &EventType::InvalidTransfer {
+                event_source: event.source(),
+                reason: "token 2 was already minted".to_string(),
+                token_id: Some(event.token_id),
+                collection: None,
+                block_number: Some(event.block_number),
+            },
+        );
+        apply_state_transition(
+            &mut state,
+            &EventType::SyncedToBlock {
+                block_number: BlockNumber::from(20_u8),
+                collection: None,
+                block_hash: Some(Hash([20; 32])),
+            },
+        );
+
+        apply_state_transition(
+            &mut state,
+            &EventType::ChainReorganized {
+                block_number: BlockNumber::from(15_u8),
+            },
+        );
+
+        assert!(state.invalid_events.is_empty());
+        assert!(state.invalid_token_ids.is_empty());
+        assert_eq!(state.validate_mint(&event), Ok(()));
+    }
+
+    #[test]
+    fn should_forget_changes_of_blocks_older_than_recorded_hashes() {
+        let mut state = state();
+        for token_id in 0..=MAX_BLOCK_HASHES as u8 {
+            apply_state_transition(
+                &mut state,
+                &EventType::AcceptedMint(mint_event(token_id, token_id)),
+            );
+        }
+
+        assert!(state
+            .token_changes
+            .iter()
+            .all(|(block_number, _)| *block_number >= BlockNumber::from(1_u8)));
+    }
+
+    fn transfer_event(block_number: u8, from: Address, to: Address, token_id: u8) -> TransferEvent {
+        TransferEvent {
+            transaction_hash: Hash([block_number; 32]),
+            block_number: BlockNumber::from(block_number),
+            block_hash: Some(Hash([block_number; 32])),
+            ..test_fixtures::transfer_event(0, from, to, token_id.into())
+        }
+    }
+
+    fn mint_event(block_number: u8, token_id: u8) -> MintEvent {
+        MintEvent {
+            transaction_hash: Hash([token_id; 32]),
+            block_number: BlockNumber::from(block_number),
+            log_index: LogIndex::from(0_u8),
+            from_address: Address::ZERO,
+            to_address: "0xdd2851cdd40ae6536831558dd46db62fac7a844d"
+                .parse()
+                .unwrap(),
+            token_id: u256::from(token_id),
+            batch_index: None,
+            value: None,
+            collection: None,
+            block_hash: Some(Hash([block_number; 32])),
+        }
+    }
+}
+
+mod skipped_blocks {
+    use crate::address::Address;
+    use crate::eth_logs::{MintEvent, ScrapingMode, TransferEvent};
+    use crate::eth_rpc::Hash;
+    use crate::numeric::{BlockNumber, LogIndex};
+    use crate::state::audit::{apply_state_transition, EventType};
+    use crate::state::State;
+    use crate::test_fixtures::{self, state};
+    use ethnum::u256;
+
+    #[test]
+    fn should_remove_scraped_skipped_block() {
+        let mut state = state();
+        for block_number in [10_u8, 20_u8] {
+            apply_state_transition(
+                &mut state,
+                &EventType::SkippedBlock {
+                    block_number: BlockNumber::from(block_number),
+                    collection: None,
+                },
+            );
+        }
+        apply_state_transition(
+            &mut state,
+            &EventType::ScrapedSkippedBlock {
+                block_number: BlockNumber::from(10_u8),
+                collection: None,
+            },
+        );
+
+        assert_eq!(
+            state.skipped_blocks_of_contracts(),
+            vec![(
+                None,
+                state.ethereum_contract_address,
+                BlockNumber::from(20_u8)
+            )]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "was not skipped")]
+    fn should_not_scrape_block_that_was_not_skipped() {
+        let mut state = state();
+        apply_state_transition(
+            &mut state,
+            &EventType::ScrapedSkippedBlock {
+                block_number: BlockNumber::from(10_u8),
+                collection: None,
+            },
+        );
+    }
+
+    #[test]
+    fn should_apply_chained_transfers_of_skipped_block() {
+        let mut state = state();
+        let [a, b, c] = [[1; 20], [2; 20], [3; 20]].map(Address::new);
+        apply_state_transition(
+            &mut state,
+            &EventType::AcceptedMint(MintEvent {
+                to_address: a,
+                ..test_fixtures::mint_event(0, 7)
+            }),
+        );
+        let transfers = vec![
+            test_fixtures::transfer_event(1, a, b, 7),
+            test_fixtures::transfer_event(2, b, c, 7),
+        ];
+
+        let applied = state.skipped_block_transfers(transfers.clone());
+        assert_eq!(applied, transfers);
+        for transfer in applied {
+            apply_state_transition(&mut state, &EventType::TransferredNft(transfer));
+        }
+
+        assert_eq!(state.owner_of(&u256::from(7_u8).into()), Some(c));
+    }
+
+    #[test]
+    fn should_skip_transfers_of_skipped_block_sent_by_former_owners() {
+        let mut state = state();
+        let [a, b, c, d] = [[1; 20], [2; 20], [3; 20], [4; 20]].map(Address::new);
+        apply_state_transition(
+            &mut state,
+            &EventType::AcceptedMint(MintEvent {
+                to_address: d,
+                ..test_fixtures::mint_event(0, 7)
+            }),
+        );
+        let burn = test_fixtures::transfer_event(3, c, Address::ZERO, 7);
+
+        assert_eq!(
+            state.skipped_block_transfers(vec![
+                test_fixtures::transfer_event(1, a, b, 7),
+                test_fixtures::transfer_event(2, b, c, 7),
+                burn.clone(),
+            ]),
+            vec![burn]
+        );
+    }
+
+    #[test]
+    fn should_keep_owner_transferred_after_mint_of_skipped_block() {
+        let mut state = State {
+            scraping_mode: ScrapingMode::AllTransfers,
+            ..state()
+        };
+        let minter: Address = "0xdd2851cdd40ae6536831558dd46db62fac7a844d"
+            .parse()
+            .unwrap();
+        let buyer: Address = "0xb44B5e756A894775FC32EDdf3314Bb1B1944dC34"
+            .parse()
+            .unwrap();
+        let transfer = TransferEvent {
+            transaction_hash: Hash([2; 32]),
+            block_number: BlockNumber::from(20_u8),
+            log_index: LogIndex::from(0_u8),
+            from_address: minter,
+            to_address: buyer,
+            token_id: u256::from(7_u8),
+            batch_index: None,
+            value: None,
+            collection: None,
+            block_hash: None,
+        };
+        let mint = MintEvent {
+            transaction_hash: Hash([1; 32]),
+            block_number: BlockNumber::from(10_u8),
+            log_index: LogIndex::from(0_u8),
+            from_address: Address::ZERO,
+            to_address: minter,
+            token_id: u256::from(7_u8),
+            batch_index: None,
+            value: None,
+            collection: None,
+            block_hash: None,
+        };
+
+        apply_state_transition(&mut state, &EventType::TransferredNft(transfer));
+        apply_state_transition(&mut state, &EventType::AcceptedMint(mint));
+
+        assert_eq!(state.owner_of(&u256::from(7_u8).into()), Some(buyer));
+    }
+}
+
+mod rpc_providers {
+    use crate::eth_rpc_client::consensus::{ConsensusConfig, ConsensusStrategy};
+    use crate::eth_rpc_client::providers::{RpcHeader, RpcProvider};
+    use crate::lifecycle::init::InitArg;
+    use crate::lifecycle::upgrade::UpgradeArg;
+    use crate::lifecycle::{ChainSpec, EthereumNetwork};
+    use crate::state::{InvalidStateError, State};
+    use crate::test_fixtures::{self, state};
+
+    #[test]
+    fn should_replace_providers_on_upgrade() {
+        let mut state = state();
+        assert_eq!(state.max_block_spread(), 799);
+
+        state
+            .upgrade(UpgradeArg {
+                rpc_providers: Some(vec![provider("https://rpc.ankr.com/eth", Some(100))]),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(state.max_block_spread(), 100);
+
+        state
+            .upgrade(UpgradeArg {
+                rpc_providers: Some(vec![]),
+                ..Default::default()
+            })
+            .unwrap();
+        assert!(state.rpc_providers.is_empty());
+        assert_eq!(state.max_block_spread(), 799);
+    }
+
+    #[test]
+    fn should_limit_block_spread_by_custom_chain_providers() {
+        let mut state = State::try_from(InitArg {
+            ethereum_network: EthereumNetwork::Custom(ChainSpec {
+                chain_id: 8453,
+                name: "Base".to_string(),
+                expected_block_size: 64 * 1024,
+                max_block_spread: 500,
+                providers: vec![
+                    provider("https://mainnet.base.org", None),
+                    provider("https://base.publicnode.com", Some(50)),
+                ],
+            }),
+            ..test_fixtures::init_arg()
+        })
+        .unwrap();
+        assert_eq!(state.max_block_spread(), 50);
+
+        state
+            .upgrade(UpgradeArg {
+                rpc_providers: Some(vec![provider("https://base.llamarpc.com", None)]),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(state.max_block_spread(), 500);
+    }
+
+    #[test]
+    fn should_reject_invalid_providers() {
+        for providers in [
+            vec![provider("http://rpc.ankr.com/eth", None)],
+            vec![provider("https://rpc.ankr.com/eth", Some(0))],
+            vec![
+                provider("https://rpc.ankr.com/eth", None),
+                provider("https://rpc.ankr.com/eth", None),
+            ],
+        ] {
+            assert!(matches!(
+                state().upgrade(UpgradeArg {
+                    rpc_providers: Some(providers),
+                    ..Default::default()
+                }),
+                Err(InvalidStateError::InvalidRpcProviders(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn should_reject_threshold_above_provider_count() {
+        let threshold = |min| ConsensusConfig {
+            eth_get_logs: Some(ConsensusStrategy::Threshold { min }),
+            eth_get_block_by_number: None,
+            eth_call: None,
+            eth_get_transaction_receipt: None,
+        };
+        let mut state = state();
+
+        // Sepolia has two default providers.
+        state
+            .upgrade(UpgradeArg {
+                consensus_config: Some(threshold(2)),
+                ..Default::default()
+            })
+            .unwrap();
+        assert!(matches!(
+            state.clone().upgrade(UpgradeArg {
+                consensus_config: Some(threshold(3)),
+                ..Default::default()
+            }),
+            Err(InvalidStateError::InvalidConsensusConfig(_))
+        ));
+        assert!(matches!(
+            state.upgrade(UpgradeArg {
+                rpc_providers: Some(vec![provider("https://rpc.ankr.com/eth", None)]),
+                ..Default::default()
+            }),
+            Err(InvalidStateError::InvalidConsensusConfig(_))
+        ));
+    }
+
+    #[test]
+    fn should_only_update_strategies_set_by_upgrade() {
+        let mut state = state();
+        for consensus_config in [
+            ConsensusConfig {
+                eth_get_transaction_receipt: Some(ConsensusStrategy::StrictMajority),
+                ..Default::default()
+            },
+            ConsensusConfig {
+                eth_call: Some(ConsensusStrategy::Threshold { min: 1 }),
+                ..Default::default()
+            },
+        ] {
+            state
+                .upgrade(UpgradeArg {
+                    consensus_config: Some(consensus_config),
+                    ..Default::default()
+                })
+                .unwrap();
+        }
+
+        assert_eq!(
+            state.consensus_config,
+            ConsensusConfig {
+                eth_call: Some(ConsensusStrategy::Threshold { min: 1 }),
+                eth_get_transaction_receipt: Some(ConsensusStrategy::StrictMajority),
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn should_redact_header_values() {
+        let provider = provider("https://rpc.ankr.com/eth", None);
+
+        assert!(!format!("{provider:?}").contains("secret"));
+        assert_eq!(
+            provider.redacted().headers,
+            Some(vec![RpcHeader {
+                name: "Authorization".to_string(),
+                value: "<redacted>".to_string(),
+            }])
+        );
+    }
+
+    fn provider(url: &str, max_block_spread: Option<u64>) -> RpcProvider {
+        RpcProvider {
+            url: url.to_string(),
+            headers: Some(vec![RpcHeader {
+                name: "Authorization".to_string(),
+                value: "Bearer secret".to_string(),
+            }]),
+            max_response_size: None,
+            max_block_spread,
+        }
+    }
 }
 
 mod mint_transaction {
     use crate::eth_logs::{EventSourceError, MintEvent};
-    use crate::lifecycle::init::InitArg;
-    use crate::numeric::{wei_from_milli_ether, LedgerMintIndex, LogIndex};
+    use crate::numeric::LogIndex;
     use crate::state::tests::received_eth_event;
-    use crate::state::{MintedEvent, State};
+    use crate::test_fixtures::state;
 
     #[test]
     fn should_record_mint_task_from_event() {
-        let mut state = dummy_state();
+        let mut state = state();
         let event = received_eth_event();
 
         state.record_event_to_mint(&event);
 
-        assert!(state.events_to_mint.contains_key(&event.source()));
-
-        let minted_event = MintedEvent {
-            mint_event: event.clone(),
-        };
+        assert!(state.events_to_mint.contains(&event.source()));
 
         state.record_successful_mint(event.source());
 
-        assert!(!state.events_to_mint.contains_key(&event.source()));
+        assert!(!state.events_to_mint.contains(&event.source()));
         assert_eq!(
-            state.minted_events.get(&event.source()),
-            Some(&minted_event)
+            state
+                .minted_events
+                .get_alt(&event.source())
+                .map(|minted| &minted.mint_event),
+            Some(&event)
         );
     }
 
     #[test]
     fn should_allow_minting_events_with_equal_txhash() {
-        let mut state = dummy_state();
+        let mut state = state();
         let event_1 = MintEvent {
             log_index: LogIndex::from(1u8),
             ..received_eth_event()
         };
         let event_2 = MintEvent {
             log_index: LogIndex::from(2u8),
+            token_id: 1_u8.into(),
             ..received_eth_event()
         };
 
@@ -106,11 +1263,11 @@ mod mint_transaction {
 
         state.record_event_to_mint(&event_1);
 
-        assert!(state.events_to_mint.contains_key(&event_1.source()));
+        assert!(state.events_to_mint.contains(&event_1.source()));
 
         state.record_event_to_mint(&event_2);
 
-        assert!(state.events_to_mint.contains_key(&event_2.source()));
+        assert!(state.events_to_mint.contains(&event_2.source()));
 
         assert_eq!(2, state.events_to_mint.len());
     }
@@ -118,41 +1275,43 @@ mod mint_transaction {
     #[test]
     #[should_panic = "unknown event"]
     fn should_not_allow_unknown_mints() {
-        let mut state = dummy_state();
+        let mut state = state();
         let event = received_eth_event();
 
-        assert!(!state.events_to_mint.contains_key(&event.source()));
+        assert!(!state.events_to_mint.contains(&event.source()));
         state.record_successful_mint(event.source());
     }
 
     #[test]
     #[should_panic = "invalid"]
     fn should_not_record_invalid_deposit_already_recorded_as_valid() {
-        let mut state = dummy_state();
+        let mut state = state();
         let event = received_eth_event();
 
         state.record_event_to_mint(&event);
 
-        assert!(state.events_to_mint.contains_key(&event.source()));
+        assert!(state.events_to_mint.contains(&event.source()));
 
         state.record_invalid_deposit(
             event.source(),
+            None,
             EventSourceError::InvalidEvent("bad".to_string()).to_string(),
+            None,
         );
     }
 
     #[test]
     fn should_not_update_already_recorded_invalid_deposit() {
-        let mut state = dummy_state();
+        let mut state = state();
         let event = received_eth_event();
         let error = EventSourceError::InvalidEvent("first".to_string());
         let other_error = EventSourceError::InvalidEvent("second".to_string());
         assert_ne!(error, other_error);
 
-        assert!(state.record_invalid_deposit(event.source(), error.to_string()));
+        assert!(state.record_invalid_deposit(event.source(), None, error.to_string(), None));
         assert_eq!(state.invalid_events[&event.source()], error.to_string());
 
-        assert!(!state.record_invalid_deposit(event.source(), other_error.to_string()));
+        assert!(!state.record_invalid_deposit(event.source(), None, other_error.to_string(), None));
         assert_eq!(state.invalid_events[&event.source()], error.to_string());
     }
 
@@ -163,27 +1322,15 @@ mod mint_transaction {
           block_number: 3_960_623, \
           log_index: 29, \
           from_address: 0xdd2851Cdd40aE6536831558DD46db62fAc7A844d, \
-          value: 10_000_000_000_000_000, \
-          principal: k2t6j-2nvnp-4zjm3-25dtz-6xhaa-c7boj-5gayf-oj3xs-i43lp-teztq-6ae \
+          to_address: 0xbb2851cDD40AE6536831558dD46dB62fAc7a844D, \
+          token_id: 0, \
+          batch_index: None, \
+          value: None, \
+          collection: None, \
+          block_hash: None \
         }";
         assert_eq!(format!("{:?}", received_eth_event()), expected);
     }
-
-    fn dummy_state() -> State {
-        use candid::Principal;
-        State::try_from(InitArg {
-            ethereum_network: Default::default(),
-            ecdsa_key_name: "test_key_1".to_string(),
-            ethereum_contract_address: None,
-            ledger_id: Principal::from_text("apia6-jaaaa-aaaar-qabma-cai")
-                .expect("BUG: invalid principal"),
-            ethereum_block_height: Default::default(),
-            minimum_withdrawal_amount: wei_from_milli_ether(10).into(),
-            next_transaction_nonce: Default::default(),
-            last_scraped_block_number: Default::default(),
-        })
-        .expect("init args should be valid")
-    }
 }
 
 fn received_eth_event() -> MintEvent {
@@ -199,44 +1346,27 @@ fn received_eth_event() -> MintEvent {
         to_address: "0xbb2851cdd40ae6536831558dd46db62fac7a844d"
             .parse()
             .unwrap(),
-        token_id: TokenId::from(0u64),
+        token_id: u256::ZERO,
+        batch_index: None,
+        value: None,
+        collection: None,
+        block_hash: None,
     }
 }
 
 mod upgrade {
     use crate::address::Address;
-    use crate::eth_rpc::BlockTag;
+    use crate::eth_logs::ScrapingMode;
+    use crate::eth_rpc::{BlockHeight, BlockTag};
     use crate::lifecycle::upgrade::UpgradeArg;
-    use crate::numeric::{wei_from_milli_ether, TransactionNonce, Wei};
-    use crate::state::{InvalidStateError, State};
+    use crate::state::InvalidStateError;
+    use crate::test_fixtures::{self, state};
     use assert_matches::assert_matches;
-    use candid::Nat;
-    use num_bigint::BigUint;
     use std::str::FromStr;
 
     #[test]
     fn should_fail_when_upgrade_args_invalid() {
-        let mut state = initial_state();
-        assert_matches!(
-            state.upgrade(UpgradeArg {
-                next_transaction_nonce: Some(Nat(BigUint::from_bytes_be(
-                    &ethnum::u256::MAX.to_be_bytes(),
-                ) + 1_u8)),
-                ..Default::default()
-            }),
-            Err(InvalidStateError::InvalidTransactionNonce(_))
-        );
-
-        let mut state = initial_state();
-        assert_matches!(
-            state.upgrade(UpgradeArg {
-                minimum_withdrawal_amount: Some(Nat::from(0)),
-                ..Default::default()
-            }),
-            Err(InvalidStateError::InvalidMinimumWithdrawalAmount(_))
-        );
-
-        let mut state = initial_state();
+        let mut state = state();
         assert_matches!(
             state.upgrade(UpgradeArg {
                 ethereum_contract_address: Some("invalid".to_string()),
@@ -245,7 +1375,7 @@ mod upgrade {
             Err(InvalidStateError::InvalidEthereumContractAddress(_))
         );
 
-        let mut state = initial_state();
+        let mut state = test_fixtures::state();
         assert_matches!(
             state.upgrade(UpgradeArg {
                 ethereum_contract_address: Some(
@@ -260,45 +1390,89 @@ mod upgrade {
     #[test]
     fn should_succeed() {
         use crate::endpoints::CandidBlockTag;
-        let mut state = initial_state();
+        let mut state = state();
         let upgrade_arg = UpgradeArg {
-            next_transaction_nonce: Some(Nat::from(15)),
-            minimum_withdrawal_amount: Some(Nat::from(100)),
             ethereum_contract_address: Some(
-                "0xb44B5e756A894775FC32EDdf3314Bb1B1944dC34".to_string(),
+                "0x7e41257f7b5c3dd3313ef02b1f4c864fe95bec2b".to_string(),
             ),
             ethereum_block_height: Some(CandidBlockTag::Safe),
+            scraping_mode: Some(ScrapingMode::AllTransfers),
+            ..Default::default()
         };
 
         state.upgrade(upgrade_arg).expect("valid upgrade args");
 
-        assert_eq!(
-            state.eth_transactions.next_transaction_nonce(),
-            TransactionNonce::from(15_u64)
+        assert_eq!(
+            state.ethereum_contract_address,
+            Address::from_str("0x7e41257f7b5c3dd3313ef02b1f4c864fe95bec2b").unwrap()
+        );
+        assert_eq!(
+            state.ethereum_block_height,
+            BlockHeight::Tag(BlockTag::Safe)
+        );
+        assert_eq!(state.scraping_mode, ScrapingMode::AllTransfers);
+    }
+
+    #[test]
+    fn should_reject_generator_config_changing_images_of_minted_tokens() {
+        use crate::generator::{GeneratorConfig, TraitType, TraitValue};
+        use crate::state::audit::{apply_state_transition, EventType};
+        use crate::state::tests::received_eth_event;
+
+        let config = GeneratorConfig {
+            trait_types: vec![TraitType {
+                name: "Background".to_string(),
+                values: vec![TraitValue {
+                    name: "Blue".to_string(),
+                    weight: 1,
+                    layer: Some("<rect fill=\"blue\"/>".to_string()),
+                    asset: None,
+                }],
+            }],
+            canvas: None,
+        };
+        let mut state = state();
+        state
+            .upgrade(UpgradeArg {
+                generator_config: Some(config.clone()),
+                ..Default::default()
+            })
+            .expect("no token was minted yet");
+        let event = received_eth_event();
+        apply_state_transition(&mut state, &EventType::AcceptedMint(event.clone()));
+        apply_state_transition(
+            &mut state,
+            &EventType::ReceivedRandomness {
+                seed: [1; 32],
+                event_sources: vec![event.source()],
+            },
+        );
+        apply_state_transition(
+            &mut state,
+            &EventType::MintedNft {
+                event_source: event.source(),
+            },
+        );
+
+        let mut changed_layer = config.clone();
+        changed_layer.trait_types[0].values[0].layer = Some("<rect fill=\"red\"/>".to_string());
+        assert_matches!(
+            state.upgrade(UpgradeArg {
+                generator_config: Some(changed_layer),
+                ..Default::default()
+            }),
+            Err(InvalidStateError::InvalidGeneratorConfig(_))
         );
-        assert_eq!(state.minimum_withdrawal_amount, Wei::from(100_u64));
+
+        let mut changed_weight = config;
+        changed_weight.trait_types[0].values[0].weight = 2;
         assert_eq!(
-            state.ethereum_contract_address,
-            Some(Address::from_str("0xb44B5e756A894775FC32EDdf3314Bb1B1944dC34").unwrap())
+            state.upgrade(UpgradeArg {
+                generator_config: Some(changed_weight),
+                ..Default::default()
+            }),
+            Ok(())
         );
-        assert_eq!(state.ethereum_block_height, BlockTag::Safe);
-    }
-
-    fn initial_state() -> State {
-        use crate::lifecycle::init::InitArg;
-        use candid::Principal;
-        State::try_from(InitArg {
-            ethereum_network: Default::default(),
-            ecdsa_key_name: "test_key_1".to_string(),
-            ethereum_contract_address: None,
-            ledger_id: Principal::from_text("apia6-jaaaa-aaaar-qabma-cai")
-                .expect("BUG: invalid principal"),
-            ethereum_block_height: Default::default(),
-            minimum_withdrawal_amount: wei_from_milli_ether(10).into(),
-            next_transaction_nonce: Default::default(),
-            last_scraped_block_number: Default::default(),
-        })
-        .expect("valid init args")
     }
 }
 
@@ -310,10 +1484,6 @@ fn arb_address() -> impl Strategy<Value = Address> {
     uniform20(any::<u8>()).prop_map(Address::new)
 }
 
-fn arb_principal() -> impl Strategy<Value = Principal> {
-    pvec(any::<u8>(), 0..=29).prop_map(|bytes| Principal::from_slice(&bytes))
-}
-
 fn arb_u256() -> impl Strategy<Value = u256> {
     uniform32(any::<u8>()).prop_map(u256::from_be_bytes)
 }
@@ -323,10 +1493,16 @@ fn arb_checked_amount_of<Unit>() -> impl Strategy<Value = CheckedAmountOf<Unit>>
 }
 
 fn arb_event_source() -> impl Strategy<Value = EventSource> {
-    (arb_hash(), arb_checked_amount_of()).prop_map(|(transaction_hash, log_index)| EventSource {
-        transaction_hash,
-        log_index,
-    })
+    (
+        arb_hash(),
+        arb_checked_amount_of(),
+        proptest::option::of(any::<u32>()),
+    )
+        .prop_map(|(transaction_hash, log_index, batch_index)| EventSource {
+            transaction_hash,
+            log_index,
+            batch_index,
+        })
 }
 
 fn arb_block_tag() -> impl Strategy<Value = CandidBlockTag> {
@@ -334,6 +1510,7 @@ fn arb_block_tag() -> impl Strategy<Value = CandidBlockTag> {
         Just(CandidBlockTag::Safe),
         Just(CandidBlockTag::Latest),
         Just(CandidBlockTag::Finalized),
+        any::<u64>().prop_map(CandidBlockTag::LatestMinusConfirmations),
     ]
 }
 
@@ -341,40 +1518,30 @@ fn arb_nat() -> impl Strategy<Value = Nat> {
     any::<u128>().prop_map(Nat::from)
 }
 
-fn arb_storage_key() -> impl Strategy<Value = StorageKey> {
-    uniform32(any::<u8>()).prop_map(StorageKey)
-}
-fn arb_access_list_item() -> impl Strategy<Value = AccessListItem> {
-    (arb_address(), pvec(arb_storage_key(), 0..100)).prop_map(|(address, storage_keys)| {
-        AccessListItem {
-            address,
-            storage_keys,
-        }
-    })
-}
-fn arb_access_list() -> impl Strategy<Value = AccessList> {
-    pvec(arb_access_list_item(), 0..100).prop_map(AccessList)
+fn arb_collection() -> impl Strategy<Value = Option<CollectionId>> {
+    proptest::option::of("[a-z][a-z0-9_-]{0,15}".prop_map(|id| id.parse().unwrap()))
 }
 
 prop_compose! {
     fn arb_init_arg()(
-        contract_address in proptest::option::of(arb_address()),
+        minter_address in proptest::option::of(arb_address()),
+        contract_address in arb_address(),
         ethereum_block_height in arb_block_tag(),
-        minimum_withdrawal_amount in arb_nat(),
-        next_transaction_nonce in arb_nat(),
-        ledger_id in arb_principal(),
-        ecdsa_key_name in "[a-z_]*",
         last_scraped_block_number in arb_nat(),
+        scraping_mode in proptest::option::of(arb_scraping_mode()),
+        token_standard in proptest::option::of(arb_token_standard()),
     ) -> InitArg {
         InitArg {
             ethereum_network: EthereumNetwork::Sepolia,
-            ecdsa_key_name,
-            ethereum_contract_address: contract_address.map(|addr| addr.to_string()),
-            ledger_id,
+            minter_address: minter_address.map(|addr| addr.to_string()),
+            ethereum_contract_address: contract_address.to_string(),
             ethereum_block_height,
-            minimum_withdrawal_amount,
-            next_transaction_nonce,
-            last_scraped_block_number
+            last_scraped_block_number,
+            generator_config: None,
+            collection_metadata: None,
+            reveal_config: None,
+            scraping_mode,
+            token_standard,
         }
     }
 }
@@ -383,26 +1550,41 @@ prop_compose! {
     fn arb_upgrade_arg()(
         contract_address in proptest::option::of(arb_address()),
         ethereum_block_height in proptest::option::of(arb_block_tag()),
-        minimum_withdrawal_amount in proptest::option::of(arb_nat()),
-        next_transaction_nonce in proptest::option::of(arb_nat()),
+        scraping_mode in proptest::option::of(arb_scraping_mode()),
+        remove_collections in proptest::option::of(pvec("[a-z]{1,8}", 0..3)),
+        verify_receipts in proptest::option::of(any::<bool>()),
     ) -> UpgradeArg {
         UpgradeArg {
             ethereum_contract_address: contract_address.map(|addr| addr.to_string()),
             ethereum_block_height,
-            minimum_withdrawal_amount,
-            next_transaction_nonce,
+            scraping_mode,
+            remove_collections,
+            verify_receipts,
+            ..Default::default()
         }
     }
 }
 
+fn arb_scraping_mode() -> impl Strategy<Value = ScrapingMode> {
+    prop_oneof![Just(ScrapingMode::Mints), Just(ScrapingMode::AllTransfers)]
+}
+
+fn arb_token_standard() -> impl Strategy<Value = TokenStandard> {
+    prop_oneof![Just(TokenStandard::Erc721), Just(TokenStandard::Erc1155)]
+}
+
 prop_compose! {
-    fn arb_received_eth_event()(
+    fn arb_mint_event()(
         transaction_hash in arb_hash(),
         block_number in arb_checked_amount_of(),
         log_index in arb_checked_amount_of(),
         from_address in arb_address(),
         to_address in arb_address(),
-        token_id in arb_checked_amount_of(),
+        token_id in arb_u256(),
+        batch_index in proptest::option::of(any::<u32>()),
+        value in proptest::option::of(arb_u256()),
+        collection in arb_collection(),
+        block_hash in proptest::option::of(arb_hash()),
     ) -> MintEvent {
         MintEvent {
             transaction_hash,
@@ -410,78 +1592,39 @@ prop_compose! {
             log_index,
             from_address,
             to_address,
-            token_id
-        }
-    }
-}
-
-prop_compose! {
-    fn arb_unsigned_tx()(
-        chain_id in any::<u64>(),
-        nonce in arb_checked_amount_of(),
-        max_priority_fee_per_gas in arb_checked_amount_of(),
-        max_fee_per_gas in arb_checked_amount_of(),
-        gas_limit in arb_checked_amount_of(),
-        destination in arb_address(),
-        amount in arb_checked_amount_of(),
-        data in pvec(any::<u8>(), 0..20),
-        access_list in arb_access_list(),
-    ) -> Eip1559TransactionRequest {
-         Eip1559TransactionRequest {
-            chain_id,
-            nonce,
-            max_priority_fee_per_gas,
-            max_fee_per_gas,
-            gas_limit,
-            destination,
-            amount,
-            data,
-            access_list,
+            token_id,
+            batch_index,
+            value,
+            collection,
+            block_hash,
         }
     }
 }
 
 prop_compose! {
-    fn arb_signed_tx()(
-        unsigned_tx in arb_unsigned_tx(),
-        r in arb_u256(),
-        s in arb_u256(),
-        signature_y_parity in any::<bool>(),
-    ) -> SignedEip1559TransactionRequest {
-        SignedEip1559TransactionRequest::from((
-            unsigned_tx,
-            Eip1559Signature {
-                r,
-                s,
-                signature_y_parity,
-            }
-        ))
-    }
-}
-
-fn arb_transaction_status() -> impl Strategy<Value = TransactionStatus> {
-    prop_oneof![
-        Just(TransactionStatus::Success),
-        Just(TransactionStatus::Failure),
-    ]
-}
-
-prop_compose! {
-    fn arb_tx_receipt()(
-        block_hash in arb_hash(),
-        block_number in arb_checked_amount_of(),
-        effective_gas_price in arb_checked_amount_of(),
-        gas_used in arb_checked_amount_of(),
-        status in arb_transaction_status(),
+    fn arb_transfer_event()(
         transaction_hash in arb_hash(),
-    ) -> TransactionReceipt {
-        TransactionReceipt {
-            block_hash,
-            block_number,
-            effective_gas_price,
-            gas_used,
-            status,
+        block_number in arb_checked_amount_of(),
+        log_index in arb_checked_amount_of(),
+        from_address in arb_address(),
+        to_address in arb_address(),
+        token_id in arb_u256(),
+        batch_index in proptest::option::of(any::<u32>()),
+        value in proptest::option::of(arb_u256()),
+        collection in arb_collection(),
+        block_hash in proptest::option::of(arb_hash()),
+    ) -> TransferEvent {
+        TransferEvent {
             transaction_hash,
+            block_number,
+            log_index,
+            from_address,
+            to_address,
+            token_id,
+            batch_index,
+            value,
+            collection,
+            block_hash,
         }
     }
 }
@@ -490,37 +1633,69 @@ fn arb_event_type() -> impl Strategy<Value = EventType> {
     prop_oneof![
         arb_init_arg().prop_map(EventType::Init),
         arb_upgrade_arg().prop_map(EventType::Upgrade),
-        arb_received_eth_event().prop_map(EventType::AcceptedMint),
-        arb_event_source().prop_map(|event_source| EventType::InvalidTransfer {
-            event_source,
-            reason: "bad principal".to_string()
-        }),
-        (arb_event_source()).prop_map(|(event_source)| { EventType::MintedNft { event_source } }),
-        arb_checked_amount_of().prop_map(|block_number| EventType::SyncedToBlock { block_number }),
-        (any::<u64>(), arb_unsigned_tx()).prop_map(|(withdrawal_id, transaction)| {
-            EventType::CreatedTransaction {
-                withdrawal_id: withdrawal_id.into(),
-                transaction,
+        arb_mint_event().prop_map(EventType::AcceptedMint),
+        (
+            arb_event_source(),
+            proptest::option::of(arb_u256()),
+            arb_collection(),
+            proptest::option::of(arb_checked_amount_of())
+        )
+            .prop_map(|(event_source, token_id, collection, block_number)| {
+                EventType::InvalidTransfer {
+                    event_source,
+                    reason: "bad token".to_string(),
+                    token_id,
+                    collection,
+                    block_number,
+                }
+            }),
+        arb_event_source().prop_map(|event_source| EventType::MintedNft { event_source }),
+        (
+            arb_checked_amount_of(),
+            arb_collection(),
+            proptest::option::of(arb_hash())
+        )
+            .prop_map(
+                |(block_number, collection, block_hash)| EventType::SyncedToBlock {
+                    block_number,
+                    collection,
+                    block_hash,
+                }
+            ),
+        (arb_checked_amount_of(), arb_collection()).prop_map(|(block_number, collection)| {
+            EventType::SkippedBlock {
+                block_number,
+                collection,
             }
         }),
-        (any::<u64>(), arb_signed_tx()).prop_map(|(withdrawal_id, transaction)| {
-            EventType::SignedTransaction {
-                withdrawal_id: withdrawal_id.into(),
-                transaction,
+        (uniform32(any::<u8>()), pvec(arb_event_source(), 0..10)).prop_map(
+            |(seed, event_sources)| EventType::ReceivedRandomness {
+                seed,
+                event_sources,
             }
-        }),
-        (any::<u64>(), arb_unsigned_tx()).prop_map(|(withdrawal_id, transaction)| {
-            EventType::ReplacedTransaction {
-                withdrawal_id: withdrawal_id.into(),
-                transaction,
+        ),
+        (uniform32(any::<u8>()), uniform32(any::<u8>())).prop_map(|(seed, provenance_hash)| {
+            EventType::CommittedProvenance {
+                seed,
+                provenance_hash,
             }
         }),
-        (any::<u64>(), arb_tx_receipt()).prop_map(|(withdrawal_id, transaction_receipt)| {
-            EventType::FinalizedTransaction {
-                withdrawal_id: withdrawal_id.into(),
-                transaction_receipt,
+        uniform32(any::<u8>()).prop_map(|seed| EventType::Revealed { seed }),
+        arb_transfer_event().prop_map(EventType::TransferredNft),
+        arb_transfer_event().prop_map(EventType::BurnedNft),
+        arb_mint_event().prop_map(EventType::AcceptedEdition),
+        arb_checked_amount_of()
+            .prop_map(|block_number| EventType::ChainReorganized { block_number }),
+        (arb_checked_amount_of(), arb_collection()).prop_map(|(block_number, collection)| {
+            EventType::ScrapedSkippedBlock {
+                block_number,
+                collection,
             }
         }),
+        (arb_event_source(), ".*").prop_map(|(event_source, reason)| EventType::RejectedMint {
+            event_source,
+            reason,
+        }),
     ]
 }
 
@@ -539,223 +1714,52 @@ proptest! {
 
 #[test]
 fn state_equivalence() {
-    use crate::eth_rpc_client::responses::{TransactionReceipt, TransactionStatus};
-    use crate::map::MultiKeyMap;
-    use crate::state::transactions::{
-        EthTransactions, EthWithdrawalRequest, Reimbursed, ReimbursementRequest,
-    };
-    use crate::state::MintedEvent;
-    use crate::tx::{Eip1559Signature, Eip1559TransactionRequest};
-    use ic_cdk::api::management_canister::ecdsa::EcdsaPublicKeyResponse;
-    use maplit::btreemap;
-
-    fn source(txhash: &str, index: u64) -> EventSource {
-        EventSource {
-            transaction_hash: txhash.parse().unwrap(),
-            log_index: LogIndex::from(index),
-        }
-    }
+    use crate::state::audit::apply_state_transition;
+    use crate::test_fixtures::state;
 
-    fn singleton_map<T: std::fmt::Debug>(
-        nonce: u128,
-        burn_index: u64,
-        value: T,
-    ) -> MultiKeyMap<TransactionNonce, LedgerBurnIndex, T> {
-        let mut map = MultiKeyMap::new();
-        map.try_insert(
-            TransactionNonce::new(nonce),
-            LedgerBurnIndex::new(burn_index),
-            value,
-        )
-        .unwrap();
-        map
-    }
-
-    let withdrawal_request1 = EthWithdrawalRequest {
-        withdrawal_amount: Wei::new(10_999_968_499_999_664_000),
-        destination: "0xA776Cc20DFdCCF0c3ba89cB9Fb0f10Aba5b98f52"
+    let minted_event = MintEvent {
+        transaction_hash: "0x705f826861c802b407843e99af986cfde8749b669e5e0a5a150f4350bcaa9bc3"
             .parse()
             .unwrap(),
-        ledger_burn_index: LedgerBurnIndex::new(10),
-        from: "2chl6-4hpzw-vqaaa-aaaaa-c".parse().unwrap(),
-        from_subaccount: None,
-        created_at: Some(1699527697000000000),
+        block_number: BlockNumber::new(450_000),
+        log_index: LogIndex::new(1),
+        token_id: u256::from(1_u8),
+        ..received_eth_event()
     };
-    let withdrawal_request2 = EthWithdrawalRequest {
-        ledger_burn_index: LedgerBurnIndex::new(20),
-        ..withdrawal_request1.clone()
+    let mut state = State {
+        first_scraped_block_number: BlockNumber::new(1_000_001),
+        last_scraped_block_number: BlockNumber::new(1_000_000),
+        ..state()
     };
-    let eth_transactions = EthTransactions {
-        withdrawal_requests: vec![withdrawal_request1.clone(), withdrawal_request2.clone()]
-            .into_iter()
-            .collect(),
-        created_tx: singleton_map(
-            2,
-            4,
-            Eip1559TransactionRequest {
-                chain_id: 1,
-                nonce: TransactionNonce::new(2),
-                max_priority_fee_per_gas: WeiPerGas::new(100_000_000),
-                max_fee_per_gas: WeiPerGas::new(100_000_000),
-                gas_limit: GasAmount::new(21_000),
-                destination: "0xA776Cc20DFdCCF0c3ba89cB9Fb0f10Aba5b98f52"
-                    .parse()
-                    .unwrap(),
-                amount: Wei::new(1_000_000_000_000),
-                data: vec![],
-                access_list: Default::default(),
-            },
-        ),
-        sent_tx: singleton_map(
-            1,
-            3,
-            vec![SignedEip1559TransactionRequest::from((
-                Eip1559TransactionRequest {
-                    chain_id: 1,
-                    nonce: TransactionNonce::new(1),
-                    max_priority_fee_per_gas: WeiPerGas::new(100_000_000),
-                    max_fee_per_gas: WeiPerGas::new(100_000_000),
-                    gas_limit: GasAmount::new(21_000),
-                    destination: "0xA776Cc20DFdCCF0c3ba89cB9Fb0f10Aba5b98f52"
-                        .parse()
-                        .unwrap(),
-                    amount: Wei::new(1_000_000_000_000),
-                    data: vec![],
-                    access_list: Default::default(),
-                },
-                Eip1559Signature {
-                    signature_y_parity: true,
-                    r: Default::default(),
-                    s: Default::default(),
-                },
-            ))],
-        ),
-        finalized_tx: singleton_map(
-            0,
-            2,
-            SignedEip1559TransactionRequest::from((
-                Eip1559TransactionRequest {
-                    chain_id: 1,
-                    nonce: TransactionNonce::new(0),
-                    max_priority_fee_per_gas: WeiPerGas::new(100_000_000),
-                    max_fee_per_gas: WeiPerGas::new(100_000_000),
-                    gas_limit: GasAmount::new(21_000),
-                    destination: "0xA776Cc20DFdCCF0c3ba89cB9Fb0f10Aba5b98f52"
-                        .parse()
-                        .unwrap(),
-                    amount: Wei::new(1_000_000_000_000),
-                    data: vec![],
-                    access_list: Default::default(),
-                },
-                Eip1559Signature {
-                    signature_y_parity: true,
-                    r: Default::default(),
-                    s: Default::default(),
-                },
-            ))
-            .try_finalize(TransactionReceipt {
-                block_hash: "0x9e1e2124a453e7b5afaabe42fb66fffb12d4b1053403d2f487d250007f3cb550"
-                    .parse()
-                    .unwrap(),
-                block_number: BlockNumber::new(400_000),
-                effective_gas_price: WeiPerGas::new(100_000_000),
-                gas_used: GasAmount::new(21_000),
-                status: TransactionStatus::Success,
+    for payload in [
+        EventType::AcceptedMint(received_eth_event()),
+        EventType::AcceptedMint(minted_event.clone()),
+        EventType::MintedNft {
+            event_source: minted_event.source(),
+        },
+        EventType::InvalidTransfer {
+            event_source: EventSource {
                 transaction_hash:
-                    "0x06afc3c693dc2ba2c19b5c287c4dddce040d766bea5fd13c8a7268b04aa94f2d"
+                    "0x05c6ec45699c9a6a4b1a4ea2058b0cee852ea2f19b18fb8313c04bf8156efde4"
                         .parse()
                         .unwrap(),
-            })
-            .expect("valid receipt"),
-        ),
-        next_nonce: TransactionNonce::new(3),
-        maybe_reimburse: btreemap! {
-            LedgerBurnIndex::new(4) => EthWithdrawalRequest {
-                withdrawal_amount: Wei::new(1_000_000_000_000),
-                ledger_burn_index: LedgerBurnIndex::new(4),
-                destination: "0xA776Cc20DFdCCF0c3ba89cB9Fb0f10Aba5b98f52".parse().unwrap(),
-                from: "ezu3d-2mifu-k3bh4-oqhrj-mbrql-5p67r-pp6pr-dbfra-unkx5-sxdtv-rae"
-                    .parse()
-                    .unwrap(),
-                from_subaccount: None,
-                created_at: Some(1699527697000000000),
-            }
-        },
-        reimbursement_requests: btreemap! {
-            LedgerBurnIndex::new(3) => ReimbursementRequest {
-                transaction_hash: Some("0x06afc3c693dc2ba2c19b5c287c4dddce040d766bea5fd13c8a7268b04aa94f2d"
-                .parse()
-                .unwrap()),
-                withdrawal_id: LedgerBurnIndex::new(3),
-                reimbursed_amount: Wei::new(100_000_000_000),
-                to: "ezu3d-2mifu-k3bh4-oqhrj-mbrql-5p67r-pp6pr-dbfra-unkx5-sxdtv-rae".parse().unwrap(),
-                to_subaccount: None,
-            }
-        },
-        reimbursed: btreemap! {
-            LedgerBurnIndex::new(6) => Reimbursed {
-                transaction_hash: Some("0x06afc3c693dc2ba2c19b5c287c4dddce040d766bea5fd13c8a7268b04aa94f2d".parse().unwrap()),
-                reimbursed_in_block: LedgerMintIndex::new(150),
-                reimbursed_amount: Wei::new(10_000_000_000_000),
-                withdrawal_id: LedgerBurnIndex::new(6),
+                log_index: LogIndex::new(11),
+                batch_index: None,
             },
+            reason: "failed to decode token id".to_string(),
+            token_id: None,
+            collection: None,
+            block_number: None,
         },
-    };
-    let state = State {
-        ethereum_network: EthereumNetwork::Mainnet,
-        ecdsa_key_name: "test_key".to_string(),
-        ledger_id: "apia6-jaaaa-aaaar-qabma-cai".parse().unwrap(),
-        ethereum_contract_address: Some(
-            "0xb44B5e756A894775FC32EDdf3314Bb1B1944dC34"
-                .parse()
-                .unwrap(),
-        ),
-        ecdsa_public_key: Some(EcdsaPublicKeyResponse {
-            public_key: vec![1; 32],
-            chain_code: vec![2; 32],
-        }),
-        minimum_withdrawal_amount: Wei::new(1_000_000_000_000_000),
-        ethereum_block_height: BlockTag::Finalized,
-        first_scraped_block_number: BlockNumber::new(1_000_001),
-        last_scraped_block_number: BlockNumber::new(1_000_000),
-        last_observed_block_number: Some(BlockNumber::new(2_000_000)),
-        events_to_mint: btreemap! {
-            source("0xac493fb20c93bd3519a4a5d90ce72d69455c41c5b7e229dafee44344242ba467", 100) => MintEvent {
-                transaction_hash: "0xac493fb20c93bd3519a4a5d90ce72d69455c41c5b7e229dafee44344242ba467".parse().unwrap(),
-                block_number: BlockNumber::new(500_000),
-                log_index: LogIndex::new(100),
-                from_address: "0x9d68bd6F351bE62ed6dBEaE99d830BECD356Ed25".parse().unwrap(),
-                to_address: "0xbb68bd6F351bE62ed6dBEaE99d830BECD356Ed25".parse().unwrap(),
-                token_id : TokenId::from(1u64)
-            }
-        },
-        minted_events: btreemap! {
-            source("0x705f826861c802b407843e99af986cfde8749b669e5e0a5a150f4350bcaa9bc3", 1) => MintedEvent {
-                mint_event: MintEvent {
-                    transaction_hash: "0x705f826861c802b407843e99af986cfde8749b669e5e0a5a150f4350bcaa9bc3".parse().unwrap(),
-                    block_number: BlockNumber::new(450_000),
-                    log_index: LogIndex::new(1),
-                    from_address: "0x9d68bd6F351bE62ed6dBEaE99d830BECD356Ed25".parse().unwrap(),
-                    to_address: "0xbb68bd6F351bE62ed6dBEaE99d830BECD356Ed25".parse().unwrap(),
-                    token_id : TokenId::from(0u64)
-                },
-            }
-        },
-        invalid_events: btreemap! {
-            source("0x05c6ec45699c9a6a4b1a4ea2058b0cee852ea2f19b18fb8313c04bf8156efde4", 11) => "failed to decode principal from bytes 0x00333c125dc9f41abaf2b8b85d49fdc7ff75b2a4000000000000000000000000".to_string(),
-        },
-        eth_transactions: eth_transactions.clone(),
-        retrieve_eth_principals: Default::default(),
-        active_tasks: Default::default(),
-        http_request_counter: 100,
-        eth_balance: Default::default(),
-        skipped_blocks: Default::default(),
-    };
+    ] {
+        apply_state_transition(&mut state, &payload);
+    }
+    state.last_observed_block_number = Some(BlockNumber::new(2_000_000));
+    state.http_request_counter = 100;
 
     assert_eq!(
         Ok(()),
         state.is_equivalent_to(&State {
-            ecdsa_public_key: None,
             last_observed_block_number: None,
             http_request_counter: 0,
             ..state.clone()
@@ -784,25 +1788,9 @@ fn state_equivalence() {
     assert_ne!(
         Ok(()),
         state.is_equivalent_to(&State {
-            ecdsa_key_name: "".to_string(),
-            ..state.clone()
-        }),
-        "changing essential fields should break equivalence",
-    );
-
-    assert_ne!(
-        Ok(()),
-        state.is_equivalent_to(&State {
-            ethereum_contract_address: None,
-            ..state.clone()
-        }),
-        "changing essential fields should break equivalence",
-    );
-
-    assert_ne!(
-        Ok(()),
-        state.is_equivalent_to(&State {
-            minimum_withdrawal_amount: Wei::new(1),
+            ethereum_contract_address: "0x7e41257f7b5c3dd3313ef02b1f4c864fe95bec2b"
+                .parse()
+                .unwrap(),
             ..state.clone()
         }),
         "changing essential fields should break equivalence",
@@ -811,7 +1799,7 @@ fn state_equivalence() {
     assert_ne!(
         Ok(()),
         state.is_equivalent_to(&State {
-            ethereum_block_height: BlockTag::Latest,
+            ethereum_block_height: BlockHeight::Tag(BlockTag::Finalized),
             ..state.clone()
         }),
         "changing essential fields should break equivalence",
@@ -844,343 +1832,12 @@ fn state_equivalence() {
         "changing essential fields should break equivalence",
     );
 
-    assert_eq!(
-        Ok(()),
-        state.is_equivalent_to(&State {
-            eth_transactions: EthTransactions {
-                withdrawal_requests: vec![withdrawal_request2.clone(), withdrawal_request1.clone()]
-                    .into_iter()
-                    .collect(),
-                ..eth_transactions.clone()
-            },
-            ..state.clone()
-        },),
-        "changing the order of withdrawal requests should result in an equivalent state",
-    );
-
-    assert_ne!(
-        Ok(()),
-        state.is_equivalent_to(&State {
-            eth_transactions: EthTransactions {
-                withdrawal_requests: vec![withdrawal_request1].into_iter().collect(),
-                ..eth_transactions.clone()
-            },
-            ..state.clone()
-        }),
-        "changing the withdrawal requests should break equivalence"
-    );
-
-    assert_ne!(
-        Ok(()),
-        state.is_equivalent_to(&State {
-            eth_transactions: EthTransactions {
-                sent_tx: Default::default(),
-                ..eth_transactions.clone()
-            },
-            ..state.clone()
-        }),
-        "changing the transactions should break equivalence"
-    );
-
-    assert_ne!(
-        Ok(()),
-        state.is_equivalent_to(&State {
-            eth_transactions: EthTransactions {
-                created_tx: Default::default(),
-                ..eth_transactions.clone()
-            },
-            ..state.clone()
-        }),
-        "changing the transactions should break equivalence"
-    );
-
-    assert_ne!(
-        Ok(()),
-        state.is_equivalent_to(&State {
-            eth_transactions: EthTransactions {
-                finalized_tx: Default::default(),
-                ..eth_transactions.clone()
-            },
-            ..state.clone()
-        }),
-        "changing the transactions should break equivalence"
-    );
-
-    assert_ne!(
-        Ok(()),
-        state.is_equivalent_to(&State {
-            eth_transactions: EthTransactions {
-                maybe_reimburse: Default::default(),
-                ..eth_transactions.clone()
-            },
-            ..state.clone()
-        }),
-        "changing the reimbursement data should break equivalence"
-    );
-
-    assert_ne!(
-        Ok(()),
-        state.is_equivalent_to(&State {
-            eth_transactions: EthTransactions {
-                reimbursement_requests: Default::default(),
-                ..eth_transactions.clone()
-            },
-            ..state.clone()
-        }),
-        "changing the reimbursement data should break equivalence"
-    );
-
-    assert_ne!(
-        Ok(()),
-        state.is_equivalent_to(&State {
-            eth_transactions: EthTransactions {
-                reimbursed: Default::default(),
-                ..eth_transactions.clone()
-            },
-            ..state.clone()
-        }),
-        "changing the reimbursement data should break equivalence"
-    );
-
     assert_ne!(
         Ok(()),
         state.is_equivalent_to(&State {
-            eth_transactions: EthTransactions {
-                next_nonce: TransactionNonce::new(1000),
-                ..eth_transactions.clone()
-            },
+            owners: Default::default(),
             ..state.clone()
         }),
-        "changing the next nonce should break equivalence"
+        "changing essential fields should break equivalence",
     );
 }
-
-mod eth_balance {
-    use crate::eth_rpc_client::responses::{TransactionReceipt, TransactionStatus};
-    use crate::lifecycle::EthereumNetwork;
-    use crate::numeric::{
-        BlockNumber, GasAmount, LedgerBurnIndex, TransactionNonce, Wei, WeiPerGas,
-    };
-    use crate::state::audit::{apply_state_transition, EventType};
-    use crate::state::tests::{a_state, received_eth_event};
-    use crate::state::transactions::EthWithdrawalRequest;
-    use crate::state::{EthBalance, State};
-    use crate::tx::{
-        Eip1559Signature, Eip1559TransactionRequest, SignedEip1559TransactionRequest,
-        TransactionPrice,
-    };
-
-    #[test]
-    // fn should_add_deposit_to_eth_balance() {
-    //     let mut state = a_state();
-    //     let balance_before = state.eth_balance.clone();
-
-    //     let deposit_event = received_eth_event();
-    //     apply_state_transition(
-    //         &mut state,
-    //         &EventType::AcceptedTransfer(deposit_event.clone()),
-    //     );
-    //     let balance_after = state.eth_balance.clone();
-
-    //     assert_eq!(
-    //         balance_after,
-    //         EthBalance {
-    //             eth_balance: deposit_event.value,
-    //             ..balance_before
-    //         }
-    //     )
-    // }
-    #[test]
-    fn should_ignore_rejected_deposit() {
-        let mut state = a_state();
-        let balance_before = state.eth_balance.clone();
-
-        let deposit_event = received_eth_event();
-        apply_state_transition(
-            &mut state,
-            &EventType::InvalidTransfer {
-                event_source: deposit_event.source(),
-                reason: "invalid principal".to_string(),
-            },
-        );
-        let balance_after = state.eth_balance.clone();
-
-        assert_eq!(balance_after, balance_before)
-    }
-
-    #[test]
-    fn should_update_after_successful_and_failed_withdrawal() {
-        let mut state_before_withdrawal = a_state();
-        apply_state_transition(
-            &mut state_before_withdrawal,
-            &EventType::AcceptedMint(received_eth_event()),
-        );
-
-        let mut state_after_successful_withdrawal = state_before_withdrawal.clone();
-        let balance_before_withdrawal = state_after_successful_withdrawal.eth_balance.clone();
-        //Values from https://sepolia.etherscan.io/tx/0xef628b8f45984bdf386f5b765b665a2e584295e1190d21c6acdfabe17c27e1bb
-        let withdrawal_flow = WithdrawalFlow {
-            withdrawal_amount: Wei::new(10_000_000_000_000_000),
-            tx_fee: TransactionPrice {
-                gas_limit: GasAmount::from(21_000_u32),
-                max_fee_per_gas: WeiPerGas::from(7_828_365_474_u64),
-                max_priority_fee_per_gas: WeiPerGas::from(1_500_000_000_u64),
-            },
-            effective_gas_price: WeiPerGas::from(0x1176e9eb9_u64),
-            tx_status: TransactionStatus::Success,
-            ..Default::default()
-        };
-        withdrawal_flow
-            .clone()
-            .apply(&mut state_after_successful_withdrawal);
-        let balance_after_successful_withdrawal =
-            state_after_successful_withdrawal.eth_balance.clone();
-
-        assert_eq!(
-            balance_after_successful_withdrawal,
-            EthBalance {
-                eth_balance: balance_before_withdrawal
-                    .eth_balance
-                    .checked_sub(Wei::from(9_934_054_275_043_000_u64))
-                    .unwrap(),
-                total_effective_tx_fees: balance_before_withdrawal
-                    .total_effective_tx_fees
-                    .checked_add(Wei::from(98_449_949_997_000_u64))
-                    .unwrap(),
-                total_unspent_tx_fees: balance_before_withdrawal
-                    .total_unspent_tx_fees
-                    .checked_add(Wei::from(65_945_724_957_000_u64))
-                    .unwrap()
-            }
-        );
-
-        let mut state_after_failed_withdrawal = state_before_withdrawal.clone();
-        let receipt_failed = WithdrawalFlow {
-            tx_status: TransactionStatus::Failure,
-            ..withdrawal_flow
-        }
-        .apply(&mut state_after_failed_withdrawal);
-        let balance_after_failed_withdrawal = state_after_failed_withdrawal.eth_balance.clone();
-
-        assert_eq!(
-            balance_after_failed_withdrawal.eth_balance,
-            balance_before_withdrawal
-                .eth_balance
-                .checked_sub(receipt_failed.effective_transaction_fee())
-                .unwrap()
-        );
-        assert_eq!(
-            balance_after_successful_withdrawal.total_effective_tx_fees,
-            balance_after_failed_withdrawal.total_effective_tx_fees
-        );
-        assert_eq!(
-            balance_after_successful_withdrawal.total_unspent_tx_fees,
-            balance_after_failed_withdrawal.total_unspent_tx_fees()
-        );
-    }
-
-    #[derive(Clone)]
-    struct WithdrawalFlow {
-        ledger_burn_index: LedgerBurnIndex,
-        nonce: TransactionNonce,
-        withdrawal_amount: Wei,
-        tx_fee: TransactionPrice,
-        effective_gas_price: WeiPerGas,
-        tx_status: TransactionStatus,
-    }
-
-    impl Default for WithdrawalFlow {
-        fn default() -> Self {
-            Self {
-                ledger_burn_index: LedgerBurnIndex::new(0),
-                nonce: TransactionNonce::ZERO,
-                withdrawal_amount: Wei::ONE,
-                tx_fee: TransactionPrice {
-                    gas_limit: GasAmount::from(21_000_u32),
-                    max_fee_per_gas: WeiPerGas::ONE,
-                    max_priority_fee_per_gas: WeiPerGas::ONE,
-                },
-                effective_gas_price: WeiPerGas::ONE,
-                tx_status: TransactionStatus::Success,
-            }
-        }
-    }
-
-    impl WithdrawalFlow {
-        fn apply(self, state: &mut State) -> TransactionReceipt {
-            let withdrawal_request = EthWithdrawalRequest {
-                withdrawal_amount: self.withdrawal_amount,
-                destination: "0xb44B5e756A894775FC32EDdf3314Bb1B1944dC34"
-                    .parse()
-                    .unwrap(),
-                ledger_burn_index: self.ledger_burn_index,
-                from: "k2t6j-2nvnp-4zjm3-25dtz-6xhaa-c7boj-5gayf-oj3xs-i43lp-teztq-6ae"
-                    .parse()
-                    .unwrap(),
-                from_subaccount: None,
-                created_at: Some(1699527697000000000),
-            };
-            apply_state_transition(
-                state,
-                &EventType::AcceptedEthWithdrawalRequest(withdrawal_request.clone()),
-            );
-
-            let max_fee = self.tx_fee.max_transaction_fee();
-            let transaction = Eip1559TransactionRequest {
-                chain_id: EthereumNetwork::Sepolia.chain_id(),
-                nonce: self.nonce,
-                max_priority_fee_per_gas: self.tx_fee.max_priority_fee_per_gas,
-                max_fee_per_gas: self.tx_fee.max_fee_per_gas,
-                gas_limit: self.tx_fee.gas_limit,
-                destination: withdrawal_request.destination,
-                amount: withdrawal_request
-                    .withdrawal_amount
-                    .checked_sub(max_fee)
-                    .unwrap(),
-                data: vec![],
-                access_list: Default::default(),
-            };
-            apply_state_transition(
-                state,
-                &EventType::CreatedTransaction {
-                    withdrawal_id: self.ledger_burn_index,
-                    transaction: transaction.clone(),
-                },
-            );
-
-            let dummy_signature = Eip1559Signature {
-                signature_y_parity: false,
-                r: Default::default(),
-                s: Default::default(),
-            };
-            let signed_tx =
-                SignedEip1559TransactionRequest::from((transaction.clone(), dummy_signature));
-            apply_state_transition(
-                state,
-                &EventType::SignedTransaction {
-                    withdrawal_id: self.ledger_burn_index,
-                    transaction: signed_tx.clone(),
-                },
-            );
-
-            let tx_receipt = TransactionReceipt {
-                block_hash: "0xce67a85c9fb8bc50213815c32814c159fd75160acf7cb8631e8e7b7cf7f1d472"
-                    .parse()
-                    .unwrap(),
-                block_number: BlockNumber::new(4190269),
-                effective_gas_price: self.effective_gas_price,
-                gas_used: signed_tx.transaction().gas_limit,
-                status: self.tx_status,
-                transaction_hash: signed_tx.hash(),
-            };
-            apply_state_transition(
-                state,
-                &EventType::FinalizedTransaction {
-                    withdrawal_id: self.ledger_burn_index,
-                    transaction_receipt: tx_receipt.clone(),
-                },
-            );
-            tx_receipt
-        }
-    }
-}
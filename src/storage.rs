@@ -10,9 +10,12 @@ use std::cell::RefCell;
 
 const LOG_INDEX_MEMORY_ID: MemoryId = MemoryId::new(0);
 const LOG_DATA_MEMORY_ID: MemoryId = MemoryId::new(1);
+const ASSET_INDEX_MEMORY_ID: MemoryId = MemoryId::new(2);
+const ASSET_DATA_MEMORY_ID: MemoryId = MemoryId::new(3);
 
 type VMem = VirtualMemory<DefaultMemoryImpl>;
 type EventLog = StableLog<Event, VMem, VMem>;
+type AssetLog = StableLog<Vec<u8>, VMem, VMem>;
 
 impl Storable for Event {
     fn to_bytes(&self) -> Cow<[u8]> {
@@ -42,6 +45,17 @@ thread_local! {
                   ).expect("failed to initialize stable log")
               )
         );
+
+    /// The contents of the committed assets.
+    static ASSETS: RefCell<AssetLog> = MEMORY_MANAGER
+        .with(|m|
+              RefCell::new(
+                  StableLog::init(
+                      m.borrow().get(ASSET_INDEX_MEMORY_ID),
+                      m.borrow().get(ASSET_DATA_MEMORY_ID)
+                  ).expect("failed to initialize stable asset log")
+              )
+        );
 }
 
 /// Appends the event to the event log.
@@ -67,3 +81,20 @@ where
 {
     EVENTS.with(|events| f(Box::new(events.borrow().iter())))
 }
+
+/// Appends the content of an asset to the asset log and returns its index.
+pub fn record_asset_content(content: Vec<u8>) -> u64 {
+    ASSETS
+        .with(|assets| assets.borrow().append(&content))
+        .expect("recording an asset should succeed")
+}
+
+/// Returns the content of the asset at the given index in the asset log.
+pub fn asset_content(index: u64) -> Option<Vec<u8>> {
+    ASSETS.with(|assets| assets.borrow().get(index))
+}
+
+/// Returns the total number of asset contents in the asset log.
+pub fn total_asset_content_count() -> u64 {
+    ASSETS.with(|assets| assets.borrow().len())
+}
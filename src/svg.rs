@@ -0,0 +1,218 @@
+//! On-chain rendering of token images.
+//!
+//! The image of a token is an SVG document stacking, in the order of the trait types,
+//! the layer of each trait value assigned to the token.
+//! A layer is either an SVG fragment, or an uploaded image asset embedded as a data URI.
+//! Rendering only concatenates layers, so that it is deterministic
+//! and its cost is bounded by the sizes enforced in [`validate_layers`],
+//! independently of the number of tokens in the collection.
+//! Images are rendered on demand from the current configuration and assets,
+//! so changes that would alter the image of a minted token are rejected, see [`check_unchanged_images`].
+
+#[cfg(test)]
+mod tests;
+
+use crate::assets::Asset;
+use crate::collections::TokenKey;
+use crate::generator::{Attribute, GeneratorConfig, TraitValue};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use candid::{CandidType, Deserialize};
+use minicbor::{Decode, Encode};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+/// The maximum size in bytes of a single SVG fragment layer.
+pub const MAX_LAYER_SIZE: usize = 64 * 1024;
+
+/// The maximum size in bytes of the layers of a single image,
+/// which keeps rendering within the instruction limit of a query.
+pub const MAX_IMAGE_LAYERS_SIZE: usize = 1024 * 1024;
+
+/// The canvas size used when the configuration does not specify any.
+pub const DEFAULT_CANVAS: Canvas = Canvas {
+    width: 1024,
+    height: 1024,
+};
+
+/// The dimensions of the rendered images, in SVG user units.
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, Encode, Decode, PartialEq, Eq)]
+pub struct Canvas {
+    #[n(0)]
+    pub width: u32,
+    #[n(1)]
+    pub height: u32,
+}
+
+/// Checks that the layers of the configuration can be rendered with the given assets.
+pub fn validate_layers(
+    config: &GeneratorConfig,
+    assets: &BTreeMap<String, Asset>,
+) -> Result<(), String> {
+    if let Some(canvas) = config.canvas {
+        if canvas.width == 0 || canvas.height == 0 {
+            return Err("canvas dimensions must be positive".to_string());
+        }
+    }
+    let mut image_layers_size = 0;
+    for trait_type in &config.trait_types {
+        let mut largest_layer_size = 0;
+        for value in &trait_type.values {
+            let layer_size = match (&value.layer, &value.asset) {
+                (None, None) => 0,
+                (Some(layer), None) => {
+                    if layer.len() > MAX_LAYER_SIZE {
+                        return Err(format!(
+                            "layer of value '{}' for trait type '{}' is {} bytes, exceeding the maximum of {MAX_LAYER_SIZE} bytes",
+                            value.name, trait_type.name, layer.len()
+                        ));
+                    }
+                    layer.len()
+                }
+                (None, Some(key)) => {
+                    let asset = assets.get(key).ok_or_else(|| {
+                        format!(
+                            "value '{}' for trait type '{}' refers to unknown asset '{key}'",
+                            value.name, trait_type.name
+                        )
+                    })?;
+                    if !asset.content_type.starts_with("image/") {
+                        return Err(format!(
+                            "asset '{key}' of content type '{}' cannot be used as a layer",
+                            asset.content_type
+                        ));
+                    }
+                    embedded_asset_size(asset)
+                }
+                (Some(_), Some(_)) => {
+                    return Err(format!(
+                        "value '{}' for trait type '{}' cannot have both a layer and an asset",
+                        value.name, trait_type.name
+                    ))
+                }
+            };
+            largest_layer_size = largest_layer_size.max(layer_size);
+        }
+        image_layers_size += largest_layer_size;
+    }
+    if image_layers_size > MAX_IMAGE_LAYERS_SIZE {
+        return Err(format!(
+            "an image can have up to {image_layers_size} bytes of layers, exceeding the maximum of {MAX_IMAGE_LAYERS_SIZE} bytes"
+        ));
+    }
+    Ok(())
+}
+
+/// Renders the image of a token with the given attributes.
+///
+/// Attributes that don't match any layer of the configuration are not drawn.
+pub fn render_svg(
+    config: &GeneratorConfig,
+    assets: &BTreeMap<String, Asset>,
+    attributes: &[Attribute],
+) -> String {
+    let canvas = config.canvas.unwrap_or(DEFAULT_CANVAS);
+    let mut svg = open_svg(canvas);
+    for value in drawn_layers(config, attributes) {
+        render_layer(&mut svg, value, assets, canvas);
+    }
+    svg.push_str("</svg>");
+    svg
+}
+
+/// Checks that the given tokens are drawn the same with the current and the updated configuration.
+pub fn check_unchanged_images<'a>(
+    current: &GeneratorConfig,
+    updated: &GeneratorConfig,
+    minted_tokens: impl IntoIterator<Item = (&'a TokenKey, &'a [Attribute])>,
+) -> Result<(), String> {
+    let layers = |config, attributes| {
+        drawn_layers(config, attributes)
+            .map(|value| (&value.layer, &value.asset))
+            .collect::<Vec<_>>()
+    };
+    for (token, attributes) in minted_tokens {
+        if attributes.is_empty() {
+            continue;
+        }
+        if current.canvas != updated.canvas
+            || layers(current, attributes) != layers(updated, attributes)
+        {
+            return Err(format!(
+                "the image of the minted token {token} would change"
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Returns whether the image of a token with the given attributes embeds the given asset.
+pub fn draws_asset(config: &GeneratorConfig, attributes: &[Attribute], key: &str) -> bool {
+    drawn_layers(config, attributes).any(|value| value.asset.as_deref() == Some(key))
+}
+
+/// Returns the values drawn on the image of a token with the given attributes, in stacking order.
+fn drawn_layers<'a>(
+    config: &'a GeneratorConfig,
+    attributes: &'a [Attribute],
+) -> impl Iterator<Item = &'a TraitValue> {
+    config.trait_types.iter().filter_map(|trait_type| {
+        attributes
+            .iter()
+            .find(|attribute| attribute.trait_type == trait_type.name)
+            .and_then(|attribute| {
+                trait_type
+                    .values
+                    .iter()
+                    .find(|value| value.name == attribute.value)
+            })
+    })
+}
+
+/// Renders the image served for every token of a hidden collection.
+pub fn render_placeholder(config: &GeneratorConfig, placeholder_layer: Option<&str>) -> String {
+    let mut svg = open_svg(config.canvas.unwrap_or(DEFAULT_CANVAS));
+    if let Some(layer) = placeholder_layer {
+        svg.push_str("<g>");
+        svg.push_str(layer);
+        svg.push_str("</g>");
+    }
+    svg.push_str("</svg>");
+    svg
+}
+
+fn open_svg(canvas: Canvas) -> String {
+    format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 {width} {height}" width="{width}" height="{height}">"#,
+        width = canvas.width,
+        height = canvas.height,
+    )
+}
+
+fn render_layer(
+    svg: &mut String,
+    value: &TraitValue,
+    assets: &BTreeMap<String, Asset>,
+    canvas: Canvas,
+) {
+    if let Some(layer) = &value.layer {
+        svg.push_str("<g>");
+        svg.push_str(layer);
+        svg.push_str("</g>");
+    }
+    if let Some(asset) = value.asset.as_ref().and_then(|key| assets.get(key)) {
+        write!(
+            svg,
+            r#"<image href="data:{};base64,{}" width="{}" height="{}"/>"#,
+            asset.content_type,
+            BASE64.encode(asset.content()),
+            canvas.width,
+            canvas.height,
+        )
+        .unwrap();
+    }
+}
+
+fn embedded_asset_size(asset: &Asset) -> usize {
+    (asset.size as usize).div_ceil(3) * 4 + asset.content_type.len()
+}
@@ -0,0 +1,265 @@
+use crate::assets::Asset;
+use crate::generator::{Attribute, GeneratorConfig, TraitType, TraitValue};
+use crate::svg::{
+    check_unchanged_images, draws_asset, render_svg, validate_layers, Canvas,
+    MAX_IMAGE_LAYERS_SIZE, MAX_LAYER_SIZE,
+};
+use std::collections::BTreeMap;
+
+mod render {
+    use super::*;
+
+    #[test]
+    fn should_stack_layers_in_trait_type_order() {
+        let attributes = vec![attribute("Eyes", "Round"), attribute("Background", "Blue")];
+
+        assert_eq!(
+            render_svg(&config(), &BTreeMap::new(), &attributes),
+            r##"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 100 50" width="100" height="50"><g><rect width="100" height="50" fill="#00f"/></g><g><circle cx="50" cy="25" r="5"/></g></svg>"##
+        );
+    }
+
+    #[test]
+    fn should_skip_values_without_layer() {
+        let attributes = vec![attribute("Background", "Gold"), attribute("Eyes", "Round")];
+
+        assert_eq!(
+            render_svg(&config(), &BTreeMap::new(), &attributes),
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 100 50" width="100" height="50"><g><circle cx="50" cy="25" r="5"/></g></svg>"#
+        );
+    }
+
+    #[test]
+    fn should_embed_asset_layers() {
+        let mut config = config();
+        config.trait_types[0].values[1].asset = Some("gold.png".to_string());
+        let assets = assets("gold.png", "image/png", b"gold");
+        let attributes = vec![attribute("Background", "Gold")];
+
+        assert_eq!(
+            render_svg(&config, &assets, &attributes),
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 100 50" width="100" height="50"><image href="data:image/png;base64,Z29sZA==" width="100" height="50"/></svg>"#
+        );
+    }
+
+    #[test]
+    fn should_render_empty_canvas_without_config() {
+        assert_eq!(
+            render_svg(
+                &GeneratorConfig::default(),
+                &BTreeMap::new(),
+                &[attribute("Eyes", "Round")]
+            ),
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 1024 1024" width="1024" height="1024"></svg>"#
+        );
+    }
+}
+
+mod validate {
+    use super::*;
+
+    #[test]
+    fn should_accept_valid_layers() {
+        assert_eq!(validate_layers(&config(), &BTreeMap::new()), Ok(()));
+    }
+
+    #[test]
+    fn should_reject_empty_canvas() {
+        let mut config = config();
+        config.canvas = Some(Canvas {
+            width: 0,
+            height: 50,
+        });
+
+        assert!(validate_layers(&config, &BTreeMap::new())
+            .unwrap_err()
+            .contains("canvas"));
+    }
+
+    #[test]
+    fn should_reject_too_large_layer() {
+        let mut config = config();
+        config.trait_types[0].values[0].layer = Some("x".repeat(MAX_LAYER_SIZE + 1));
+
+        assert!(validate_layers(&config, &BTreeMap::new())
+            .unwrap_err()
+            .contains("exceeding the maximum"));
+    }
+
+    #[test]
+    fn should_reject_too_large_images() {
+        let trait_types = (0..=MAX_IMAGE_LAYERS_SIZE / MAX_LAYER_SIZE)
+            .map(|i| TraitType {
+                name: format!("Trait {i}"),
+                values: vec![value("Value", Some(&"x".repeat(MAX_LAYER_SIZE)))],
+            })
+            .collect();
+        let config = GeneratorConfig {
+            trait_types,
+            canvas: None,
+        };
+
+        assert!(validate_layers(&config, &BTreeMap::new())
+            .unwrap_err()
+            .contains("an image can have up to"));
+    }
+
+    #[test]
+    fn should_reject_unknown_assets() {
+        let mut config = config();
+        config.trait_types[0].values[1].asset = Some("gold.png".to_string());
+
+        assert!(validate_layers(&config, &BTreeMap::new())
+            .unwrap_err()
+            .contains("unknown asset 'gold.png'"));
+    }
+
+    #[test]
+    fn should_reject_non_image_assets() {
+        let mut config = config();
+        config.trait_types[0].values[1].asset = Some("font.woff2".to_string());
+        let assets = assets("font.woff2", "font/woff2", b"font");
+
+        assert!(validate_layers(&config, &assets)
+            .unwrap_err()
+            .contains("cannot be used as a layer"));
+    }
+
+    #[test]
+    fn should_reject_values_with_layer_and_asset() {
+        let mut config = config();
+        config.trait_types[0].values[0].asset = Some("gold.png".to_string());
+        let assets = assets("gold.png", "image/png", b"gold");
+
+        assert!(validate_layers(&config, &assets)
+            .unwrap_err()
+            .contains("cannot have both a layer and an asset"));
+    }
+}
+
+mod unchanged_images {
+    use super::*;
+    use crate::collections::TokenKey;
+    use ethnum::u256;
+
+    #[test]
+    fn should_accept_changes_to_values_not_drawn_on_minted_tokens() {
+        let attributes = vec![attribute("Background", "Blue"), attribute("Eyes", "Round")];
+        let mut updated = config();
+        updated.trait_types[0].values[0].weight = 10;
+        updated.trait_types[0].values[1].layer = Some("<rect/>".to_string());
+        updated.trait_types[1]
+            .values
+            .push(value("Square", Some("<rect/>")));
+
+        assert_eq!(
+            check_unchanged_images(&config(), &updated, [(&token(), attributes.as_slice())]),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn should_reject_changes_to_values_drawn_on_minted_tokens() {
+        let attributes = vec![attribute("Background", "Blue"), attribute("Eyes", "Round")];
+        let mut changed_layer = config();
+        changed_layer.trait_types[1].values[0].layer = Some("<rect/>".to_string());
+        let mut removed_value = config();
+        removed_value.trait_types[1].values.clear();
+        let mut reordered = config();
+        reordered.trait_types.reverse();
+        let mut resized = config();
+        resized.canvas = None;
+
+        for updated in [changed_layer, removed_value, reordered, resized] {
+            assert_eq!(
+                check_unchanged_images(&config(), &updated, [(&token(), attributes.as_slice())]),
+                Err("the image of the minted token 7 would change".to_string())
+            );
+        }
+    }
+
+    #[test]
+    fn should_ignore_tokens_without_traits() {
+        let mut updated = config();
+        updated.trait_types.clear();
+        updated.canvas = None;
+
+        assert_eq!(
+            check_unchanged_images(&config(), &updated, [(&token(), [].as_slice())]),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn should_find_drawn_assets() {
+        let mut config = config();
+        config.trait_types[0].values[1].asset = Some("gold.png".to_string());
+
+        assert!(draws_asset(
+            &config,
+            &[attribute("Background", "Gold")],
+            "gold.png"
+        ));
+        assert!(!draws_asset(
+            &config,
+            &[attribute("Background", "Blue")],
+            "gold.png"
+        ));
+    }
+
+    fn token() -> TokenKey {
+        TokenKey::from(u256::from(7_u8))
+    }
+}
+
+fn config() -> GeneratorConfig {
+    GeneratorConfig {
+        trait_types: vec![
+            TraitType {
+                name: "Background".to_string(),
+                values: vec![
+                    value(
+                        "Blue",
+                        Some(r##"<rect width="100" height="50" fill="#00f"/>"##),
+                    ),
+                    value("Gold", None),
+                ],
+            },
+            TraitType {
+                name: "Eyes".to_string(),
+                values: vec![value("Round", Some(r#"<circle cx="50" cy="25" r="5"/>"#))],
+            },
+        ],
+        canvas: Some(Canvas {
+            width: 100,
+            height: 50,
+        }),
+    }
+}
+
+fn value(name: &str, layer: Option<&str>) -> TraitValue {
+    TraitValue {
+        name: name.to_string(),
+        weight: 1,
+        layer: layer.map(str::to_string),
+        asset: None,
+    }
+}
+
+fn assets(key: &str, content_type: &str, content: &[u8]) -> BTreeMap<String, Asset> {
+    let asset = Asset {
+        key: key.to_string(),
+        content_type: content_type.to_string(),
+        sha256: ic_crypto_sha2::Sha256::hash(content),
+        size: content.len() as u64,
+        content_index: crate::storage::record_asset_content(content.to_vec()),
+    };
+    BTreeMap::from([(key.to_string(), asset)])
+}
+
+fn attribute(trait_type: &str, value: &str) -> Attribute {
+    Attribute {
+        trait_type: trait_type.to_string(),
+        value: value.to_string(),
+    }
+}
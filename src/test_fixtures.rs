@@ -0,0 +1,70 @@
+//! Fixtures shared by the unit tests of the different modules.
+
+use crate::address::Address;
+use crate::eth_logs::{MintEvent, TransferEvent};
+use crate::lifecycle::init::InitArg;
+use crate::numeric::{BlockNumber, LogIndex};
+use crate::state::State;
+use ethnum::u256;
+
+pub const CONTRACT: &str = "0xb44B5e756A894775FC32EDdf3314Bb1B1944dC34";
+pub const HOLDER: &str = "0xdd2851cdd40ae6536831558dd46db62fac7a844d";
+const TRANSACTION_HASH: &str = "0xf1ac37d920fa57d9caeebc7136fea591191250309ffca95ae0e8a7739de89cc2";
+const BLOCK_NUMBER: u128 = 3960623;
+
+/// Arguments initializing a minter for the ERC-721 contract [`CONTRACT`],
+/// without generator, metadata or reveal.
+pub fn init_arg() -> InitArg {
+    InitArg {
+        ethereum_network: Default::default(),
+        minter_address: None,
+        ethereum_contract_address: CONTRACT.to_string(),
+        ethereum_block_height: Default::default(),
+        last_scraped_block_number: Default::default(),
+        generator_config: None,
+        collection_metadata: None,
+        reveal_config: None,
+        scraping_mode: None,
+        token_standard: None,
+    }
+}
+
+pub fn state() -> State {
+    State::try_from(init_arg()).expect("init args should be valid")
+}
+
+pub fn holder() -> Address {
+    HOLDER.parse().unwrap()
+}
+
+/// Mint of `token_id` to [`HOLDER`] by the log `log_index` of a transaction in block 3960623.
+pub fn mint_event(log_index: u64, token_id: u64) -> MintEvent {
+    MintEvent {
+        transaction_hash: TRANSACTION_HASH.parse().unwrap(),
+        block_number: BlockNumber::new(BLOCK_NUMBER),
+        log_index: LogIndex::from(log_index),
+        from_address: Address::ZERO,
+        to_address: holder(),
+        token_id: u256::from(token_id),
+        batch_index: None,
+        value: None,
+        collection: None,
+        block_hash: None,
+    }
+}
+
+/// Transfer of `token_id` by the log `log_index` of the same transaction as [`mint_event`].
+pub fn transfer_event(log_index: u64, from: Address, to: Address, token_id: u64) -> TransferEvent {
+    TransferEvent {
+        transaction_hash: TRANSACTION_HASH.parse().unwrap(),
+        block_number: BlockNumber::new(BLOCK_NUMBER),
+        log_index: LogIndex::from(log_index),
+        from_address: from,
+        to_address: to,
+        token_id: u256::from(token_id),
+        batch_index: None,
+        value: None,
+        collection: None,
+        block_hash: None,
+    }
+}
@@ -50,11 +50,11 @@ fn deserialize_json_reply() {
 
 mod eth_get_logs {
     use crate::address::Address;
-    use crate::eth_logs::MintEvent;
+    use crate::eth_logs::TransferEvent;
     use crate::eth_rpc::{FixedSizeData, LogEntry};
-    use crate::numeric::{BlockNumber, LogIndex, Wei, TokenId};
+    use crate::numeric::{BlockNumber, LogIndex};
     use assert_matches::assert_matches;
-    use candid::Principal;
+    use ethnum::u256;
     use ic_crypto_sha3::Keccak256;
     use std::str::FromStr;
 
@@ -100,16 +100,16 @@ mod eth_get_logs {
 
     #[test]
     fn should_have_correct_topic() {
-        use crate::eth_logs::RECEIVED_ETH_EVENT_TOPIC;
+        use crate::eth_logs::TRANSFER_EVENT_TOPIC;
 
-        //must match event signature in minter.sol
-        let event_signature = "ReceivedEth(address,uint256,bytes32)";
+        //must match the event signature of ERC-721 contracts
+        let event_signature = "Transfer(address,address,uint256)";
         let topic = Keccak256::hash(event_signature);
-        assert_eq!(topic, RECEIVED_ETH_EVENT_TOPIC)
+        assert_eq!(topic, TRANSFER_EVENT_TOPIC)
     }
 
     #[test]
-    fn should_parse_received_eth_event() {
+    fn should_parse_transfer_event() {
         let event = r#"{
             "address": "0xb44b5e756a894775fc32eddf3314bb1b1944dc34",
             "topics": [
@@ -127,8 +127,8 @@ mod eth_get_logs {
             "removed": false
         }"#;
         let parsed_event =
-            MintEvent::try_from(serde_json::from_str::<LogEntry>(event).unwrap()).unwrap();
-        let expected_event = MintEvent {
+            TransferEvent::try_from(serde_json::from_str::<LogEntry>(event).unwrap()).unwrap();
+        let expected_event = TransferEvent {
             transaction_hash: "0x705f826861c802b407843e99af986cfde8749b669e5e0a5a150f4350bcaa9bc3"
                 .parse()
                 .unwrap(),
@@ -140,7 +140,15 @@ mod eth_get_logs {
             to_address: "0x29469395eaf6f95920e59f858042f0e28d98a20b"
                 .parse()
                 .unwrap(),
-            token_id: TokenId::from(721u64),
+            token_id: u256::from(0x1c09_u16),
+            batch_index: None,
+            value: None,
+            collection: None,
+            block_hash: Some(
+                "0x8436209a391f7bc076123616ecb229602124eb6c1007f5eae84df8e098885d3c"
+                    .parse()
+                    .unwrap(),
+            ),
         };
 
         assert_eq!(parsed_event, expected_event);
@@ -166,7 +174,7 @@ mod eth_get_logs {
         }"#;
 
         let parsed_event =
-            MintEvent::try_from(serde_json::from_str::<LogEntry>(event).unwrap());
+            TransferEvent::try_from(serde_json::from_str::<LogEntry>(event).unwrap());
         let expected_error = Err(TransferEventError::InvalidEventSource {
             source: EventSource {
                 transaction_hash:
@@ -174,7 +182,10 @@ mod eth_get_logs {
                         .parse()
                         .unwrap(),
                 log_index: LogIndex::from(39_u8),
+                batch_index: None,
             },
+            token_id: None,
+            block_number: BlockNumber::new(0x3ca487),
             error: EventSourceError::InvalidEvent(
                 "this event has been removed from the chain".to_string(),
             ),
@@ -270,142 +281,6 @@ fn address_display() {
     }
 }
 
-mod rlp_encoding {
-    use crate::address::Address;
-    use crate::numeric::{GasAmount, TransactionNonce, Wei, WeiPerGas};
-    use crate::tx::{
-        AccessList, Eip1559Signature, Eip1559TransactionRequest, SignedEip1559TransactionRequest,
-    };
-    use ethnum::u256;
-    use rlp::Encodable;
-    use std::str::FromStr;
-
-    const SEPOLIA_TEST_CHAIN_ID: u64 = 11155111;
-
-    #[test]
-    fn test_rlp_encoding() {
-        use crate::tx::{AccessList, Eip1559TransactionRequest};
-        use ethers_core::abi::ethereum_types::H160;
-        use ethers_core::types::transaction::eip1559::Eip1559TransactionRequest as EthersCoreEip1559TransactionRequest;
-        use ethers_core::types::transaction::eip2930::AccessList as EthersCoreAccessList;
-        use ethers_core::types::Signature as EthersCoreSignature;
-        use ethers_core::types::{Bytes, U256};
-        use ethnum::u256;
-
-        let address_bytes: [u8; 20] = [
-            180, 75, 94, 117, 106, 137, 71, 117, 252, 50, 237, 223, 51, 20, 187, 27, 25, 68, 220,
-            52,
-        ];
-
-        let ethers_core_tx = EthersCoreEip1559TransactionRequest {
-            from: None,
-            to: Some(ethers_core::types::NameOrAddress::Address(H160::from(
-                address_bytes,
-            ))),
-            gas: Some(1.into()),
-            value: Some(2.into()),
-            data: Some(Bytes::new()),
-            nonce: Some(0.into()),
-            access_list: EthersCoreAccessList::from(vec![]),
-            max_priority_fee_per_gas: Some(3.into()),
-            max_fee_per_gas: Some(4.into()),
-            chain_id: Some(1.into()),
-        };
-        let minter_tx = Eip1559TransactionRequest {
-            chain_id: 1,
-            destination: Address::new(address_bytes),
-            nonce: 0_u64.into(),
-            gas_limit: 1_u32.into(),
-            max_fee_per_gas: 4_u64.into(),
-            amount: 2_u64.into(),
-            data: vec![],
-            access_list: AccessList::new(),
-            max_priority_fee_per_gas: 3_u64.into(),
-        };
-        assert_eq!(
-            minter_tx.rlp_bytes().to_vec(),
-            ethers_core_tx.rlp().to_vec()
-        );
-
-        let signature = Eip1559Signature {
-            signature_y_parity: true,
-            r: u256::from_str_radix(
-                "b92224ecdb5295f3b889059621909c6b7a2308ccd0e5f13812409d80706b13cd",
-                16,
-            )
-            .unwrap(),
-            s: u256::from_str_radix(
-                "0bec9da278e6388a9d6934c911684234e16db1610c2227545c7b192db277c4b1",
-                16,
-            )
-            .unwrap(),
-        };
-
-        assert_eq!(
-            SignedEip1559TransactionRequest::from((minter_tx, signature))
-                .rlp_bytes()
-                .to_vec(),
-            ethers_core_tx
-                .rlp_signed(&EthersCoreSignature {
-                    v: 1,
-                    r: U256::from_str_radix(
-                        "b92224ecdb5295f3b889059621909c6b7a2308ccd0e5f13812409d80706b13cd",
-                        16
-                    )
-                    .unwrap(),
-                    s: U256::from_str_radix(
-                        "0bec9da278e6388a9d6934c911684234e16db1610c2227545c7b192db277c4b1",
-                        16
-                    )
-                    .unwrap(),
-                })
-                .to_vec()
-        );
-    }
-
-    #[test]
-    fn should_compute_correct_rlp_encoding_of_signed_transaction() {
-        // see https://sepolia.etherscan.io/getRawTx?tx=0x66a9a218ea720ac6d2c9e56f7e44836c1541c186b7627bda220857ce34e2df7f
-        let signature = Eip1559Signature {
-            signature_y_parity: true,
-            r: u256::from_str_hex(
-                "0x7d097b81dc8bf5ad313f8d6656146d4723d0e6bb3fb35f1a709e6a3d4426c0f3",
-            )
-            .unwrap(),
-            s: u256::from_str_hex(
-                "0x4f8a618d959e7d96e19156f0f5f2ed321b34e2004a0c8fdb7f02bc7d08b74441",
-            )
-            .unwrap(),
-        };
-        let transaction = Eip1559TransactionRequest {
-            chain_id: SEPOLIA_TEST_CHAIN_ID,
-            nonce: TransactionNonce::from(6_u8),
-            max_priority_fee_per_gas: WeiPerGas::new(0x59682f00),
-            max_fee_per_gas: WeiPerGas::new(0x598653cd),
-            gas_limit: GasAmount::new(56_511),
-            destination: Address::from_str("0xb44B5e756A894775FC32EDdf3314Bb1B1944dC34").unwrap(),
-            amount: Wei::new(1_000_000_000_000_000),
-            data: hex::decode(
-                "b214faa51d882d15b09f8e81e29606305f5fefc5eff3e2309620a3557ecae39d62020000",
-            )
-            .unwrap(),
-            access_list: AccessList::new(),
-        };
-        let tx_hash = transaction.hash();
-        assert_eq!(
-            tx_hash.to_string(),
-            "0x2d9e6453d9864cff7453ca35dcab86be744c641ba4891c2fe9aeaa2f767b9758"
-        );
-
-        let signed_transaction = SignedEip1559TransactionRequest::from((transaction, signature));
-        assert_eq!(signed_transaction.raw_transaction_hex(), "0x02f89883aa36a7068459682f0084598653cd82dcbf94b44b5e756a894775fc32eddf3314bb1b1944dc3487038d7ea4c68000a4b214faa51d882d15b09f8e81e29606305f5fefc5eff3e2309620a3557ecae39d62020000c001a07d097b81dc8bf5ad313f8d6656146d4723d0e6bb3fb35f1a709e6a3d4426c0f3a04f8a618d959e7d96e19156f0f5f2ed321b34e2004a0c8fdb7f02bc7d08b74441");
-        assert_eq!(
-            signed_transaction.hash().to_string(),
-            "0x66a9a218ea720ac6d2c9e56f7e44836c1541c186b7627bda220857ce34e2df7f"
-        );
-    }
-}
-
 mod eth_get_block_by_number {
     use crate::eth_rpc::{into_nat, Block, BlockSpec, BlockTag, GetBlockByNumberParams, Quantity};
     use crate::numeric::{BlockNumber, Wei};
@@ -747,6 +622,9 @@ mod eth_get_block_by_number {
             Block {
                 number: BlockNumber::new(0x10eb3c6),
                 base_fee_per_gas: Wei::new(0x4b85a0fcd),
+                hash: "0x85db6d6ad071d127795df4c5f1b04863629d7c2832c89550aa2771bf81c40c85"
+                    .parse()
+                    .unwrap(),
             }
         )
     }
@@ -758,112 +636,3 @@ mod eth_get_block_by_number {
         assert_eq!(nat.to_string(), "20_272_779_213")
     }
 }
-
-mod eth_fee_history {
-    use crate::eth_rpc::{BlockSpec, BlockTag, FeeHistory, FeeHistoryParams, Quantity};
-    use crate::numeric::{BlockNumber, WeiPerGas};
-
-    #[test]
-    fn should_serialize_fee_history_params_as_tuple() {
-        let params = FeeHistoryParams {
-            block_count: Quantity::from(5_u8),
-            highest_block: BlockSpec::Tag(BlockTag::Finalized),
-            reward_percentiles: vec![10, 20, 30],
-        };
-        let serialized_params = serde_json::to_string(&params).unwrap();
-        assert_eq!(serialized_params, r#"["0x5","finalized",[10,20,30]]"#);
-    }
-
-    #[test]
-    fn should_deserialize_eth_fee_history_response() {
-        const ETH_FEE_HISTORY: &str = r#"{
-        "baseFeePerGas": [
-            "0x729d3f3b3",
-            "0x766e503ea",
-            "0x75b51b620",
-            "0x74094f2b4",
-            "0x716724f03",
-            "0x73b467f76"
-        ],
-        "gasUsedRatio": [
-            0.6332004,
-            0.47556506666666665,
-            0.4432122666666667,
-            0.4092196,
-            0.5811903
-        ],
-        "oldestBlock": "0x10f73fc",
-        "reward": [
-            [
-                "0x5f5e100",
-                "0x5f5e100",
-                "0x68e7780"
-            ],
-            [
-                "0x55d4a80",
-                "0x5f5e100",
-                "0x5f5e100"
-            ],
-            [
-                "0x5f5e100",
-                "0x5f5e100",
-                "0x5f5e100"
-            ],
-            [
-                "0x5f5e100",
-                "0x5f5e100",
-                "0x5f5e100"
-            ],
-            [
-                "0x5f5e100",
-                "0x5f5e100",
-                "0x180789e0"
-            ]
-        ]
-    }"#;
-
-        let fee_history: FeeHistory = serde_json::from_str(ETH_FEE_HISTORY).unwrap();
-
-        assert_eq!(
-            fee_history,
-            FeeHistory {
-                oldest_block: BlockNumber::new(0x10f73fc),
-                base_fee_per_gas: vec![
-                    WeiPerGas::new(0x729d3f3b3),
-                    WeiPerGas::new(0x766e503ea),
-                    WeiPerGas::new(0x75b51b620),
-                    WeiPerGas::new(0x74094f2b4),
-                    WeiPerGas::new(0x716724f03),
-                    WeiPerGas::new(0x73b467f76)
-                ],
-                reward: vec![
-                    vec![
-                        WeiPerGas::new(0x5f5e100),
-                        WeiPerGas::new(0x5f5e100),
-                        WeiPerGas::new(0x68e7780)
-                    ],
-                    vec![
-                        WeiPerGas::new(0x55d4a80),
-                        WeiPerGas::new(0x5f5e100),
-                        WeiPerGas::new(0x5f5e100)
-                    ],
-                    vec![
-                        WeiPerGas::new(0x5f5e100),
-                        WeiPerGas::new(0x5f5e100),
-                        WeiPerGas::new(0x5f5e100)
-                    ],
-                    vec![
-                        WeiPerGas::new(0x5f5e100),
-                        WeiPerGas::new(0x5f5e100),
-                        WeiPerGas::new(0x5f5e100)
-                    ],
-                    vec![
-                        WeiPerGas::new(0x5f5e100),
-                        WeiPerGas::new(0x5f5e100),
-                        WeiPerGas::new(0x180789e0)
-                    ]
-                ],
-            }
-        )
-    }
-}